@@ -1,14 +1,27 @@
-use std::sync::Mutex;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
 
+use anyhow::format_err;
 use arci::*;
 use r2r::geometry_msgs::msg::Twist;
 use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use tracing::warn;
 
-use crate::Node;
+use crate::{utils, Node};
 
 /// `arci::MoveBase` implementation for ROS2.
 pub struct Ros2CmdVelMoveBase {
-    vel_publisher: Mutex<r2r::Publisher<Twist>>,
+    vel_publisher: Arc<Mutex<r2r::Publisher<Twist>>>,
+    enabled: Arc<AtomicBool>,
+    /// The latest velocity not yet published, the task publishing it, and the
+    /// publish period if the publish rate is limited.
+    rate_limiter: Option<(Arc<Mutex<Option<Twist>>>, JoinHandle<()>, Duration)>,
     // keep not to be dropped
     _node: Node,
 }
@@ -22,25 +35,128 @@ impl Ros2CmdVelMoveBase {
             .create_publisher(cmd_topic_name, r2r::QosProfile::default())
             .unwrap();
         Self {
-            vel_publisher: Mutex::new(vel_publisher),
+            vel_publisher: Arc::new(Mutex::new(vel_publisher)),
+            enabled: Arc::new(AtomicBool::new(true)),
+            rate_limiter: None,
             _node: node,
         }
     }
+
+    /// Sets the maximum frequency (in Hz) to publish velocities at.
+    ///
+    /// If `Some`, the velocities sent are published by a background task at
+    /// this frequency: only the latest one sent since the last publish is
+    /// published, and the intermediate ones are dropped. This decouples the
+    /// rate of the caller (e.g., a teleoperation loop) from the rate on the
+    /// wire. Zero velocities published while disabled are not limited, and
+    /// while disabled, the task drops the pending velocity and publishes zero
+    /// velocity instead. If `None` (default), every velocity is published
+    /// immediately.
+    ///
+    /// Returns an error and keeps the current rate if the rate is not finite
+    /// and positive, or too high to be represented as an interval.
+    pub fn set_max_publish_rate(&mut self, max_publish_rate_hz: Option<f64>) -> Result<(), Error> {
+        let period = match max_publish_rate_hz {
+            Some(rate) => match Duration::try_from_secs_f64(1.0 / rate) {
+                Ok(period) if rate.is_finite() && rate > 0.0 && !period.is_zero() => Some(period),
+                _ => {
+                    return Err(Error::Other(format_err!(
+                        "max_publish_rate_hz must be finite and positive: {rate}"
+                    )))
+                }
+            },
+            None => None,
+        };
+        if let Some((_, handle, _)) = self.rate_limiter.take() {
+            handle.abort();
+        }
+        if let Some(period) = period {
+            self.spawn_rate_limiter(period);
+        }
+        Ok(())
+    }
+
+    /// Spawns the task publishing the pending velocity every `period`.
+    fn spawn_rate_limiter(&mut self, period: Duration) {
+        let pending = Arc::new(Mutex::new(None::<Twist>));
+        let handle = utils::spawn({
+            let pending = pending.clone();
+            let vel_publisher = self.vel_publisher.clone();
+            let enabled = self.enabled.clone();
+            async move {
+                let mut interval = tokio::time::interval(period);
+                interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                loop {
+                    interval.tick().await;
+                    // Check the flag and publish under the publisher lock, so
+                    // that a stale velocity is never published after the zero
+                    // velocity published by a disabled `send_velocity`.
+                    let vel_publisher = vel_publisher.lock().unwrap();
+                    let twist_msg = if enabled.load(Ordering::SeqCst) {
+                        let Some(twist_msg) = pending.lock().unwrap().take() else {
+                            continue;
+                        };
+                        twist_msg
+                    } else {
+                        *pending.lock().unwrap() = None;
+                        Twist::default()
+                    };
+                    if let Err(e) = vel_publisher.publish(&twist_msg) {
+                        warn!("r2r publish error: {e:?}");
+                    }
+                }
+            }
+        });
+        self.rate_limiter = Some((pending, handle, period));
+    }
+
+    /// Sets the flag which enables sending velocities.
+    ///
+    /// While the flag is `false`, sending a velocity publishes zero velocity
+    /// instead and returns an error. See also
+    /// [`Ros2ControlClient::set_enabled_flag`](crate::Ros2ControlClient::set_enabled_flag).
+    pub fn set_enabled_flag(&mut self, enabled: Arc<AtomicBool>) {
+        self.enabled = enabled;
+        // Restart the task to check the new flag.
+        if let Some((_, handle, period)) = self.rate_limiter.take() {
+            handle.abort();
+            self.spawn_rate_limiter(period);
+        }
+    }
+
+    fn publish(&self, twist_msg: &Twist) -> Result<(), Error> {
+        if let Some((pending, _, _)) = &self.rate_limiter {
+            *pending.lock().unwrap() = Some(twist_msg.clone());
+            return Ok(());
+        }
+        self.publish_now(twist_msg)
+    }
+
+    fn publish_now(&self, twist_msg: &Twist) -> Result<(), Error> {
+        self.vel_publisher
+            .lock()
+            .unwrap()
+            .publish(twist_msg)
+            .map_err(|e| arci::Error::Connection {
+                message: format!("r2r publish error: {e:?}"),
+            })
+    }
 }
 
 impl MoveBase for Ros2CmdVelMoveBase {
     fn send_velocity(&self, velocity: &BaseVelocity) -> Result<(), Error> {
+        if !self.enabled.load(Ordering::SeqCst) {
+            if let Some((pending, _, _)) = &self.rate_limiter {
+                *pending.lock().unwrap() = None;
+            }
+            self.publish_now(&Twist::default())?;
+            return Err(Error::Other(format_err!("disabled")));
+        }
         let mut twist_msg = Twist::default();
         twist_msg.linear.x = velocity.x;
         twist_msg.linear.y = velocity.y;
         twist_msg.angular.z = velocity.theta;
-        self.vel_publisher
-            .lock()
-            .unwrap()
-            .publish(&twist_msg)
-            .map_err(|e| arci::Error::Connection {
-                message: format!("r2r publish error: {e:?}"),
-            })
+        self.publish(&twist_msg)
     }
 
     fn current_velocity(&self) -> Result<BaseVelocity, Error> {
@@ -48,10 +164,30 @@ impl MoveBase for Ros2CmdVelMoveBase {
     }
 }
 
+impl Drop for Ros2CmdVelMoveBase {
+    fn drop(&mut self) {
+        if let Some((_, handle, _)) = &self.rate_limiter {
+            handle.abort();
+        }
+    }
+}
+
 /// Configuration for `Ros2CmdVelMoveBase`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Ros2CmdVelMoveBaseConfig {
     /// Topic name for geometry_msgs/Twist.
     pub topic: String,
+    /// Maximum frequency (in Hz) to publish velocities at. Every velocity is published if not set.
+    #[serde(default)]
+    pub max_publish_rate_hz: Option<f64>,
+}
+
+impl Ros2CmdVelMoveBaseConfig {
+    /// Creates a `Ros2CmdVelMoveBase` from this configuration.
+    pub fn build(&self, node: Node) -> Result<Ros2CmdVelMoveBase, Error> {
+        let mut move_base = Ros2CmdVelMoveBase::new(node, &self.topic);
+        move_base.set_max_publish_rate(self.max_publish_rate_hz)?;
+        Ok(move_base)
+    }
 }
@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use arci::Error;
+use r2r::controller_manager_msgs::{msg::ControllerState, srv::ListControllers};
+
+use crate::Node;
+
+const JOINT_TRAJECTORY_CONTROLLER_TYPE: &str =
+    "joint_trajectory_controller/JointTrajectoryController";
+
+/// Information of a controller managed by ros2_control controller_manager.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControllerInfo {
+    /// Name of the controller. For a joint trajectory controller, this is the
+    /// action name for [`Ros2ControlClient::new`](crate::Ros2ControlClient::new).
+    pub name: String,
+    /// Type of the controller (e.g., `joint_trajectory_controller/JointTrajectoryController`).
+    pub controller_type: String,
+    /// State of the controller (e.g., `active` or `inactive`).
+    pub state: String,
+    /// Names of the joints the controller claims (or requires, if it is not active).
+    pub joints: Vec<String>,
+}
+
+impl From<ControllerState> for ControllerInfo {
+    fn from(state: ControllerState) -> Self {
+        // Interfaces are named "<joint>/<interface>", e.g., "joint1/position".
+        let interfaces = if state.claimed_interfaces.is_empty() {
+            state.required_command_interfaces
+        } else {
+            state.claimed_interfaces
+        };
+        let mut joints: Vec<String> = vec![];
+        for interface in &interfaces {
+            let joint = interface
+                .rsplit_once('/')
+                .map_or(interface.as_str(), |(joint, _)| joint);
+            if !joints.iter().any(|j| j == joint) {
+                joints.push(joint.to_owned());
+            }
+        }
+        Self {
+            name: state.name,
+            controller_type: state.type_,
+            state: state.state,
+            joints,
+        }
+    }
+}
+
+/// Lists the controllers managed by `controller_manager_name` (e.g., `/controller_manager`).
+///
+/// This calls the `list_controllers` service of the controller manager, so
+/// the node must be spun (e.g., by [`Node::run_spin_thread`]). Returns an
+/// error if the service is not available within `timeout`.
+pub async fn list_controllers(
+    node: &Node,
+    controller_manager_name: &str,
+    timeout: Duration,
+) -> Result<Vec<ControllerInfo>, Error> {
+    let client = node
+        .r2r()
+        .create_client::<ListControllers::Service>(&format!(
+            "{controller_manager_name}/list_controllers"
+        ))
+        .map_err(anyhow::Error::from)?;
+    let is_available = node
+        .r2r()
+        .is_available(&client)
+        .map_err(anyhow::Error::from)?;
+    tokio::time::timeout(timeout, is_available)
+        .await
+        .map_err(|_| Error::Connection {
+            message: format!(
+                "{controller_manager_name}/list_controllers is not available within {timeout:?}"
+            ),
+        })?
+        .map_err(anyhow::Error::from)?;
+    let response = client
+        .request(&ListControllers::Request {})
+        .map_err(anyhow::Error::from)?
+        .await
+        .map_err(anyhow::Error::from)?;
+    Ok(response
+        .controller
+        .into_iter()
+        .map(ControllerInfo::from)
+        .collect())
+}
+
+/// Lists the joint trajectory controllers managed by `/controller_manager`.
+///
+/// See [`list_controllers`] for details.
+pub async fn list_joint_trajectory_controllers(
+    node: &Node,
+    timeout: Duration,
+) -> Result<Vec<ControllerInfo>, Error> {
+    Ok(list_controllers(node, "/controller_manager", timeout)
+        .await?
+        .into_iter()
+        .filter(|controller| controller.controller_type == JOINT_TRAJECTORY_CONTROLLER_TYPE)
+        .collect())
+}
@@ -0,0 +1,169 @@
+use std::time::Duration;
+
+use anyhow::format_err;
+use arci::{
+    nalgebra::{Quaternion, Translation2, UnitComplex},
+    Error, Isometry2, Isometry3, TrajectoryPoint, UnitQuaternion, Vector3,
+};
+use r2r::{
+    builtin_interfaces::msg as builtin_msg, geometry_msgs::msg as geometry_msg,
+    std_msgs::msg::Header, trajectory_msgs::msg as trajectory_msg,
+};
+
+/// Converts `Duration` to builtin_interfaces/Duration.
+///
+/// Durations longer than `i32::MAX` seconds are saturated.
+pub fn to_ros2_duration(duration: Duration) -> builtin_msg::Duration {
+    builtin_msg::Duration {
+        sec: duration.as_secs().try_into().unwrap_or(i32::MAX),
+        nanosec: duration.subsec_nanos(),
+    }
+}
+
+/// Converts `Duration` since the epoch (e.g., from `r2r::Clock::get_now`) to
+/// builtin_interfaces/Time.
+///
+/// Returns an error if the seconds don't fit in `i32`.
+pub fn to_ros2_time(time: Duration) -> Result<builtin_msg::Time, Error> {
+    Ok(builtin_msg::Time {
+        sec: time
+            .as_secs()
+            .try_into()
+            .map_err(|_| Error::Other(format_err!("time {time:?} is out of range")))?,
+        nanosec: time.subsec_nanos(),
+    })
+}
+
+/// Converts builtin_interfaces/Duration to `Duration`.
+///
+/// Negative durations are saturated to zero.
+pub fn from_ros2_duration(duration: &builtin_msg::Duration) -> Duration {
+    match u64::try_from(duration.sec) {
+        Ok(sec) => Duration::new(sec, duration.nanosec),
+        Err(_) => Duration::ZERO,
+    }
+}
+
+/// Converts `arci::TrajectoryPoint` to trajectory_msgs/JointTrajectoryPoint.
+///
+/// If the point has no velocities, zero velocities for all the positions are
+/// set so that the controller uses cubic interpolation.
+pub fn to_ros2_trajectory_point(point: &TrajectoryPoint) -> trajectory_msg::JointTrajectoryPoint {
+    trajectory_msg::JointTrajectoryPoint {
+        positions: point.positions.clone(),
+        velocities: point
+            .velocities
+            .clone()
+            .unwrap_or_else(|| vec![0.0; point.positions.len()]),
+        time_from_start: to_ros2_duration(point.time_from_start),
+        ..Default::default()
+    }
+}
+
+/// Converts trajectory_msgs/JointTrajectoryPoint to `arci::TrajectoryPoint`.
+///
+/// Empty velocities are converted to `None`. Accelerations and efforts are
+/// ignored.
+pub fn from_ros2_trajectory_point(point: &trajectory_msg::JointTrajectoryPoint) -> TrajectoryPoint {
+    TrajectoryPoint {
+        positions: point.positions.clone(),
+        velocities: if point.velocities.is_empty() {
+            None
+        } else {
+            Some(point.velocities.clone())
+        },
+        time_from_start: from_ros2_duration(&point.time_from_start),
+    }
+}
+
+/// Converts 2D pose of arci to geometry_msgs/Pose.
+///
+/// The pose is on the xy plane (z is zero), and the rotation angle of the
+/// 2D pose is the yaw of the orientation (rotation around the z axis).
+pub fn to_ros2_pose(pose: &Isometry2<f64>) -> geometry_msg::Pose {
+    let q = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), pose.rotation.angle());
+    geometry_msg::Pose {
+        position: geometry_msg::Point {
+            x: pose.translation.x,
+            y: pose.translation.y,
+            z: 0.0,
+        },
+        orientation: geometry_msg::Quaternion {
+            x: q.coords.x,
+            y: q.coords.y,
+            z: q.coords.z,
+            w: q.coords.w,
+        },
+    }
+}
+
+/// Converts geometry_msgs/Pose to 2D pose of arci.
+///
+/// The pose is projected onto the xy plane: z is ignored, and the rotation
+/// angle is the yaw of the orientation. The orientation doesn't need to be
+/// normalized.
+pub fn from_ros2_pose(pose: &geometry_msg::Pose) -> Isometry2<f64> {
+    let q = UnitQuaternion::from_quaternion(Quaternion::new(
+        pose.orientation.w,
+        pose.orientation.x,
+        pose.orientation.y,
+        pose.orientation.z,
+    ));
+    Isometry2::from_parts(
+        Translation2::new(pose.position.x, pose.position.y),
+        UnitComplex::new(q.euler_angles().2),
+    )
+}
+
+/// Converts 2D pose of arci to geometry_msgs/PoseStamped in `frame_id` frame.
+///
+/// See [`to_ros2_pose`] for the conversion of the pose.
+pub fn to_ros2_pose_stamped(
+    pose: &Isometry2<f64>,
+    frame_id: &str,
+    stamp: builtin_msg::Time,
+) -> geometry_msg::PoseStamped {
+    geometry_msg::PoseStamped {
+        header: Header {
+            frame_id: frame_id.to_owned(),
+            stamp,
+        },
+        pose: to_ros2_pose(pose),
+    }
+}
+
+/// Converts 3D pose of arci to geometry_msgs/Transform.
+pub fn to_ros2_transform(transform: &Isometry3<f64>) -> geometry_msg::Transform {
+    let q = transform.rotation;
+    geometry_msg::Transform {
+        translation: geometry_msg::Vector3 {
+            x: transform.translation.x,
+            y: transform.translation.y,
+            z: transform.translation.z,
+        },
+        rotation: geometry_msg::Quaternion {
+            x: q.coords.x,
+            y: q.coords.y,
+            z: q.coords.z,
+            w: q.coords.w,
+        },
+    }
+}
+
+/// Converts 3D pose of arci to geometry_msgs/TransformStamped from
+/// `parent_frame_id` frame to `child_frame_id` frame.
+pub fn to_ros2_transform_stamped(
+    transform: &Isometry3<f64>,
+    parent_frame_id: &str,
+    child_frame_id: &str,
+    stamp: builtin_msg::Time,
+) -> geometry_msg::TransformStamped {
+    geometry_msg::TransformStamped {
+        header: Header {
+            frame_id: parent_frame_id.to_owned(),
+            stamp,
+        },
+        child_frame_id: child_frame_id.to_owned(),
+        transform: to_ros2_transform(transform),
+    }
+}
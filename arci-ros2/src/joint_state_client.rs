@@ -0,0 +1,117 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use anyhow::format_err;
+use arci::{Error, JointTrajectoryClient, TrajectoryPoint, WaitFuture};
+use r2r::{sensor_msgs::msg::JointState, QosProfile};
+
+use crate::{utils, Node};
+
+/// Read-only `arci::JointTrajectoryClient` implementation for ROS2 which
+/// subscribes to sensor_msgs/JointState.
+///
+/// This is useful to monitor robots which don't use ros2_control and just
+/// publish joint states. The joints are reordered by the joint names given on
+/// construction, and messages containing only some of the joints (e.g.,
+/// published by several nodes) are merged. Sending positions or trajectories
+/// returns an error.
+pub struct Ros2JointStateClient {
+    joint_names: Vec<String>,
+    /// Position and velocity (if reported) of each joint
+    joint_states: Arc<RwLock<HashMap<String, (f64, Option<f64>)>>>,
+    topic_name: String,
+    // keep not to be dropped
+    _node: Node,
+}
+
+impl Ros2JointStateClient {
+    /// Creates a new `Ros2JointStateClient` from sensor_msgs/JointState topic
+    /// name (typically `/joint_states`).
+    pub fn new(node: Node, topic_name: &str, joint_names: Vec<String>) -> Result<Self, Error> {
+        let mut subscriber = node
+            .r2r()
+            .subscribe::<JointState>(topic_name, QosProfile::default())
+            .map_err(anyhow::Error::from)?;
+        let mut states = HashMap::new();
+        let mut update = move |msg: JointState| {
+            for (i, name) in msg.name.iter().enumerate() {
+                if let Some(&position) = msg.position.get(i) {
+                    states.insert(name.clone(), (position, msg.velocity.get(i).copied()));
+                }
+            }
+            states.clone()
+        };
+        let joint_states = utils::subscribe_one(&mut subscriber, Duration::from_secs(1))
+            .map(&mut update)
+            .unwrap_or_default();
+        let joint_states = Arc::new(RwLock::new(joint_states));
+        utils::subscribe_thread(subscriber, joint_states.clone(), update);
+
+        Ok(Self {
+            joint_names,
+            joint_states,
+            topic_name: topic_name.to_owned(),
+            _node: node,
+        })
+    }
+
+    fn current_joint_states<T>(
+        &self,
+        f: impl Fn(&(f64, Option<f64>)) -> Option<T>,
+    ) -> Result<Vec<T>, Error> {
+        let joint_states = self.joint_states.read().unwrap();
+        self.joint_names
+            .iter()
+            .map(|name| {
+                joint_states
+                    .get(name)
+                    .and_then(&f)
+                    .ok_or_else(|| Error::Connection {
+                        message: format!(
+                            "Failed to get the state of {name} from {}",
+                            self.topic_name
+                        ),
+                    })
+            })
+            .collect()
+    }
+
+    /// Returns the current joint velocities.
+    ///
+    /// Returns an error if the velocity of any joint has not been reported.
+    pub fn current_joint_velocities(&self) -> Result<Vec<f64>, Error> {
+        self.current_joint_states(|&(_, velocity)| velocity)
+    }
+}
+
+impl JointTrajectoryClient for Ros2JointStateClient {
+    fn joint_names(&self) -> Vec<String> {
+        self.joint_names.clone()
+    }
+
+    fn current_joint_positions(&self) -> Result<Vec<f64>, Error> {
+        self.current_joint_states(|&(position, _)| Some(position))
+    }
+
+    fn send_joint_positions(
+        &self,
+        _positions: Vec<f64>,
+        _duration: Duration,
+    ) -> Result<WaitFuture, Error> {
+        Err(Error::Other(format_err!(
+            "Ros2JointStateClient is read-only: sending positions is not supported"
+        )))
+    }
+
+    fn send_joint_trajectory(
+        &self,
+        _trajectory: Vec<TrajectoryPoint>,
+    ) -> Result<WaitFuture, Error> {
+        Err(Error::Other(format_err!(
+            "Ros2JointStateClient is read-only: sending trajectories is not supported"
+        )))
+    }
+}
@@ -0,0 +1,100 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::format_err;
+use arci::{Error, JointTrajectoryClient};
+use r2r::{sensor_msgs::msg::JointState, std_msgs::msg::Header};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::{to_ros2_time, utils, Node};
+
+/// Periodically publishes the current joint positions of a
+/// `JointTrajectoryClient` as sensor_msgs/JointState.
+///
+/// This allows visualizing the robot (e.g., by robot_state_publisher and RViz)
+/// when it is driven by a client which doesn't publish joint states, such as
+/// a simulated or dummy client. Publishing stops when this is dropped.
+pub struct Ros2JointStatePublisher {
+    handle: JoinHandle<()>,
+    // keep not to be dropped
+    _node: Node,
+}
+
+impl Ros2JointStatePublisher {
+    /// Creates a new `Ros2JointStatePublisher` publishing the joint positions
+    /// of `client` to `topic_name` at every `interval`.
+    ///
+    /// Returns an error if `interval` is zero.
+    pub fn new(
+        node: Node,
+        topic_name: &str,
+        client: Arc<dyn JointTrajectoryClient>,
+        interval: Duration,
+    ) -> Result<Self, Error> {
+        if interval.is_zero() {
+            return Err(Error::Other(format_err!(
+                "The interval to publish joint states must be positive"
+            )));
+        }
+        let publisher = node
+            .r2r()
+            .create_publisher::<JointState>(topic_name, r2r::QosProfile::default())
+            .map_err(anyhow::Error::from)?;
+        let mut clock = r2r::Clock::create(r2r::ClockType::RosTime).map_err(anyhow::Error::from)?;
+        let handle = utils::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                // The client may block (e.g., waiting for a reply from the robot).
+                let client_clone = client.clone();
+                let positions = match tokio::task::spawn_blocking(move || {
+                    client_clone.current_joint_positions()
+                })
+                .await
+                .map_err(|e| Error::Other(e.into()))
+                .and_then(|positions| positions)
+                {
+                    Ok(positions) => positions,
+                    Err(e) => {
+                        warn!("Failed to get current joint positions: {e}");
+                        continue;
+                    }
+                };
+                let stamp = match clock
+                    .get_now()
+                    .map_err(|e| Error::Other(e.into()))
+                    .and_then(to_ros2_time)
+                {
+                    Ok(stamp) => stamp,
+                    Err(e) => {
+                        warn!("Failed to get the current time: {e}");
+                        continue;
+                    }
+                };
+                let msg = JointState {
+                    header: Header {
+                        stamp,
+                        ..Default::default()
+                    },
+                    name: client.joint_names(),
+                    position: positions,
+                    ..Default::default()
+                };
+                if let Err(e) = publisher.publish(&msg) {
+                    warn!("Failed to publish joint states: {e}");
+                }
+            }
+        });
+        Ok(Self {
+            handle,
+            _node: node,
+        })
+    }
+}
+
+impl Drop for Ros2JointStatePublisher {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
@@ -4,22 +4,38 @@
 #![allow(missing_debug_implementations)] // TODO: Some r2r types don't implement Debug
 
 mod cmd_vel_move_base;
+mod controller_manager;
+mod conversions;
+mod joint_state_client;
+mod joint_state_publisher;
 mod navigation;
 mod node;
 mod plugin;
+mod qos;
+mod robot_config;
 mod ros2_control;
+mod ros2_image;
 mod ros2_laser_scan;
 mod ros2_localization_client;
 mod ros2_transform_resolver;
+mod tf_broadcaster;
 #[allow(missing_docs)]
 pub mod utils;
 
 pub use cmd_vel_move_base::*;
+pub use controller_manager::*;
+pub use conversions::*;
+pub use joint_state_client::*;
+pub use joint_state_publisher::*;
 pub use navigation::*;
 pub use node::*;
+pub use qos::*;
 // re-export
 pub use r2r;
+pub use robot_config::*;
 pub use ros2_control::*;
+pub use ros2_image::*;
 pub use ros2_laser_scan::*;
 pub use ros2_localization_client::*;
 pub use ros2_transform_resolver::*;
+pub use tf_broadcaster::*;
@@ -1,21 +1,16 @@
 use std::{
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc, Mutex,
-    },
+    sync::{Arc, Mutex},
     time::Duration,
 };
 
 use anyhow::format_err;
 use arci::*;
 use futures::stream::StreamExt;
-use r2r::{
-    builtin_interfaces::msg::Time, geometry_msgs::msg, nav2_msgs::action::NavigateToPose,
-    std_msgs::msg::Header,
-};
+use r2r::nav2_msgs::action::NavigateToPose;
 use serde::{Deserialize, Serialize};
+use tracing::warn;
 
-use crate::{utils, Node};
+use crate::{to_ros2_pose_stamped, to_ros2_time, utils, Node};
 
 /// `arci::Navigation` implementation for ROS2.
 pub struct Ros2Navigation {
@@ -41,23 +36,6 @@ impl Ros2Navigation {
     }
 }
 
-fn to_pose_msg(pose: &Isometry2<f64>) -> msg::Pose {
-    let q = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), pose.rotation.angle());
-    msg::Pose {
-        position: msg::Point {
-            x: pose.translation.x,
-            y: pose.translation.y,
-            z: 0.0,
-        },
-        orientation: msg::Quaternion {
-            x: q.coords.x,
-            y: q.coords.y,
-            z: q.coords.z,
-            w: q.coords.w,
-        },
-    }
-}
-
 impl Navigation for Ros2Navigation {
     fn send_goal_pose(
         &self,
@@ -69,47 +47,53 @@ impl Navigation for Ros2Navigation {
         let current_goal = self.current_goal.clone();
         let action_client = self.action_client.clone();
         let is_available = node.r2r().is_available(&self.action_client).unwrap();
+        let mut clock = r2r::Clock::create(r2r::ClockType::RosTime).map_err(anyhow::Error::from)?;
+        let now = clock.get_now().map_err(anyhow::Error::from)?;
+        let goal = NavigateToPose::Goal {
+            pose: to_ros2_pose_stamped(&goal, frame_id, to_ros2_time(now)?),
+            ..Default::default()
+        };
         let (sender, receiver) = tokio::sync::oneshot::channel();
-        let frame_id = frame_id.to_owned();
-        tokio::spawn(async move {
-            let is_done = Arc::new(AtomicBool::new(false));
-            let is_done_clone = is_done.clone();
+        utils::spawn(async move {
             let current_goal_clone = current_goal.clone();
-            tokio::spawn(async move {
-                let mut clock = r2r::Clock::create(r2r::ClockType::RosTime).unwrap();
-                let now = clock.get_now().unwrap();
-                let goal = NavigateToPose::Goal {
-                    pose: msg::PoseStamped {
-                        header: Header {
-                            frame_id,
-                            stamp: Time {
-                                sec: now.as_secs() as i32,
-                                nanosec: now.subsec_nanos(),
-                            },
-                        },
-                        pose: to_pose_msg(&goal),
-                    },
-                    ..Default::default()
-                };
-
-                is_available.await.unwrap();
-                let send_goal_request = action_client.send_goal_request(goal).unwrap();
-                let (goal, result, feedback) = send_goal_request.await.unwrap();
-                *current_goal_clone.lock().unwrap() = Some(goal.clone());
-                tokio::spawn(async move { feedback.for_each(|_| std::future::ready(())).await });
-                result.await.unwrap(); // TODO: handle goal state
-                is_done.store(true, Ordering::Relaxed);
-            });
-            utils::wait(is_done_clone).await;
+            let navigate = async move {
+                is_available.await.map_err(|e| Error::Other(e.into()))?;
+                let send_goal_request = action_client
+                    .send_goal_request(goal)
+                    .map_err(|e| Error::Other(e.into()))?;
+                let (goal, result, feedback) = send_goal_request
+                    .await
+                    .map_err(|e| Error::Other(e.into()))?;
+                *current_goal_clone.lock().unwrap() = Some(goal);
+                utils::spawn(async move { feedback.for_each(|_| std::future::ready(())).await });
+                // TODO: handle goal state. "canceled" should be an error?
+                result.await.map_err(|e| Error::Other(e.into()))?;
+                Ok::<_, Error>(())
+            };
+            let result = match tokio::time::timeout(timeout, navigate).await {
+                Ok(result) => result,
+                Err(_) => {
+                    // Cancel the goal so that the robot does not keep moving
+                    // after the caller has been told that the goal timed out.
+                    let goal = current_goal.lock().unwrap().take();
+                    if let Some(goal) = goal {
+                        match goal.cancel() {
+                            Ok(fut) => {
+                                let _ = fut.await;
+                            }
+                            Err(e) => warn!("failed to cancel the goal: {e}"),
+                        }
+                    }
+                    Err(Error::Other(format_err!("timeout {timeout:?}")))
+                }
+            };
             *current_goal.lock().unwrap() = None;
-            // TODO: "canceled" should be an error?
-            let _ = sender.send(());
+            let _ = sender.send(result);
         });
-        let timeout_fut = tokio::time::sleep(timeout);
         let wait = WaitFuture::new(async move {
-            tokio::select! {
-                _ = receiver => Ok(()),
-                _ = timeout_fut => Err(arci::Error::Other(format_err!("timeout {timeout:?}"))),
+            match receiver.await {
+                Ok(result) => result,
+                Err(e) => Err(arci::Error::Other(e.into())),
             }
         });
 
@@ -121,7 +105,7 @@ impl Navigation for Ros2Navigation {
         //       Therefore, if cancel is called during that period, it will not work correctly.
         if let Some(current_goal) = self.current_goal.lock().unwrap().take() {
             let fut = current_goal.cancel().map_err(|e| Error::Other(e.into()))?;
-            tokio::spawn(async move {
+            utils::spawn(async move {
                 let _ = fut.await;
             });
         }
@@ -136,3 +120,10 @@ pub struct Ros2NavigationConfig {
     /// Action name for nav2_msgs/NavigateToPose.
     pub action_name: String,
 }
+
+impl Ros2NavigationConfig {
+    /// Creates a `Ros2Navigation` from this configuration.
+    pub fn build(&self, node: Node) -> Ros2Navigation {
+        Ros2Navigation::new(node, &self.action_name)
+    }
+}
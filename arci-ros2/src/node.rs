@@ -6,7 +6,17 @@ use std::{
     time::Duration,
 };
 
+use crate::utils;
+
+const DEFAULT_SPIN_PERIOD: Duration = Duration::from_millis(100);
+
 /// ROS2 node. This is a wrapper around `Arc<Mutex<r2r::Node>>`.
+///
+/// The node must be spun to process the callbacks of subscriptions, services
+/// and actions used by the clients in this crate. Call
+/// [`run_spin_thread`](Self::run_spin_thread) to spin it in the background, or
+/// [`spin_once`](Self::spin_once) (or [`spin_forever`](Self::spin_forever))
+/// to drive it from a custom loop.
 #[derive(Clone)]
 pub struct Node {
     inner: Arc<NodeInner>,
@@ -15,6 +25,7 @@ pub struct Node {
 struct NodeInner {
     node: Mutex<r2r::Node>,
     has_spin_thread: AtomicBool,
+    spin_period: Mutex<Duration>,
 }
 
 impl Node {
@@ -35,6 +46,7 @@ impl Node {
             inner: Arc::new(NodeInner {
                 node: Mutex::new(node),
                 has_spin_thread: AtomicBool::new(false),
+                spin_period: Mutex::new(DEFAULT_SPIN_PERIOD),
             }),
         })
     }
@@ -44,20 +56,51 @@ impl Node {
         self.inner.node.lock().unwrap()
     }
 
-    /// Creates a thread to spin the ROS2 node.
+    /// Creates a thread to spin the ROS2 node at every `interval`.
+    ///
+    /// This sets the spin period to `interval`, and the thread follows later
+    /// changes by [`set_spin_period`](Self::set_spin_period). The thread stops
+    /// when all other clones of this node are dropped. Only the first call
+    /// creates a thread.
     pub fn run_spin_thread(&self, interval: Duration) {
+        self.set_spin_period(interval);
         if self.inner.has_spin_thread.swap(true, Ordering::Relaxed) {
             return;
         }
         let node = self.clone();
-        tokio::spawn(async move {
+        utils::spawn(async move {
             while Arc::strong_count(&node.inner) > 1 {
-                node.spin_once(interval).await;
+                node.spin_once(node.spin_period()).await;
             }
         });
     }
 
+    /// Sets the period to spin the ROS2 node by [`run_spin_thread`](Self::run_spin_thread)
+    /// and [`spin_forever`](Self::spin_forever). Default is 100 ms.
+    pub fn set_spin_period(&self, period: Duration) {
+        *self.inner.spin_period.lock().unwrap() = period;
+    }
+
+    /// Returns the period to spin the ROS2 node.
+    pub fn spin_period(&self) -> Duration {
+        *self.inner.spin_period.lock().unwrap()
+    }
+
+    /// Spins the ROS2 node at every spin period until the returned future is
+    /// dropped.
+    ///
+    /// This is useful to spin the node on a task managed by the caller instead
+    /// of [`run_spin_thread`](Self::run_spin_thread).
+    pub async fn spin_forever(&self) {
+        loop {
+            self.spin_once(self.spin_period()).await;
+        }
+    }
+
     /// Spins the ROS2 node.
+    ///
+    /// This processes the pending callbacks once, and then waits until
+    /// `duration` has elapsed since the call.
     pub async fn spin_once(&self, duration: Duration) {
         let now = std::time::Instant::now();
         // Sleep with tokio::time::sleep instead of spin_once, since spin_once
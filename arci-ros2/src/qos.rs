@@ -0,0 +1,32 @@
+use r2r::QosProfile;
+use serde::{Deserialize, Serialize};
+
+/// Preset of the QoS profile used to subscribe topics.
+///
+/// The subscriber must be compatible with the publisher to receive messages,
+/// e.g., sensor drivers often publish with [`SensorData`](Self::SensorData),
+/// which is not received by the default reliable subscriber.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum QosPreset {
+    /// `QosProfile::default()`: reliable, volatile and keeps the last 10 messages.
+    #[default]
+    Default,
+    /// `QosProfile::sensor_data()`: best effort, volatile and keeps the last 5 messages.
+    SensorData,
+    /// `QosProfile::system_default()`: the default of the RMW implementation.
+    SystemDefault,
+    /// `QosProfile::default()` with best effort reliability.
+    BestEffort,
+}
+
+impl From<QosPreset> for QosProfile {
+    fn from(preset: QosPreset) -> Self {
+        match preset {
+            QosPreset::Default => QosProfile::default(),
+            QosPreset::SensorData => QosProfile::sensor_data(),
+            QosPreset::SystemDefault => QosProfile::system_default(),
+            QosPreset::BestEffort => QosProfile::default().best_effort(),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use std::{collections::HashMap, sync::Arc};
+
+use arci::{Error, JointTrajectoryClient, LaserScan2D, Localization, MoveBase, Navigation};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Node, Ros2CmdVelMoveBaseConfig, Ros2ControlConfig, Ros2LaserScan2DConfig,
+    Ros2LocalizationClientConfig, Ros2NavigationConfig,
+};
+
+/// Configuration of all arci-ros2 clients of a robot.
+///
+/// Each field is a map from the name of a client to its configuration.
+/// Use [`build`](Ros2RobotConfig::build) to create all clients at once.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Ros2RobotConfig {
+    /// Configurations for `Ros2ControlClient`.
+    #[serde(default)]
+    pub joint_trajectory_clients: HashMap<String, Ros2ControlConfig>,
+    /// Configurations for `Ros2CmdVelMoveBase`.
+    #[serde(default)]
+    pub move_bases: HashMap<String, Ros2CmdVelMoveBaseConfig>,
+    /// Configurations for `Ros2Navigation`.
+    #[serde(default)]
+    pub navigations: HashMap<String, Ros2NavigationConfig>,
+    /// Configurations for `Ros2LocalizationClient`.
+    #[serde(default)]
+    pub localizations: HashMap<String, Ros2LocalizationClientConfig>,
+    /// Configurations for `Ros2LaserScan2D`.
+    #[serde(default)]
+    pub laser_scans: HashMap<String, Ros2LaserScan2DConfig>,
+}
+
+/// Clients created by [`Ros2RobotConfig::build`], keyed by their names.
+#[derive(Default)]
+pub struct RobotClients {
+    /// `arci::JointTrajectoryClient`s.
+    pub joint_trajectory_clients: HashMap<String, Arc<dyn JointTrajectoryClient>>,
+    /// `arci::MoveBase`s.
+    pub move_bases: HashMap<String, Arc<dyn MoveBase>>,
+    /// `arci::Navigation`s.
+    pub navigations: HashMap<String, Arc<dyn Navigation>>,
+    /// `arci::Localization`s.
+    pub localizations: HashMap<String, Arc<dyn Localization>>,
+    /// `arci::LaserScan2D`s.
+    pub laser_scans: HashMap<String, Arc<dyn LaserScan2D>>,
+}
+
+impl Ros2RobotConfig {
+    /// Creates all clients in this configuration, sharing `node`.
+    pub fn build(&self, node: &Node) -> Result<RobotClients, Error> {
+        let mut clients = RobotClients::default();
+        for (name, config) in &self.joint_trajectory_clients {
+            clients
+                .joint_trajectory_clients
+                .insert(name.clone(), Arc::new(config.build(node.clone())?));
+        }
+        for (name, config) in &self.move_bases {
+            clients
+                .move_bases
+                .insert(name.clone(), Arc::new(config.build(node.clone())?));
+        }
+        for (name, config) in &self.navigations {
+            clients
+                .navigations
+                .insert(name.clone(), Arc::new(config.build(node.clone())));
+        }
+        for (name, config) in &self.localizations {
+            clients
+                .localizations
+                .insert(name.clone(), Arc::new(config.build(node.clone())?));
+        }
+        for (name, config) in &self.laser_scans {
+            clients
+                .laser_scans
+                .insert(name.clone(), Arc::new(config.build(node.clone())?));
+        }
+        Ok(clients)
+    }
+}
@@ -1,36 +1,114 @@
 use std::{
+    collections::HashMap,
+    future::Future,
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc, RwLock,
+        Arc, Mutex, RwLock,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+use anyhow::format_err;
 use arci::*;
-use futures::stream::StreamExt;
+use futures::{
+    future::{self, Either},
+    stream::StreamExt,
+};
 use r2r::{
-    builtin_interfaces::{msg as builtin_msg, msg::Time},
     control_msgs::{action::FollowJointTrajectory, msg::JointTrajectoryControllerState},
     std_msgs::msg::Header,
     trajectory_msgs::msg as trajectory_msg,
 };
 use serde::{Deserialize, Serialize};
+use tracing::debug;
 
-use crate::{utils, Node};
+use crate::{
+    from_ros2_duration, to_ros2_duration, to_ros2_time, to_ros2_trajectory_point, utils, Node,
+    QosPreset,
+};
 
 /// `arci::JointTrajectoryClient` implementation for ROS2.
+///
+/// Sending an empty trajectory is a no-op: it completes immediately without
+/// sending a goal or preempting the running trajectory.
+///
+/// The `WaitFuture` of a trajectory resolves to an error if the controller
+/// rejects or aborts it, e.g., when the path tolerance is violated.
 pub struct Ros2ControlClient {
     action_client: r2r::ActionClient<FollowJointTrajectory::Action>,
     /// r2r::Node to handle the action
     node: Node,
     joint_names: Vec<String>,
+    /// Names of all joints of the controller, in the order of its state.
+    controller_joint_names: Vec<String>,
     joint_state: Arc<RwLock<JointTrajectoryControllerState>>,
+    positions_from_feedback: bool,
+    /// Joint names and positions of the latest feedback of the running goal.
+    feedback_positions: Arc<RwLock<FeedbackPositions>>,
+    resample_dt: Option<Duration>,
+    goal_time_tolerance: Option<Duration>,
+    result_timeout: Option<Duration>,
+    progress_interval: Option<Duration>,
+    wait_for_first_feedback: Option<Duration>,
+    max_joint_step: Option<f64>,
+    /// Position and velocity limits by joint name.
+    trajectory_limits: Option<HashMap<String, (JointPositionLimit, f64)>>,
+    /// Velocity and acceleration limits by joint name, which trajectories are
+    /// slowed down to.
+    motion_limits: Option<HashMap<String, (f64, f64)>>,
+    preemption_policy: PreemptionPolicy,
+    active_goal: Arc<Mutex<ActiveGoal>>,
+    goal_queue: Arc<tokio::sync::Mutex<()>>,
+    enabled: Arc<AtomicBool>,
+    debug_trajectory_publisher: Option<Arc<Mutex<r2r::Publisher<trajectory_msg::JointTrajectory>>>>,
 }
 
 impl Ros2ControlClient {
     /// Creates a new `Ros2ControlClient` from control_msgs/FollowJointTrajectory action name.
+    ///
+    /// This waits for the state of the controller for 1 second. See
+    /// [`with_retry`](Self::with_retry) to wait longer.
     #[track_caller]
     pub fn new(node: Node, action_name: &str) -> Result<Self, Error> {
+        Self::with_retry(node, action_name, Duration::from_secs(1), 0)
+    }
+
+    /// Creates a new `Ros2ControlClient` from control_msgs/FollowJointTrajectory
+    /// action name, retrying to get the state of the controller.
+    ///
+    /// The state topic is subscribed and waited for `state_timeout`, and if no
+    /// message is received, it is subscribed again up to `max_retry` times.
+    /// This makes the construction robust when the controller has just come up.
+    /// Returns `Error::Connection` if no state is received or the state does
+    /// not contain the positions of all joints.
+    #[track_caller]
+    pub fn with_retry(
+        node: Node,
+        action_name: &str,
+        state_timeout: Duration,
+        max_retry: usize,
+    ) -> Result<Self, Error> {
+        Self::with_qos(
+            node,
+            action_name,
+            state_timeout,
+            max_retry,
+            r2r::QosProfile::default(),
+        )
+    }
+
+    /// Creates a new `Ros2ControlClient` from control_msgs/FollowJointTrajectory
+    /// action name, subscribing the state topic with `qos`.
+    ///
+    /// See [`with_retry`](Self::with_retry) for `state_timeout` and `max_retry`.
+    #[track_caller]
+    pub fn with_qos(
+        node: Node,
+        action_name: &str,
+        state_timeout: Duration,
+        max_retry: usize,
+        qos: r2r::QosProfile,
+    ) -> Result<Self, Error> {
         // http://wiki.ros.org/joint_trajectory_controller
         let action_client = node
             .r2r()
@@ -40,26 +118,1054 @@ impl Ros2ControlClient {
             .map_err(anyhow::Error::from)?;
 
         let state_topic = format!("{action_name}/state");
-        let mut state_subscriber = node
-            .r2r()
-            .subscribe::<JointTrajectoryControllerState>(&state_topic, r2r::QosProfile::default())
-            .map_err(anyhow::Error::from)?;
-        let Some(joint_state) = utils::subscribe_one(&mut state_subscriber, Duration::from_secs(1))
-        else {
+        let mut received = None;
+        for i in 0..=max_retry {
+            if i != 0 {
+                debug!("Retrying to get joint_state from {state_topic} ({i} / {max_retry}) ...");
+            }
+            let mut state_subscriber = node
+                .r2r()
+                .subscribe::<JointTrajectoryControllerState>(&state_topic, qos.clone())
+                .map_err(anyhow::Error::from)?;
+            if let Some(joint_state) = utils::subscribe_one(&mut state_subscriber, state_timeout) {
+                received = Some((joint_state, state_subscriber));
+                break;
+            }
+        }
+        let Some((joint_state, state_subscriber)) = received else {
             return Err(Error::Connection {
                 message: format!("Failed to get joint_state from {state_topic}"),
             });
         };
         let joint_names = joint_state.joint_names.clone();
+        validate_joint_names(&joint_names, &joint_state)?;
         let joint_state = Arc::new(RwLock::new(joint_state));
         utils::subscribe_thread(state_subscriber, joint_state.clone(), |state| state);
 
         Ok(Self {
             action_client,
             node,
+            controller_joint_names: joint_names.clone(),
             joint_names,
             joint_state,
+            positions_from_feedback: false,
+            feedback_positions: Arc::new(RwLock::new(FeedbackPositions::default())),
+            resample_dt: None,
+            goal_time_tolerance: None,
+            result_timeout: None,
+            progress_interval: None,
+            wait_for_first_feedback: None,
+            max_joint_step: None,
+            trajectory_limits: None,
+            motion_limits: None,
+            preemption_policy: PreemptionPolicy::default(),
+            active_goal: Arc::new(Mutex::new(ActiveGoal::default())),
+            goal_queue: Arc::new(tokio::sync::Mutex::new(())),
+            enabled: Arc::new(AtomicBool::new(true)),
+            debug_trajectory_publisher: None,
+        })
+    }
+
+    /// Sets the names of the joints to command, in the order of the positions
+    /// of trajectories and the current joint positions.
+    ///
+    /// The joints must be a subset of the joints reported by the controller,
+    /// otherwise `Error::NoJoint` is returned. The other joints of the
+    /// controller are held at their current positions while a trajectory is
+    /// running. If empty, all joints of the controller are used in the order of
+    /// its state, which is the default.
+    pub fn set_joint_names(&mut self, joint_names: Vec<String>) -> Result<(), Error> {
+        if joint_names.is_empty() {
+            self.joint_names = self.controller_joint_names.clone();
+            return Ok(());
+        }
+        validate_joint_names(&joint_names, &self.joint_state.read().unwrap())?;
+        self.joint_names = joint_names;
+        Ok(())
+    }
+
+    /// Sets the time resolution used to resample trajectories before sending.
+    ///
+    /// If `Some`, intermediate points are inserted at the given interval using
+    /// cubic interpolation. If `None` (default), trajectories are sent as is.
+    pub fn set_resample_dt(&mut self, resample_dt: Option<Duration>) {
+        self.resample_dt = resample_dt;
+    }
+
+    /// Sets the time tolerance allowed to reach the goal after the last point
+    /// of the trajectory.
+    ///
+    /// If `None` (default), the default of the controller is used.
+    pub fn set_goal_time_tolerance(&mut self, goal_time_tolerance: Option<Duration>) {
+        self.goal_time_tolerance = goal_time_tolerance;
+    }
+
+    /// Sets the time to wait for the result of the action after the duration
+    /// of the trajectory has elapsed.
+    ///
+    /// If the result is not reported in time (e.g., the controller accepted the
+    /// goal but never reports the result), the goal is canceled and an error
+    /// is returned. The trajectory is not sent again. If `None` (default), the
+    /// result is awaited indefinitely.
+    pub fn set_result_timeout(&mut self, result_timeout: Option<Duration>) {
+        self.result_timeout = result_timeout;
+    }
+
+    /// Sets the minimum interval between progress updates of
+    /// [`send_joint_trajectory_with_progress`](Self::send_joint_trajectory_with_progress).
+    ///
+    /// Action feedback arriving within the interval after the last update is
+    /// not reported as progress, so that high-rate feedback does not wake up
+    /// the receiver too often. The completion (1.0) is always reported. If
+    /// `None` (default), the progress is updated on every feedback.
+    pub fn set_progress_interval(&mut self, progress_interval: Option<Duration>) {
+        self.progress_interval = progress_interval;
+    }
+
+    /// Sets the topic to publish the trajectory of each goal sent to the
+    /// controller as trajectory_msgs/JointTrajectory.
+    ///
+    /// The published trajectory is the one actually sent (e.g., after
+    /// resampling), which is useful to visualize it in RViz and to diagnose
+    /// whether a bad motion comes from planning or from the controller. If
+    /// `None` (default), nothing is published.
+    pub fn set_debug_trajectory_topic(&mut self, topic_name: Option<&str>) -> Result<(), Error> {
+        self.debug_trajectory_publisher = match topic_name {
+            Some(topic_name) => Some(Arc::new(Mutex::new(
+                self.node
+                    .r2r()
+                    .create_publisher(topic_name, r2r::QosProfile::default())
+                    .map_err(anyhow::Error::from)?,
+            ))),
+            None => None,
+        };
+        Ok(())
+    }
+
+    /// Sets the maximum time [`send_joint_trajectory`](JointTrajectoryClient::send_joint_trajectory)
+    /// (and `send_joint_positions`) blocks until the first feedback of the
+    /// action arrives.
+    ///
+    /// Some controllers accept a goal before they actually start moving, so
+    /// the joint positions read just after sending may be the ones before the
+    /// motion. If `Some`, sending returns after the first feedback (i.e., after
+    /// the motion has begun), when the action completes or fails, or when the
+    /// timeout elapses, whichever comes first. Since this blocks the calling
+    /// thread, the node must be spun and the action driven on other threads.
+    /// When called from a runtime, it must be a multi-threaded one, whose
+    /// worker thread is handed off by `tokio::task::block_in_place` while
+    /// waiting. If `None` (default), sending returns immediately.
+    pub fn set_wait_for_first_feedback(&mut self, timeout: Option<Duration>) {
+        self.wait_for_first_feedback = timeout;
+    }
+
+    /// Sets the policy applied when a new trajectory is sent while another one
+    /// is still running.
+    pub fn set_preemption_policy(&mut self, preemption_policy: PreemptionPolicy) {
+        self.preemption_policy = preemption_policy;
+    }
+
+    /// Sets the maximum joint position difference allowed between consecutive
+    /// trajectory points (and between the current positions and the first point).
+    ///
+    /// Trajectories exceeding it are rejected with an error. If `None`
+    /// (default), no limit is applied.
+    pub fn set_max_joint_step(&mut self, max_joint_step: Option<f64>) {
+        self.max_joint_step = max_joint_step;
+    }
+
+    /// Sets whether to report the current joint positions from the feedback of
+    /// the running trajectory.
+    ///
+    /// If `true`, [`current_joint_positions`](JointTrajectoryClient::current_joint_positions)
+    /// returns `actual.positions` of the latest action feedback while a
+    /// trajectory is running, so that it is consistent with the trajectory
+    /// execution. The state topic is used when no trajectory is running.
+    /// Default is `false`.
+    pub fn set_positions_from_feedback(&mut self, positions_from_feedback: bool) {
+        self.positions_from_feedback = positions_from_feedback;
+    }
+
+    /// Sets the position and velocity limits which trajectories are validated
+    /// against before sending.
+    ///
+    /// Trajectories violating them are rejected with an error. See
+    /// [`arci::utils::validate_trajectory`] for details.
+    ///
+    /// The limits are in the order of the current
+    /// [`joint_names`](Self::set_joint_names) and are kept by joint name, so
+    /// they still apply after the joint names are changed. Joints set later
+    /// without limits are not limited.
+    ///
+    /// Returns `Error::LengthMismatch` if the lengths of the limits and joints
+    /// are different.
+    pub fn set_trajectory_limits(
+        &mut self,
+        position_limits: Vec<JointPositionLimit>,
+        velocity_limits: Vec<f64>,
+    ) -> Result<(), Error> {
+        for len in [position_limits.len(), velocity_limits.len()] {
+            if len != self.joint_names.len() {
+                return Err(Error::LengthMismatch {
+                    model: self.joint_names.len(),
+                    input: len,
+                });
+            }
+        }
+        self.trajectory_limits = Some(
+            self.joint_names
+                .iter()
+                .cloned()
+                .zip(position_limits.into_iter().zip(velocity_limits))
+                .collect(),
+        );
+        Ok(())
+    }
+
+    /// Sets the maximum velocities and accelerations of the joints, which
+    /// trajectories are slowed down to instead of being rejected by the
+    /// controller.
+    ///
+    /// The limits are in the order of the current
+    /// [`joint_names`](Self::set_joint_names) and are kept by joint name, so
+    /// they still apply after the joint names are changed. Joints set later
+    /// without limits, and all joints if `None`, are not limited. The duration of
+    /// [`send_joint_positions`](JointTrajectoryClient::send_joint_positions)
+    /// is extended to the minimum duration to move from rest to rest with a
+    /// trapezoidal velocity profile, and trajectories are scaled in time by
+    /// [`arci::utils::scale_trajectory_to_limits`] starting from the current
+    /// positions. Trajectories are never sped up. Not limited by default.
+    ///
+    /// Returns `Error::LengthMismatch` if the lengths of the limits and joints
+    /// are different, and `Error::Other` if a limit is NaN or not positive. An
+    /// infinite limit means the joint is not limited.
+    pub fn set_motion_limits(
+        &mut self,
+        velocity_limits: Option<Vec<f64>>,
+        acceleration_limits: Option<Vec<f64>>,
+    ) -> Result<(), Error> {
+        if velocity_limits.is_none() && acceleration_limits.is_none() {
+            self.motion_limits = None;
+            return Ok(());
+        }
+        let dof = self.joint_names.len();
+        let fill = |limits: Option<Vec<f64>>| match limits {
+            Some(limits) if limits.len() != dof => Err(Error::LengthMismatch {
+                model: dof,
+                input: limits.len(),
+            }),
+            Some(limits) => match limits.iter().find(|&&l| l.is_nan() || l <= 0.0) {
+                Some(limit) => Err(Error::Other(format_err!(
+                    "motion limits must be positive: {limit}"
+                ))),
+                None => Ok(limits),
+            },
+            None => Ok(vec![f64::INFINITY; dof]),
+        };
+        let velocity_limits = fill(velocity_limits)?;
+        let acceleration_limits = fill(acceleration_limits)?;
+        self.motion_limits = Some(
+            self.joint_names
+                .iter()
+                .cloned()
+                .zip(velocity_limits.into_iter().zip(acceleration_limits))
+                .collect(),
+        );
+        Ok(())
+    }
+
+    /// Returns the velocity and acceleration limits set by
+    /// [`set_motion_limits`](Self::set_motion_limits) in the order of the
+    /// current joint names, or `None` if not limited.
+    fn current_motion_limits(&self) -> Option<(Vec<f64>, Vec<f64>)> {
+        let motion_limits = self.motion_limits.as_ref()?;
+        Some(
+            self.joint_names
+                .iter()
+                .map(|joint_name| {
+                    motion_limits
+                        .get(joint_name)
+                        .copied()
+                        .unwrap_or((f64::INFINITY, f64::INFINITY))
+                })
+                .unzip(),
+        )
+    }
+
+    /// Sets the flag which enables sending trajectories.
+    ///
+    /// While the flag is `false`, new trajectories are rejected and the running
+    /// one is canceled. Sharing the same flag between clients (e.g., with
+    /// [`Ros2CmdVelMoveBase::set_enabled_flag`](crate::Ros2CmdVelMoveBase::set_enabled_flag))
+    /// allows stopping all of them at once, e.g., on emergency stop.
+    pub fn set_enabled_flag(&mut self, enabled: Arc<AtomicBool>) {
+        self.enabled = enabled;
+    }
+
+    fn check_enabled(&self) -> Result<(), Error> {
+        if self.enabled.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        cancel_active_goal(&self.active_goal)?;
+        Err(Error::Other(format_err!("disabled")))
+    }
+
+    fn check_joint_steps(&self, trajectory: &[TrajectoryPoint]) -> Result<(), Error> {
+        let Some(max_joint_step) = self.max_joint_step else {
+            return Ok(());
+        };
+        let mut prev = self.current_joint_positions()?;
+        for (i, point) in trajectory.iter().enumerate() {
+            if point.positions.len() != prev.len() {
+                return Err(Error::LengthMismatch {
+                    model: prev.len(),
+                    input: point.positions.len(),
+                });
+            }
+            for ((name, p), q) in self.joint_names.iter().zip(&prev).zip(&point.positions) {
+                if (q - p).abs() > max_joint_step {
+                    return Err(Error::Other(format_err!(
+                        "Joint {name} moves {} at trajectory point {i}, which exceeds max_joint_step {max_joint_step}",
+                        (q - p).abs()
+                    )));
+                }
+            }
+            prev.clone_from(&point.positions);
+        }
+        Ok(())
+    }
+
+    /// Scales `trajectory` in time to respect the limits set by
+    /// [`set_motion_limits`](Self::set_motion_limits), including the move from
+    /// the current positions to the first point.
+    fn slow_down_to_motion_limits(
+        &self,
+        trajectory: Vec<TrajectoryPoint>,
+    ) -> Result<Vec<TrajectoryPoint>, Error> {
+        let Some((velocity_limits, acceleration_limits)) = self.current_motion_limits() else {
+            return Ok(trajectory);
+        };
+        let start = TrajectoryPoint::new(self.current_joint_positions()?, Duration::ZERO);
+        let mut scaled = arci::utils::scale_trajectory_to_limits(
+            [start].into_iter().chain(trajectory).collect(),
+            &velocity_limits,
+            &acceleration_limits,
+        )?;
+        scaled.remove(0);
+        Ok(scaled)
+    }
+
+    fn resample(&self, trajectory: Vec<TrajectoryPoint>) -> Result<Vec<TrajectoryPoint>, Error> {
+        match self.resample_dt {
+            Some(dt) if !dt.is_zero() => {
+                let start = self.current_joint_positions()?;
+                Ok(arci::utils::resample_trajectory(&start, trajectory, dt))
+            }
+            _ => Ok(trajectory),
+        }
+    }
+
+    /// Converts `trajectory` of [`joint_names`](Self::set_joint_names) into the
+    /// one of all joints of the controller, holding the other joints at their
+    /// current positions.
+    fn to_controller_trajectory(
+        &self,
+        trajectory: Vec<TrajectoryPoint>,
+    ) -> Result<Vec<TrajectoryPoint>, Error> {
+        if self.joint_names == self.controller_joint_names {
+            return Ok(trajectory);
+        }
+        let current_positions = {
+            let joints = self.joint_state.read().unwrap();
+            joint_values(
+                &self.controller_joint_names,
+                &joints.joint_names,
+                &joints.actual.positions,
+            )?
+        };
+        trajectory
+            .into_iter()
+            .map(|point| {
+                let mut positions = current_positions.clone();
+                copy_joint_positions(
+                    &self.joint_names,
+                    &point.positions,
+                    &self.controller_joint_names,
+                    &mut positions,
+                )?;
+                let velocities = point
+                    .velocities
+                    .map(|velocities| {
+                        let mut full_velocities = vec![0.0; positions.len()];
+                        copy_joint_positions(
+                            &self.joint_names,
+                            &velocities,
+                            &self.controller_joint_names,
+                            &mut full_velocities,
+                        )
+                        .map(|()| full_velocities)
+                    })
+                    .transpose()?;
+                Ok(TrajectoryPoint {
+                    positions,
+                    velocities,
+                    time_from_start: point.time_from_start,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the current joint positions.
+    ///
+    /// This is the async equivalent of
+    /// [`JointTrajectoryClient::current_joint_positions`], which reads the
+    /// cached state without blocking, so this completes immediately.
+    pub async fn current_joint_positions_async(&self) -> Result<Vec<f64>, Error> {
+        JointTrajectoryClient::current_joint_positions(self)
+    }
+
+    /// Returns the status of the goal most recently accepted by the controller,
+    /// or `None` if no goal has been accepted yet.
+    ///
+    /// This can be polled while the trajectory is running, e.g., to abort and
+    /// replan depending on the progress, without waiting on the `WaitFuture`.
+    /// After the goal completes, its final status is kept until the next goal
+    /// is accepted.
+    pub fn goal_status(&self) -> Option<GoalStatus> {
+        let active_goal = self.active_goal.lock().unwrap();
+        let last_goal = active_goal.last_goal.as_ref()?;
+        Some(match last_goal.status {
+            Some(status) => status,
+            None => last_goal
+                .goal
+                .get_status()
+                .map_or(GoalStatus::Unknown, GoalStatus::from),
+        })
+    }
+
+    /// Aborts the running trajectories, e.g., on emergency stop.
+    ///
+    /// The goal accepted by the action server is canceled, and the `WaitFuture`s
+    /// of the trajectories sent before this call, including queued ones and
+    /// ones whose goal request is in flight, resolve to `Error::Canceled`.
+    /// Trajectories sent after this call are not affected.
+    pub fn abort(&self) -> Result<(), Error> {
+        let mut active_goal = self.active_goal.lock().unwrap();
+        active_goal.aborts += 1;
+        cancel_goal(&mut active_goal)
+    }
+
+    /// Returns the current joint efforts reported by the controller.
+    ///
+    /// Returns an error if the controller state does not contain efforts
+    /// (e.g., the effort array is empty).
+    pub fn current_joint_efforts(&self) -> Result<Vec<f64>, Error> {
+        let joints = self.joint_state.read().unwrap();
+        if joints.actual.effort.len() != joints.joint_names.len() {
+            return Err(Error::Other(format_err!(
+                "Controller state does not contain effort for each joint"
+            )));
+        }
+        joint_values(
+            &self.joint_names,
+            &joints.joint_names,
+            &joints.actual.effort,
+        )
+    }
+
+    /// Sends the specified joint trajectory and waits until the action completes.
+    ///
+    /// Unlike [`JointTrajectoryClient::send_joint_trajectory`], this does not
+    /// spawn a task to drive the action: the goal is sent and awaited on the
+    /// caller's task, and the operation is abandoned if the future is dropped.
+    pub async fn send_joint_trajectory_async(
+        &self,
+        trajectory: Vec<TrajectoryPoint>,
+    ) -> Result<(), Error> {
+        self.start_goal(trajectory, GoalOptions::default())?.await
+    }
+
+    /// Sends the specified joint trajectory and returns a future that waits
+    /// until the action completes, along with a receiver of the progress.
+    ///
+    /// The progress is the estimated completion fraction (from 0.0 to 1.0),
+    /// computed from `actual.time_from_start` of the action feedback and the
+    /// duration of the trajectory.
+    pub fn send_joint_trajectory_with_progress(
+        &self,
+        trajectory: Vec<TrajectoryPoint>,
+    ) -> Result<(WaitFuture, tokio::sync::watch::Receiver<f64>), Error> {
+        let (sender, receiver) = tokio::sync::watch::channel(0.0);
+        // Spawn the action so that it keeps running even if the returned future is dropped.
+        let handle = utils::spawn(self.start_goal(
+            trajectory,
+            GoalOptions {
+                progress: Some(sender),
+                ..Default::default()
+            },
+        )?);
+        let wait =
+            WaitFuture::new(async move { handle.await.map_err(|e| arci::Error::Other(e.into()))? });
+        Ok((wait, receiver))
+    }
+
+    /// Sends the specified joint trajectory to start at `start_time`.
+    ///
+    /// The goal is sent immediately, and its header stamp is set to
+    /// `start_time` converted to ROS time so that the controller begins the
+    /// execution then. This allows starting trajectories of several controllers
+    /// at the same time. If `start_time` is in the past, the trajectory starts
+    /// immediately. `time_from_start` of the points is relative to `start_time`.
+    pub fn send_joint_trajectory_at(
+        &self,
+        trajectory: Vec<TrajectoryPoint>,
+        start_time: Instant,
+    ) -> Result<WaitFuture, Error> {
+        // Spawn the action so that it keeps running even if the returned future is dropped.
+        let handle = utils::spawn(self.start_goal(
+            trajectory,
+            GoalOptions {
+                start_time: Some(start_time),
+                ..Default::default()
+            },
+        )?);
+        Ok(WaitFuture::new(async move {
+            handle.await.map_err(|e| arci::Error::Other(e.into()))?
+        }))
+    }
+
+    /// Sends the specified joint trajectory and returns a future that waits
+    /// until the action completes, along with a handle to cancel it.
+    ///
+    /// Calling [`CancelHandle::cancel`] or dropping the handle cancels the goal,
+    /// and the returned future resolves to `Error::Canceled`. Use
+    /// [`CancelHandle::detach`] to let the trajectory run to completion
+    /// without keeping the handle.
+    pub fn send_joint_trajectory_cancellable(
+        &self,
+        trajectory: Vec<TrajectoryPoint>,
+    ) -> Result<(WaitFuture, CancelHandle), Error> {
+        let cancel = Arc::new(Mutex::new(GoalCancel::default()));
+        // Spawn the action so that it keeps running even if the returned future is dropped.
+        let handle = utils::spawn(self.start_goal(
+            trajectory,
+            GoalOptions {
+                cancel: Some(cancel.clone()),
+                ..Default::default()
+            },
+        )?);
+        let wait =
+            WaitFuture::new(async move { handle.await.map_err(|e| arci::Error::Other(e.into()))? });
+        let cancel_handle = CancelHandle::new(move || {
+            let mut cancel = cancel.lock().unwrap();
+            cancel.canceled = true;
+            if let Some(goal) = cancel.goal.take() {
+                if let Ok(fut) = goal.cancel() {
+                    utils::spawn(async move {
+                        let _ = fut.await;
+                    });
+                }
+            }
+        });
+        Ok((wait, cancel_handle))
+    }
+
+    /// Applies the preemption policy and returns a future that sends the goal
+    /// and waits until the action completes.
+    ///
+    /// See [`GoalOptions`] for the options.
+    fn start_goal(
+        &self,
+        trajectory: Vec<TrajectoryPoint>,
+        options: GoalOptions,
+    ) -> Result<impl Future<Output = Result<(), Error>> + Send + 'static, Error> {
+        let GoalOptions {
+            start_time,
+            progress,
+            cancel,
+            started,
+        } = options;
+        self.check_enabled()?;
+        if trajectory.is_empty() {
+            if let Some(progress) = progress {
+                let _ = progress.send(1.0);
+            }
+            return Ok(Either::Left(future::ready(Ok(()))));
+        }
+        self.check_joint_steps(&trajectory)?;
+        let trajectory = self.slow_down_to_motion_limits(trajectory)?;
+        if let Some(trajectory_limits) = &self.trajectory_limits {
+            let (position_limits, velocity_limits): (Vec<_>, Vec<_>) = self
+                .joint_names
+                .iter()
+                .map(|joint_name| {
+                    trajectory_limits
+                        .get(joint_name)
+                        .copied()
+                        .unwrap_or((JointPositionLimit::none(), f64::INFINITY))
+                })
+                .unzip();
+            arci::utils::validate_trajectory(&trajectory, &position_limits, &velocity_limits)?;
+        }
+        let trajectory = self.resample(trajectory)?;
+        let trajectory = self.to_controller_trajectory(trajectory)?;
+        let is_available = self
+            .node
+            .r2r()
+            .is_available(&self.action_client)
+            .map_err(anyhow::Error::from)?;
+
+        let (running, aborts) = {
+            let mut active_goal = self.active_goal.lock().unwrap();
+            match self.preemption_policy {
+                PreemptionPolicy::RejectIfBusy => {
+                    if active_goal.running != 0 {
+                        return Err(Error::Other(format_err!(
+                            "Rejected a new trajectory: another trajectory is running"
+                        )));
+                    }
+                }
+                PreemptionPolicy::Preempt => {
+                    // TODO: goal is None until send_goal_request.await is complete.
+                    //       Therefore, a goal sent just before this will not be canceled.
+                    cancel_goal(&mut active_goal)?;
+                }
+                PreemptionPolicy::Queue => {}
+            }
+            active_goal.running += 1;
+            (
+                RunningGoalGuard(self.active_goal.clone()),
+                active_goal.aborts,
+            )
+        };
+        let queue = match self.preemption_policy {
+            PreemptionPolicy::Queue => Some(self.goal_queue.clone()),
+            _ => None,
+        };
+
+        let action_client = self.action_client.clone();
+        let active_goal = self.active_goal.clone();
+        let enabled = self.enabled.clone();
+        let feedback_positions = self
+            .positions_from_feedback
+            .then(|| self.feedback_positions.clone());
+        let joint_names = self.controller_joint_names.clone();
+        let goal_time_tolerance = self.goal_time_tolerance;
+        let result_timeout = self.result_timeout;
+        let progress_interval = self.progress_interval;
+        let debug_trajectory_publisher = self.debug_trajectory_publisher.clone();
+        let total_duration = trajectory
+            .last()
+            .map_or(0.0, |point| point.time_from_start.as_secs_f64());
+        Ok(Either::Right(async move {
+            let _running = running;
+            // Hold the queue lock until the action completes so that queued
+            // trajectories run one by one in the order they were sent.
+            let _queue_guard = match queue {
+                Some(queue) => Some(queue.lock_owned().await),
+                None => None,
+            };
+            if is_aborted(&active_goal, aborts) {
+                return Err(canceled_error());
+            }
+            let mut clock =
+                r2r::Clock::create(r2r::ClockType::RosTime).map_err(anyhow::Error::from)?;
+            let start_delay = start_time.map_or(Duration::ZERO, |t| {
+                t.saturating_duration_since(Instant::now())
+            });
+            let stamp = clock.get_now().map_err(anyhow::Error::from)? + start_delay;
+            let goal = FollowJointTrajectory::Goal {
+                trajectory: trajectory_msg::JointTrajectory {
+                    joint_names,
+                    points: trajectory.iter().map(to_ros2_trajectory_point).collect(),
+                    header: Header {
+                        stamp: to_ros2_time(stamp)?,
+                        ..Default::default()
+                    },
+                },
+                goal_time_tolerance: goal_time_tolerance
+                    .map(to_ros2_duration)
+                    .unwrap_or_default(),
+                ..Default::default()
+            };
+            if let Some(publisher) = &debug_trajectory_publisher {
+                if let Err(e) = publisher.lock().unwrap().publish(&goal.trajectory) {
+                    debug!("Failed to publish the debug trajectory: {e}");
+                }
+            }
+            is_available.await.map_err(anyhow::Error::from)?;
+            if is_canceled(&cancel) || is_aborted(&active_goal, aborts) {
+                return Err(canceled_error());
+            }
+            let send_goal_request = action_client
+                .send_goal_request(goal)
+                .map_err(anyhow::Error::from)?;
+            let (goal, result, feedback) = send_goal_request.await.map_err(anyhow::Error::from)?;
+            let uuid = goal.uuid;
+            {
+                let mut active_goal = active_goal.lock().unwrap();
+                if active_goal.aborts != aborts {
+                    // Aborted while the goal request was in flight.
+                    let fut = goal.cancel().map_err(|e| Error::Other(e.into()))?;
+                    utils::spawn(async move {
+                        let _ = fut.await;
+                    });
+                    return Err(canceled_error());
+                }
+                active_goal.last_goal = Some(LastGoal {
+                    goal: goal.clone(),
+                    status: None,
+                });
+                active_goal.goal = Some(goal.clone());
+            }
+            // Claim the feedback slot so that the feedback of the goal
+            // preempted by this one doesn't overwrite it, and its completion
+            // doesn't clear it.
+            let feedback_generation = feedback_positions.as_ref().map(|feedback_positions| {
+                let mut feedback_positions = feedback_positions.write().unwrap();
+                feedback_positions.generation += 1;
+                feedback_positions.positions = None;
+                feedback_positions.generation
+            });
+            if let Some(cancel) = &cancel {
+                let mut cancel = cancel.lock().unwrap();
+                if cancel.canceled {
+                    // Canceled while the goal request was in flight.
+                    let fut = goal.cancel().map_err(|e| Error::Other(e.into()))?;
+                    utils::spawn(async move {
+                        let _ = fut.await;
+                    });
+                } else {
+                    cancel.goal = Some(goal);
+                }
+            }
+            let progress = progress.map(Arc::new);
+            let progress_clone = progress.clone();
+            let active_goal_clone = active_goal.clone();
+            let enabled_clone = enabled.clone();
+            let feedback_positions_clone = feedback_positions.clone();
+            let mut last_progress: Option<Instant> = None;
+            let mut started = started;
+            utils::spawn(async move {
+                feedback
+                    .for_each(|feedback| {
+                        if let Some(started) = started.take() {
+                            let _ = started.send(());
+                        }
+                        if !enabled_clone.load(Ordering::SeqCst) {
+                            let _ = cancel_active_goal(&active_goal_clone);
+                        }
+                        if let (Some(feedback_positions), Some(generation)) =
+                            (&feedback_positions_clone, feedback_generation)
+                        {
+                            let mut feedback_positions = feedback_positions.write().unwrap();
+                            if feedback_positions.generation == generation {
+                                feedback_positions.positions = Some((
+                                    feedback.joint_names.clone(),
+                                    feedback.actual.positions.clone(),
+                                ));
+                            }
+                        }
+                        let throttled = matches!(
+                            (progress_interval, last_progress),
+                            (Some(interval), Some(last)) if last.elapsed() < interval
+                        );
+                        if let (Some(progress), false) = (&progress_clone, throttled) {
+                            last_progress = Some(Instant::now());
+                            let elapsed =
+                                from_ros2_duration(&feedback.actual.time_from_start).as_secs_f64();
+                            let fraction = if total_duration > 0.0 {
+                                (elapsed / total_duration).clamp(0.0, 1.0)
+                            } else {
+                                1.0
+                            };
+                            let _ = progress.send(fraction);
+                        }
+                        std::future::ready(())
+                    })
+                    .await
+            });
+            let result = match result_timeout {
+                Some(timeout) => tokio::time::timeout(
+                    start_delay + Duration::from_secs_f64(total_duration) + timeout,
+                    result,
+                )
+                .await
+                .ok(),
+                None => Some(result.await),
+            };
+            if let (Some(feedback_positions), Some(generation)) =
+                (&feedback_positions, feedback_generation)
+            {
+                let mut feedback_positions = feedback_positions.write().unwrap();
+                if feedback_positions.generation == generation {
+                    feedback_positions.positions = None;
+                }
+            }
+            {
+                let mut active_goal = active_goal.lock().unwrap();
+                if active_goal.goal.as_ref().map(|goal| goal.uuid) == Some(uuid) {
+                    if result.is_none() {
+                        cancel_goal(&mut active_goal)?;
+                    }
+                    active_goal.goal = None;
+                }
+                if let Some(last_goal) = &mut active_goal.last_goal {
+                    if last_goal.goal.uuid == uuid {
+                        if let Some(Ok((status, _))) = &result {
+                            last_goal.status = Some((*status).into());
+                        }
+                    }
+                }
+            }
+            if let Some(cancel) = &cancel {
+                cancel.lock().unwrap().goal = None;
+            }
+            if is_aborted(&active_goal, aborts) {
+                return Err(canceled_error());
+            }
+            let Some(result) = result else {
+                return Err(Error::Other(format_err!("result timeout")));
+            };
+            let (status, result) = result.map_err(anyhow::Error::from)?;
+            if is_canceled(&cancel) {
+                return Err(canceled_error());
+            }
+            if !enabled.load(Ordering::SeqCst) {
+                return Err(Error::Other(format_err!("disabled")));
+            }
+            check_result(status, &result)?;
+            if let Some(progress) = progress {
+                let _ = progress.send(1.0);
+            }
+            Ok(())
+        }))
+    }
+}
+
+/// Policy applied when a new trajectory is sent to [`Ros2ControlClient`] while
+/// another one is still running.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum PreemptionPolicy {
+    /// Returns an error for the new trajectory.
+    RejectIfBusy,
+    /// Cancels the running trajectory, whose `WaitFuture` resolves to
+    /// `Error::Canceled`, and sends the new one.
+    #[default]
+    Preempt,
+    /// Sends the new trajectory after the running ones are complete.
+    Queue,
+}
+
+/// Options of a goal sent by [`Ros2ControlClient::start_goal`].
+#[derive(Default)]
+struct GoalOptions {
+    /// The time to start the goal, or immediately if `None`.
+    start_time: Option<Instant>,
+    /// The sender of the estimated completion fraction.
+    progress: Option<tokio::sync::watch::Sender<f64>>,
+    /// The cancellation state shared with a [`CancelHandle`].
+    cancel: Option<Arc<Mutex<GoalCancel>>>,
+    /// The sender notified on the first feedback.
+    started: Option<tokio::sync::oneshot::Sender<()>>,
+}
+
+#[derive(Default)]
+struct GoalCancel {
+    /// Whether the goal has been canceled via the handle.
+    canceled: bool,
+    /// The goal accepted by the action server and not yet complete.
+    goal: Option<r2r::ActionClientGoal<FollowJointTrajectory::Action>>,
+}
+
+fn is_canceled(cancel: &Option<Arc<Mutex<GoalCancel>>>) -> bool {
+    cancel
+        .as_ref()
+        .map_or(false, |cancel| cancel.lock().unwrap().canceled)
+}
+
+/// Returns `true` if [`Ros2ControlClient::abort`] has been called since
+/// `aborts` was read.
+fn is_aborted(active_goal: &Mutex<ActiveGoal>, aborts: u64) -> bool {
+    active_goal.lock().unwrap().aborts != aborts
+}
+
+fn canceled_error() -> Error {
+    Error::Canceled {
+        message: "The trajectory was canceled".to_owned(),
+    }
+}
+
+#[derive(Default)]
+struct ActiveGoal {
+    /// The number of trajectories sent and not yet complete.
+    running: usize,
+    /// The number of calls to [`Ros2ControlClient::abort`].
+    aborts: u64,
+    /// The goal most recently accepted by the action server.
+    goal: Option<r2r::ActionClientGoal<FollowJointTrajectory::Action>>,
+    /// The goal most recently accepted by the action server, kept after
+    /// completion or cancellation for [`Ros2ControlClient::goal_status`].
+    last_goal: Option<LastGoal>,
+}
+
+/// Joint positions of the latest feedback, and the goal they belong to.
+#[derive(Default)]
+struct FeedbackPositions {
+    /// Incremented for each accepted goal, which owns the slot until the next.
+    generation: u64,
+    /// Joint names and positions of the latest feedback of the owning goal.
+    positions: Option<(Vec<String>, Vec<f64>)>,
+}
+
+struct LastGoal {
+    goal: r2r::ActionClientGoal<FollowJointTrajectory::Action>,
+    /// The final status reported with the result, if received.
+    status: Option<GoalStatus>,
+}
+
+/// Status of a goal sent by [`Ros2ControlClient`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GoalStatus {
+    /// The status is not known.
+    Unknown,
+    /// The goal has been accepted and is awaiting execution.
+    Accepted,
+    /// The goal is being executed.
+    Executing,
+    /// The goal has been requested to be canceled and is being canceled.
+    Canceling,
+    /// The goal has been completed successfully.
+    Succeeded,
+    /// The goal has been canceled, e.g., preempted by another goal.
+    Canceled,
+    /// The goal has been aborted by the controller.
+    Aborted,
+}
+
+impl GoalStatus {
+    /// Returns `true` if the goal is complete, successfully or not.
+    pub fn is_terminal(self) -> bool {
+        matches!(self, Self::Succeeded | Self::Canceled | Self::Aborted)
+    }
+}
+
+impl From<r2r::GoalStatus> for GoalStatus {
+    fn from(status: r2r::GoalStatus) -> Self {
+        match status {
+            r2r::GoalStatus::Unknown => Self::Unknown,
+            r2r::GoalStatus::Accepted => Self::Accepted,
+            r2r::GoalStatus::Executing => Self::Executing,
+            r2r::GoalStatus::Canceling => Self::Canceling,
+            r2r::GoalStatus::Succeeded => Self::Succeeded,
+            r2r::GoalStatus::Canceled => Self::Canceled,
+            r2r::GoalStatus::Aborted => Self::Aborted,
+        }
+    }
+}
+
+/// Returns the minimum duration to move by `distance` from rest to rest within
+/// `max_velocity` and `max_acceleration`, with a trapezoidal velocity profile.
+fn min_move_duration(distance: f64, max_velocity: f64, max_acceleration: f64) -> f64 {
+    if max_velocity.is_infinite() && max_acceleration.is_infinite() {
+        0.0
+    } else if distance <= max_velocity * max_velocity / max_acceleration {
+        // The max velocity is not reached.
+        2.0 * (distance / max_acceleration).sqrt()
+    } else {
+        distance / max_velocity + max_velocity / max_acceleration
+    }
+}
+
+/// Returns the values of `joint_names` from `values` ordered by
+/// `state_joint_names`.
+///
+/// Returns `Error::NoJoint` if a joint is not in `state_joint_names`.
+fn joint_values(
+    joint_names: &[String],
+    state_joint_names: &[String],
+    values: &[f64],
+) -> Result<Vec<f64>, Error> {
+    joint_names
+        .iter()
+        .map(|name| {
+            let i = state_joint_names
+                .iter()
+                .position(|n| n == name)
+                .ok_or_else(|| Error::NoJoint(name.clone()))?;
+            values.get(i).copied().ok_or_else(|| Error::Connection {
+                message: format!("The controller state does not contain the value of {name}"),
+            })
         })
+        .collect()
+}
+
+/// Checks that the positions of all `joint_names` are reported in `state`.
+fn validate_joint_names(
+    joint_names: &[String],
+    state: &JointTrajectoryControllerState,
+) -> Result<(), Error> {
+    joint_values(joint_names, &state.joint_names, &state.actual.positions).map(|_| ())
+}
+
+/// Converts the result of a goal into an error unless it succeeded.
+///
+/// A canceled goal (e.g., preempted by another goal) is `Error::Canceled`, and a
+/// non-zero `error_code` or an aborted goal is `Error::Other` describing the
+/// reason reported by the controller.
+fn check_result(
+    status: r2r::GoalStatus,
+    result: &FollowJointTrajectory::Result,
+) -> Result<(), Error> {
+    let reason = match result.error_code {
+        FollowJointTrajectory::Result::SUCCESSFUL => None,
+        FollowJointTrajectory::Result::INVALID_GOAL => Some("invalid goal"),
+        FollowJointTrajectory::Result::INVALID_JOINTS => Some("invalid joints"),
+        FollowJointTrajectory::Result::OLD_HEADER_TIMESTAMP => Some("old header timestamp"),
+        FollowJointTrajectory::Result::PATH_TOLERANCE_VIOLATED => Some("path tolerance violated"),
+        FollowJointTrajectory::Result::GOAL_TOLERANCE_VIOLATED => Some("goal tolerance violated"),
+        _ => Some("unknown error"),
+    };
+    match (status, reason) {
+        (r2r::GoalStatus::Canceled, _) => Err(Error::Canceled {
+            message: format!("The trajectory was canceled: {}", result.error_string),
+        }),
+        (_, Some(reason)) => Err(Error::Other(format_err!(
+            "FollowJointTrajectory failed with {reason} (error_code = {}): {}",
+            result.error_code,
+            result.error_string
+        ))),
+        (r2r::GoalStatus::Succeeded, None) => Ok(()),
+        (status, None) => Err(Error::Other(format_err!(
+            "FollowJointTrajectory finished with status {status:?}: {}",
+            result.error_string
+        ))),
+    }
+}
+
+/// Cancels the goal most recently accepted by the action server, if any.
+fn cancel_goal(active_goal: &mut ActiveGoal) -> Result<(), Error> {
+    if let Some(goal) = active_goal.goal.take() {
+        let fut = goal.cancel().map_err(|e| Error::Other(e.into()))?;
+        utils::spawn(async move {
+            let _ = fut.await;
+        });
+    }
+    Ok(())
+}
+
+fn cancel_active_goal(active_goal: &Mutex<ActiveGoal>) -> Result<(), Error> {
+    cancel_goal(&mut active_goal.lock().unwrap())
+}
+
+/// Marks a trajectory as complete when dropped.
+struct RunningGoalGuard(Arc<Mutex<ActiveGoal>>);
+
+impl Drop for RunningGoalGuard {
+    fn drop(&mut self) {
+        self.0.lock().unwrap().running -= 1;
     }
 }
 
@@ -68,15 +1174,25 @@ impl JointTrajectoryClient for Ros2ControlClient {
         self.joint_names.clone()
     }
 
+    /// Returns the current joint positions from the cached controller state
+    /// (or the action feedback), so this does not block and can also be
+    /// called from async code.
     fn current_joint_positions(&self) -> Result<Vec<f64>, arci::Error> {
+        if self.positions_from_feedback {
+            if let Some((joint_names, positions)) =
+                &self.feedback_positions.read().unwrap().positions
+            {
+                if let Ok(positions) = joint_values(&self.joint_names, joint_names, positions) {
+                    return Ok(positions);
+                }
+            }
+        }
         let joints = self.joint_state.read().unwrap();
-        Ok(self
-            .joint_names
-            .iter()
-            .map(|name| {
-                joints.actual.positions[joints.joint_names.iter().position(|n| n == name).unwrap()]
-            })
-            .collect())
+        joint_values(
+            &self.joint_names,
+            &joints.joint_names,
+            &joints.actual.positions,
+        )
     }
 
     fn send_joint_positions(
@@ -84,6 +1200,27 @@ impl JointTrajectoryClient for Ros2ControlClient {
         positions: Vec<f64>,
         duration: Duration,
     ) -> Result<WaitFuture, arci::Error> {
+        let duration = match self.current_motion_limits() {
+            Some((velocity_limits, acceleration_limits)) => {
+                let current = self.current_joint_positions()?;
+                if positions.len() != current.len() {
+                    return Err(Error::LengthMismatch {
+                        model: current.len(),
+                        input: positions.len(),
+                    });
+                }
+                let min_duration = current
+                    .iter()
+                    .zip(&positions)
+                    .zip(velocity_limits.iter().zip(acceleration_limits))
+                    .map(|((p0, p1), (&v, &a))| min_move_duration((p1 - p0).abs(), v, a))
+                    .fold(duration.as_secs_f64(), f64::max);
+                Duration::try_from_secs_f64(min_duration).map_err(|e| {
+                    Error::Other(format_err!("invalid duration {min_duration}: {e}"))
+                })?
+            }
+            None => duration,
+        };
         self.send_joint_trajectory(vec![TrajectoryPoint {
             positions,
             velocities: None,
@@ -95,64 +1232,32 @@ impl JointTrajectoryClient for Ros2ControlClient {
         &self,
         trajectory: Vec<TrajectoryPoint>,
     ) -> Result<WaitFuture, arci::Error> {
-        let node = self.node.clone();
-        let action_client = self.action_client.clone();
-        let is_available = node.r2r().is_available(&self.action_client).unwrap();
-        let (sender, receiver) = tokio::sync::oneshot::channel();
-        let joint_names = self.joint_names.clone();
-        tokio::spawn(async move {
-            let is_done = Arc::new(AtomicBool::new(false));
-            let is_done_clone = is_done.clone();
-            tokio::spawn(async move {
-                let mut clock = r2r::Clock::create(r2r::ClockType::RosTime).unwrap();
-                let now = clock.get_now().unwrap();
-                let goal = FollowJointTrajectory::Goal {
-                    trajectory: trajectory_msg::JointTrajectory {
-                        joint_names,
-                        points: trajectory
-                            .into_iter()
-                            .map(|tp| trajectory_msg::JointTrajectoryPoint {
-                                velocities: tp
-                                    .velocities
-                                    .unwrap_or_else(|| vec![0.0; tp.positions.len()]),
-                                positions: tp.positions,
-                                time_from_start: builtin_msg::Duration {
-                                    sec: tp
-                                        .time_from_start
-                                        .as_secs()
-                                        .try_into()
-                                        .unwrap_or(i32::MAX),
-                                    nanosec: tp.time_from_start.subsec_nanos(),
-                                },
-                                ..Default::default()
-                            })
-                            .collect(),
-                        header: Header {
-                            stamp: Time {
-                                sec: now.as_secs() as i32,
-                                nanosec: now.subsec_nanos(),
-                            },
-                            ..Default::default()
-                        },
-                    },
-                    ..Default::default()
-                };
-                is_available.await.unwrap();
-                let send_goal_request = action_client.send_goal_request(goal).unwrap();
-                let (_goal, result, feedback) = send_goal_request.await.unwrap();
-                tokio::spawn(async move { feedback.for_each(|_| std::future::ready(())).await });
-                result.await.unwrap(); // TODO: handle goal state
-                is_done.store(true, Ordering::Relaxed);
+        let (started, wait_started) = match self.wait_for_first_feedback {
+            Some(timeout) => {
+                let (sender, receiver) = tokio::sync::oneshot::channel();
+                (Some(sender), Some((receiver, timeout)))
+            }
+            None => (None, None),
+        };
+        // Spawn the action so that it keeps running even if the returned future is dropped.
+        let handle = utils::spawn(self.start_goal(
+            trajectory,
+            GoalOptions {
+                started,
+                ..Default::default()
+            },
+        )?);
+        if let Some((receiver, timeout)) = wait_started {
+            utils::block_on(async move {
+                // The sender is dropped when the action completes or fails without feedback.
+                if tokio::time::timeout(timeout, receiver).await.is_err() {
+                    debug!("No feedback arrived within {timeout:?}");
+                }
             });
-            utils::wait(is_done_clone).await;
-            // TODO: "canceled" should be an error?
-            let _ = sender.send(());
-        });
-        let wait =
-            WaitFuture::new(
-                async move { receiver.await.map_err(|e| arci::Error::Other(e.into())) },
-            );
-        Ok(wait)
+        }
+        Ok(WaitFuture::new(async move {
+            handle.await.map_err(|e| arci::Error::Other(e.into()))?
+        }))
     }
 }
 
@@ -162,7 +1267,81 @@ impl JointTrajectoryClient for Ros2ControlClient {
 pub struct Ros2ControlConfig {
     /// Action name for control_msgs/FollowJointTrajectory.
     pub action_name: String,
-    /// Names of joints.
+    /// Names of the joints to command, a subset of the joints of the controller. All joints of the controller if empty.
     #[serde(default)]
     pub joint_names: Vec<String>,
+    /// Time resolution to resample trajectories at before sending. Disabled if not set.
+    #[serde(default)]
+    pub resample_dt: Option<Duration>,
+    /// Policy applied when a new trajectory is sent while another one is running.
+    #[serde(default)]
+    pub preemption_policy: PreemptionPolicy,
+    /// Time tolerance allowed to reach the goal after the last point. The controller's default is used if not set.
+    #[serde(default)]
+    pub goal_time_tolerance: Option<Duration>,
+    /// Maximum velocities of the joints to slow down trajectories to. Not limited if not set.
+    #[serde(default)]
+    pub velocity_limits: Option<Vec<f64>>,
+    /// Maximum accelerations of the joints to slow down trajectories to. Not limited if not set.
+    #[serde(default)]
+    pub acceleration_limits: Option<Vec<f64>>,
+    /// Maximum joint position difference allowed between consecutive trajectory points. Not limited if not set.
+    #[serde(default)]
+    pub max_joint_step: Option<f64>,
+    /// Report the current joint positions from the action feedback while a trajectory is running.
+    #[serde(default)]
+    pub positions_from_feedback: bool,
+    /// Time to wait for the action result after the duration of the trajectory. Waits indefinitely if not set.
+    #[serde(default)]
+    pub result_timeout: Option<Duration>,
+    /// Minimum interval between progress updates. Updated on every feedback if not set.
+    #[serde(default)]
+    pub progress_interval: Option<Duration>,
+    /// Time to wait for the state of the controller on startup.
+    #[serde(default = "default_state_timeout")]
+    pub state_timeout: Duration,
+    /// Number of times to retry waiting for the state of the controller on startup.
+    #[serde(default)]
+    pub state_max_retry: usize,
+    /// QoS profile to subscribe the state topic with. See [`QosPreset`].
+    #[serde(default)]
+    pub state_qos: QosPreset,
+    /// Maximum time to block sending until the first feedback arrives. Returns immediately if not set.
+    #[serde(default)]
+    pub wait_for_first_feedback: Option<Duration>,
+    /// Topic to publish the trajectory of each sent goal to for visualization. Not published if not set.
+    #[serde(default)]
+    pub debug_trajectory_topic: Option<String>,
+}
+
+impl Ros2ControlConfig {
+    /// Creates a `Ros2ControlClient` from this configuration.
+    pub fn build(&self, node: Node) -> Result<Ros2ControlClient, Error> {
+        let mut client = Ros2ControlClient::with_qos(
+            node,
+            &self.action_name,
+            self.state_timeout,
+            self.state_max_retry,
+            self.state_qos.into(),
+        )?;
+        client.set_resample_dt(self.resample_dt);
+        client.set_preemption_policy(self.preemption_policy);
+        client.set_goal_time_tolerance(self.goal_time_tolerance);
+        client.set_result_timeout(self.result_timeout);
+        client.set_progress_interval(self.progress_interval);
+        client.set_wait_for_first_feedback(self.wait_for_first_feedback);
+        client.set_max_joint_step(self.max_joint_step);
+        client.set_positions_from_feedback(self.positions_from_feedback);
+        client.set_debug_trajectory_topic(self.debug_trajectory_topic.as_deref())?;
+        client.set_joint_names(self.joint_names.clone())?;
+        client.set_motion_limits(
+            self.velocity_limits.clone(),
+            self.acceleration_limits.clone(),
+        )?;
+        Ok(client)
+    }
+}
+
+fn default_state_timeout() -> Duration {
+    Duration::from_secs(1)
 }
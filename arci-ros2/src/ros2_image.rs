@@ -0,0 +1,120 @@
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use arci::Error;
+use r2r::{sensor_msgs::msg::Image, QosProfile};
+
+use crate::{utils, Node};
+
+/// Subscriber of sensor_msgs/Image that keeps the latest frame.
+pub struct Ros2Image {
+    image: Arc<RwLock<Option<Image>>>,
+    image_topic_name: String,
+    // keep not to be dropped
+    _node: Node,
+}
+
+impl Ros2Image {
+    /// Creates a new `Ros2Image` from sensor_msgs/Image topic name.
+    pub fn new(node: Node, image_topic_name: &str) -> Result<Self, Error> {
+        Self::with_qos(node, image_topic_name, QosProfile::default())
+    }
+
+    /// Creates a new `Ros2Image` subscribing the topic with the given QoS profile.
+    ///
+    /// Camera drivers often publish with `QosProfile::sensor_data()`, which is
+    /// not received by the default reliable subscriber.
+    pub fn with_qos(node: Node, image_topic_name: &str, qos: QosProfile) -> Result<Self, Error> {
+        let mut image_subscriber = node
+            .r2r()
+            .subscribe::<Image>(image_topic_name, qos)
+            .map_err(anyhow::Error::from)?;
+        let image = utils::subscribe_one(&mut image_subscriber, Duration::from_secs(1));
+        let image = Arc::new(RwLock::new(image));
+        utils::subscribe_thread(image_subscriber, image.clone(), Some);
+
+        Ok(Self {
+            image,
+            image_topic_name: image_topic_name.to_owned(),
+            _node: node,
+        })
+    }
+
+    /// Returns `(width, height, step, encoding, data)` of the latest frame.
+    ///
+    /// `data` is the raw buffer of the message, so each row may be followed by
+    /// padding bytes. `step` is the length of a row in bytes.
+    pub fn current_image(&self) -> Result<(u32, u32, u32, String, Vec<u8>), Error> {
+        match &*self.image.read().unwrap() {
+            Some(msg) => Ok((
+                msg.width,
+                msg.height,
+                msg.step,
+                msg.encoding.clone(),
+                msg.data.clone(),
+            )),
+            None => Err(Error::Connection {
+                message: format!("Failed to get image from {}", self.image_topic_name),
+            }),
+        }
+    }
+}
+
+/// Converts a frame returned by [`Ros2Image::current_image`] into a packed
+/// `rgb8` buffer of `width * height * 3` bytes.
+///
+/// `step` is the length of a row of `data` in bytes, including the padding.
+/// Supported encodings are `rgb8`, `bgr8` and `mono8`.
+pub fn image_to_rgb8(
+    width: u32,
+    height: u32,
+    step: u32,
+    encoding: &str,
+    data: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let channels = match encoding {
+        "rgb8" | "bgr8" => 3,
+        "mono8" => 1,
+        _ => {
+            return Err(Error::Other(anyhow::format_err!(
+                "Unsupported image encoding: {encoding}"
+            )));
+        }
+    };
+    let (width, height, step) = (width as usize, height as usize, step as usize);
+    if height == 0 || width == 0 {
+        return Ok(vec![]);
+    }
+    if step < width * channels {
+        return Err(Error::Other(anyhow::format_err!(
+            "Image step is too short: {step} bytes for {width} pixels of {encoding}"
+        )));
+    }
+    if data.len() < step * (height - 1) + width * channels {
+        return Err(Error::Other(anyhow::format_err!(
+            "Image data is too short: {} bytes for {width}x{height} {encoding} with step {step}",
+            data.len()
+        )));
+    }
+
+    let mut rgb = Vec::with_capacity(width * height * 3);
+    for row in data.chunks(step).take(height) {
+        let row = &row[..width * channels];
+        match encoding {
+            "rgb8" => rgb.extend_from_slice(row),
+            "bgr8" => {
+                for pixel in row.chunks_exact(3) {
+                    rgb.extend_from_slice(&[pixel[2], pixel[1], pixel[0]]);
+                }
+            }
+            _ => {
+                for &v in row {
+                    rgb.extend_from_slice(&[v, v, v]);
+                }
+            }
+        }
+    }
+    Ok(rgb)
+}
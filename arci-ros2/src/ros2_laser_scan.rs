@@ -1,67 +1,304 @@
-use std::{
-    sync::{Arc, RwLock},
-    time::Duration,
-};
+use std::time::{Duration, SystemTime};
 
 use arci::*;
 use r2r::{sensor_msgs::msg::LaserScan, QosProfile};
 use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
 
-use crate::{utils, Node};
+use crate::{utils, Node, QosPreset};
 
 /// `arci::LaserScan2D` implementation for ROS2.
 pub struct Ros2LaserScan2D {
-    scan: Arc<RwLock<Option<LaserScan>>>,
+    scan: watch::Receiver<Option<LaserScan>>,
     laser_scan_topic_name: String,
+    target_frame: Option<String>,
+    min_intensity: Option<f64>,
+    max_beams: Option<usize>,
+    timeout: Duration,
     // keep not to be dropped
     _node: Node,
 }
 
 impl Ros2LaserScan2D {
     /// Creates a new `Ros2LaserScan2D` from sensor_msgs/LaserScan topic name.
+    ///
+    /// This waits for the first scan for 1 second. See
+    /// [`with_timeout`](Self::with_timeout) to wait longer.
     pub fn new(node: Node, laser_scan_topic_name: &str) -> Result<Self, Error> {
+        Self::with_timeout(node, laser_scan_topic_name, Duration::from_secs(1))
+    }
+
+    /// Creates a new `Ros2LaserScan2D` from sensor_msgs/LaserScan topic name,
+    /// waiting for the first scan for `timeout`.
+    ///
+    /// The topic is subscribed once, and the latest scan is kept updated in
+    /// the background, so reading scans never waits for a new message. Reading
+    /// scans returns `Error::Connection` only if no scan has arrived yet, after
+    /// waiting for one for `timeout` again.
+    pub fn with_timeout(
+        node: Node,
+        laser_scan_topic_name: &str,
+        timeout: Duration,
+    ) -> Result<Self, Error> {
+        Self::with_qos(node, laser_scan_topic_name, timeout, QosProfile::default())
+    }
+
+    /// Creates a new `Ros2LaserScan2D` from sensor_msgs/LaserScan topic name,
+    /// subscribing it with `qos`.
+    ///
+    /// See [`with_timeout`](Self::with_timeout) for `timeout`.
+    pub fn with_qos(
+        node: Node,
+        laser_scan_topic_name: &str,
+        timeout: Duration,
+        qos: QosProfile,
+    ) -> Result<Self, Error> {
         let mut scan_subscriber = node
             .r2r()
-            .subscribe::<LaserScan>(laser_scan_topic_name, QosProfile::default())
+            .subscribe::<LaserScan>(laser_scan_topic_name, qos)
             .map_err(anyhow::Error::from)?;
-        let scan = utils::subscribe_one(&mut scan_subscriber, Duration::from_secs(1));
-        let scan = Arc::new(RwLock::new(scan));
-        utils::subscribe_thread(scan_subscriber, scan.clone(), Some);
+        let scan = utils::subscribe_one(&mut scan_subscriber, timeout);
+        let (sender, scan) = watch::channel(scan);
+        utils::subscribe_watch(scan_subscriber, sender, Some);
 
         Ok(Self {
             scan,
             laser_scan_topic_name: laser_scan_topic_name.to_owned(),
+            target_frame: None,
+            min_intensity: None,
+            max_beams: None,
+            timeout,
             _node: node,
         })
     }
+
+    /// Sets the frame to place the points of the scans in. See
+    /// [`sensor_transform`](Self::sensor_transform).
+    pub fn set_target_frame(&mut self, target_frame: Option<String>) {
+        self.target_frame = target_frame;
+    }
+
+    /// Returns the frame to place the points of the scans in.
+    pub fn target_frame(&self) -> Option<&str> {
+        self.target_frame.as_deref()
+    }
+
+    /// Sets the minimum intensity of the valid beams.
+    ///
+    /// The ranges of the beams whose intensity is below `min_intensity` are
+    /// set to NaN, so they are skipped by [`Scan2D::finite_ranges`]. This has
+    /// no effect on scans without intensities.
+    pub fn set_min_intensity(&mut self, min_intensity: Option<f64>) {
+        self.min_intensity = min_intensity;
+    }
+
+    /// Returns the minimum intensity of the valid beams.
+    pub fn min_intensity(&self) -> Option<f64> {
+        self.min_intensity
+    }
+
+    /// Sets the maximum number of beams of a scan.
+    ///
+    /// Scans with more beams (or intensities) than `max_beams` are regarded
+    /// as malformed, and an error is returned instead of converting them.
+    ///
+    /// Note that this only rejects such scans when they are read, and does
+    /// not bound the memory used by the subscription: the latest message is
+    /// received and kept in full regardless of its size.
+    pub fn set_max_beams(&mut self, max_beams: Option<usize>) {
+        self.max_beams = max_beams;
+    }
+
+    /// Returns the maximum number of beams of a scan.
+    pub fn max_beams(&self) -> Option<usize> {
+        self.max_beams
+    }
+
+    /// Calls `f` with the latest scan, blocking the current thread until one
+    /// arrives if no scan has arrived yet.
+    fn latest_scan<T>(&self, f: impl FnOnce(&LaserScan) -> T) -> Result<T, Error> {
+        if let Some(msg) = &*self.scan.borrow() {
+            return Ok(f(msg));
+        }
+        utils::block_on(self.latest_scan_async(f))
+    }
+
+    /// Calls `f` with the latest scan, waiting for the subscriber to receive
+    /// one if no scan has arrived yet.
+    async fn latest_scan_async<T>(&self, f: impl FnOnce(&LaserScan) -> T) -> Result<T, Error> {
+        let mut scan = self.scan.clone();
+        let received = tokio::time::timeout(self.timeout, async {
+            while scan.borrow().is_none() {
+                if scan.changed().await.is_err() {
+                    // The subscription has ended.
+                    return false;
+                }
+            }
+            true
+        })
+        .await;
+        if !matches!(received, Ok(true)) {
+            return Err(self.no_scan_error());
+        }
+        let msg = scan.borrow();
+        Ok(f(msg.as_ref().unwrap()))
+    }
+
+    fn no_scan_error(&self) -> Error {
+        Error::Connection {
+            message: format!("Failed to get scan from {}", self.laser_scan_topic_name),
+        }
+    }
+
+    /// Returns the frame of the sensor (`header.frame_id`) of the latest scan.
+    pub fn frame_id(&self) -> Result<String, Error> {
+        self.latest_scan(|msg| msg.header.frame_id.clone())
+    }
+
+    /// Returns the pose of the sensor in the target frame at the time of the
+    /// latest scan, resolved by `transform_resolver`.
+    ///
+    /// Returns an error if the target frame is not set.
+    pub fn sensor_transform(
+        &self,
+        transform_resolver: &dyn TransformResolver,
+    ) -> Result<Isometry3<f64>, Error> {
+        let (frame_id, stamp) =
+            self.latest_scan(|msg| (msg.header.frame_id.clone(), header_stamp(msg)))?;
+        self.resolve_sensor_transform(transform_resolver, &frame_id, stamp)
+    }
+
+    fn resolve_sensor_transform(
+        &self,
+        transform_resolver: &dyn TransformResolver,
+        frame_id: &str,
+        stamp: Option<SystemTime>,
+    ) -> Result<Isometry3<f64>, Error> {
+        let Some(target_frame) = &self.target_frame else {
+            return Err(Error::Other(anyhow::format_err!(
+                "Target frame is not set for {}",
+                self.laser_scan_topic_name
+            )));
+        };
+        let Some(stamp) = stamp else {
+            return Err(Error::Other(anyhow::format_err!(
+                "Scan from {} has an out of range stamp",
+                self.laser_scan_topic_name
+            )));
+        };
+        transform_resolver.resolve_transformation(target_frame, frame_id, stamp)
+    }
+
+    /// Returns the positions of the valid beams of the latest scan in the
+    /// target frame.
+    ///
+    /// Invalid beams are skipped as in [`Scan2D::finite_ranges`]. This is
+    /// useful to fuse scans of multiple sensors in the frame of the robot base.
+    pub fn current_points(
+        &self,
+        transform_resolver: &dyn TransformResolver,
+    ) -> Result<Vec<Vector3<f64>>, Error> {
+        let (scan, frame_id, stamp) = self.latest_scan(|msg| {
+            (
+                self.convert_scan(msg),
+                msg.header.frame_id.clone(),
+                header_stamp(msg),
+            )
+        })?;
+        let scan = scan?;
+        let transform = self.resolve_sensor_transform(transform_resolver, &frame_id, stamp)?;
+        Ok(scan
+            .finite_ranges()
+            .map(|(angle, range)| {
+                transform * Vector3::new(range * angle.cos(), range * angle.sin(), 0.0)
+            })
+            .collect())
+    }
+
+    fn convert_scan(&self, msg: &LaserScan) -> Result<Scan2D, Error> {
+        if let Some(max_beams) = self.max_beams {
+            let num_beams = msg.ranges.len().max(msg.intensities.len());
+            if num_beams > max_beams {
+                return Err(Error::Other(anyhow::format_err!(
+                    "Scan from {} has {num_beams} beams, exceeding the maximum {max_beams}",
+                    self.laser_scan_topic_name
+                )));
+            }
+        }
+        Ok(to_scan2d(msg, self.min_intensity))
+    }
+}
+
+/// Returns `header.stamp` of `msg`, or `None` if it is out of the range of
+/// `SystemTime`.
+fn header_stamp(msg: &LaserScan) -> Option<SystemTime> {
+    let stamp = &msg.header.stamp;
+    let sec = Duration::from_secs(stamp.sec.unsigned_abs().into());
+    let time = if stamp.sec >= 0 {
+        SystemTime::UNIX_EPOCH.checked_add(sec)?
+    } else {
+        SystemTime::UNIX_EPOCH.checked_sub(sec)?
+    };
+    time.checked_add(Duration::from_nanos(stamp.nanosec.into()))
+}
+
+fn to_scan2d(msg: &LaserScan, min_intensity: Option<f64>) -> Scan2D {
+    let mut ranges = msg.ranges.iter().map(|&v| v as f64).collect::<Vec<f64>>();
+    if let Some(min_intensity) = min_intensity {
+        for (range, &intensity) in ranges.iter_mut().zip(&msg.intensities) {
+            if (intensity as f64) < min_intensity {
+                *range = f64::NAN;
+            }
+        }
+    }
+    let mut scan = Scan2D::default();
+    scan.angle_min = msg.angle_min as f64;
+    scan.angle_max = msg.angle_max as f64;
+    scan.angle_increment = msg.angle_increment as f64;
+    scan.time_increment = msg.time_increment as f64;
+    scan.scan_time = msg.scan_time as f64;
+    scan.range_min = msg.range_min as f64;
+    scan.range_max = msg.range_max as f64;
+    scan.ranges = ranges;
+    scan.intensities = msg
+        .intensities
+        .iter()
+        .map(|&v| v as f64)
+        .collect::<Vec<f64>>();
+    // The stamp and frame of the messages without them are zero and empty.
+    scan.stamp = (msg.header.stamp.sec != 0 || msg.header.stamp.nanosec != 0)
+        .then(|| header_stamp(msg))
+        .flatten();
+    scan.frame_id = (!msg.header.frame_id.is_empty()).then(|| msg.header.frame_id.clone());
+    scan
+}
+
+impl Ros2LaserScan2D {
+    /// Returns the latest scan.
+    ///
+    /// This is the async equivalent of [`LaserScan2D::current_scan`].
+    ///
+    /// The ranges are converted as is, so invalid measurements reported as NaN
+    /// or infinity by the driver are kept. Use [`Scan2D::finite_ranges`] to
+    /// consume only the valid beams. The beams filtered out by
+    /// [`set_min_intensity`](Self::set_min_intensity) are also NaN.
+    ///
+    /// `stamp` and `frame_id` are taken from the header, or `None` if they are
+    /// not set in the message.
+    pub async fn current_scan_async(&self) -> Result<Scan2D, Error> {
+        self.latest_scan_async(|msg| self.convert_scan(msg)).await?
+    }
+}
+
+impl Sensor<Scan2D> for Ros2LaserScan2D {
+    fn read(&self) -> Result<Scan2D, Error> {
+        self.latest_scan(|msg| self.convert_scan(msg))?
+    }
 }
 
 impl LaserScan2D for Ros2LaserScan2D {
     fn current_scan(&self) -> Result<arci::Scan2D, arci::Error> {
-        let subscribed_scan = self.scan.read().unwrap();
-        let current_scan = match &*subscribed_scan {
-            Some(msg) => Scan2D {
-                angle_min: msg.angle_min as f64,
-                angle_max: msg.angle_max as f64,
-                angle_increment: msg.angle_increment as f64,
-                time_increment: msg.time_increment as f64,
-                scan_time: msg.scan_time as f64,
-                range_min: msg.range_min as f64,
-                range_max: msg.range_max as f64,
-                ranges: msg.ranges.iter().map(|&v| v as f64).collect::<Vec<f64>>(),
-                intensities: msg
-                    .intensities
-                    .iter()
-                    .map(|&v| v as f64)
-                    .collect::<Vec<f64>>(),
-            },
-            None => {
-                return Err(Error::Connection {
-                    message: format!("Failed to get scan from {}", self.laser_scan_topic_name),
-                });
-            }
-        };
-        Ok(current_scan)
+        self.read()
     }
 }
 
@@ -71,4 +308,37 @@ impl LaserScan2D for Ros2LaserScan2D {
 pub struct Ros2LaserScan2DConfig {
     /// Topic name for sensor_msgs/LaserScan.
     pub topic: String,
+    /// Time to wait for the first scan. See [`Ros2LaserScan2D::with_timeout`].
+    #[serde(default = "default_timeout")]
+    pub timeout: Duration,
+    /// QoS profile to subscribe the topic with. See [`QosPreset`].
+    #[serde(default)]
+    pub qos: QosPreset,
+    /// Frame to place the points of the scans in, e.g., the robot base frame.
+    #[serde(default)]
+    pub target_frame: Option<String>,
+    /// Minimum intensity of the valid beams. See
+    /// [`Ros2LaserScan2D::set_min_intensity`].
+    #[serde(default)]
+    pub min_intensity: Option<f64>,
+    /// Maximum number of beams of a scan. See
+    /// [`Ros2LaserScan2D::set_max_beams`].
+    #[serde(default)]
+    pub max_beams: Option<usize>,
+}
+
+impl Ros2LaserScan2DConfig {
+    /// Creates a `Ros2LaserScan2D` from this configuration.
+    pub fn build(&self, node: Node) -> Result<Ros2LaserScan2D, Error> {
+        let mut laser_scan =
+            Ros2LaserScan2D::with_qos(node, &self.topic, self.timeout, self.qos.into())?;
+        laser_scan.set_target_frame(self.target_frame.clone());
+        laser_scan.set_min_intensity(self.min_intensity);
+        laser_scan.set_max_beams(self.max_beams);
+        Ok(laser_scan)
+    }
+}
+
+fn default_timeout() -> Duration {
+    Duration::from_secs(1)
 }
@@ -3,26 +3,27 @@ use std::{
     time::Duration,
 };
 
-use arci::{nalgebra as na, *};
-use r2r::{
-    geometry_msgs::msg::{PoseWithCovariance, PoseWithCovarianceStamped},
-    QosProfile,
-};
+use arci::*;
+use r2r::{geometry_msgs::msg::PoseWithCovarianceStamped, QosProfile};
 use serde::{Deserialize, Serialize};
 use tracing::info;
 
-use crate::{utils, Node};
+use crate::{from_ros2_pose, utils, Node};
 
 /// `arci::Localization` implementation for ROS2.
+///
+/// The robot pose is the latest geometry_msgs/PoseWithCovarianceStamped
+/// estimated by a localization node, e.g., `/amcl_pose` of AMCL.
 pub struct Ros2LocalizationClient {
     node: Node,
     nomotion_update_client: Option<r2r::Client<r2r::std_srvs::srv::Empty::Service>>,
-    pose: Arc<RwLock<Option<PoseWithCovariance>>>,
+    pose: Arc<RwLock<Option<PoseWithCovarianceStamped>>>,
     amcl_pose_topic_name: String,
+    frame_id: Option<String>,
 }
 
 impl Ros2LocalizationClient {
-    /// Creates a new `Ros2LaserScan2D`.
+    /// Creates a new `Ros2LocalizationClient`.
     pub fn new(
         node: Node,
         request_final_nomotion_update_hack: bool,
@@ -33,10 +34,9 @@ impl Ros2LocalizationClient {
             .r2r()
             .subscribe::<PoseWithCovarianceStamped>(amcl_pose_topic_name, QosProfile::default())
             .map_err(anyhow::Error::from)?;
-        let pose = utils::subscribe_one(&mut pose_subscriber, Duration::from_secs(1))
-            .map(|pose| pose.pose);
+        let pose = utils::subscribe_one(&mut pose_subscriber, Duration::from_secs(1));
         let pose = Arc::new(RwLock::new(pose));
-        utils::subscribe_thread(pose_subscriber, pose.clone(), |pose| Some(pose.pose));
+        utils::subscribe_thread(pose_subscriber, pose.clone(), Some);
 
         let nomotion_update_client = if request_final_nomotion_update_hack {
             Some(
@@ -53,9 +53,25 @@ impl Ros2LocalizationClient {
             nomotion_update_client,
             pose,
             amcl_pose_topic_name: amcl_pose_topic_name.to_owned(),
+            frame_id: None,
         })
     }
 
+    /// Sets the frame which the poses are expected in, e.g., `map`.
+    ///
+    /// If set, [`current_pose`](Localization::current_pose) returns an error
+    /// for the poses in other frames (`header.frame_id`), e.g., when the
+    /// localization node is configured with another global frame. Poses
+    /// without frames are not checked.
+    pub fn set_frame_id(&mut self, frame_id: Option<String>) {
+        self.frame_id = frame_id;
+    }
+
+    /// Returns the frame which the poses are expected in.
+    pub fn frame_id(&self) -> Option<&str> {
+        self.frame_id.as_deref()
+    }
+
     /// Request final nomotion update hack
     pub async fn request_nomotion_update(&self) {
         match self.nomotion_update_client.as_ref() {
@@ -75,29 +91,28 @@ impl Ros2LocalizationClient {
 }
 
 impl Localization for Ros2LocalizationClient {
-    fn current_pose(&self, _frame_id: &str) -> Result<Isometry2<f64>, Error> {
+    /// Returns the latest pose of the robot, converted into 2D.
+    ///
+    /// If `frame_id` is not empty, it is checked as well as the frame set by
+    /// [`set_frame_id`](Ros2LocalizationClient::set_frame_id).
+    fn current_pose(&self, frame_id: &str) -> Result<Isometry2<f64>, Error> {
         let subscribed_pose = self.pose.read().unwrap();
-        let current_pose = match &*subscribed_pose {
-            Some(msg) => {
-                let u_q = na::UnitQuaternion::from_quaternion(na::Quaternion::new(
-                    msg.pose.orientation.w,
-                    msg.pose.orientation.x,
-                    msg.pose.orientation.y,
-                    msg.pose.orientation.z,
-                ));
-
-                na::Isometry::from_parts(
-                    na::Translation2::new(msg.pose.position.x, msg.pose.position.y),
-                    na::UnitComplex::from_angle(u_q.angle()),
-                )
-            }
-            None => {
-                return Err(Error::Connection {
-                    message: format!("Failed to get pose from {}", self.amcl_pose_topic_name),
-                });
-            }
+        let Some(msg) = &*subscribed_pose else {
+            return Err(Error::Connection {
+                message: format!("Failed to get pose from {}", self.amcl_pose_topic_name),
+            });
         };
-        Ok(current_pose)
+        let pose_frame_id = &msg.header.frame_id;
+        let expected_frame_ids = [self.frame_id.as_deref(), Some(frame_id)];
+        for expected in expected_frame_ids.into_iter().flatten() {
+            if !expected.is_empty() && !pose_frame_id.is_empty() && pose_frame_id != expected {
+                return Err(Error::Other(anyhow::format_err!(
+                    "The pose from {} is in frame {pose_frame_id:?}, not {expected:?}",
+                    self.amcl_pose_topic_name
+                )));
+            }
+        }
+        Ok(from_ros2_pose(&msg.pose.pose))
     }
 }
 
@@ -111,4 +126,22 @@ pub struct Ros2LocalizationClientConfig {
     pub nomotion_update_service_name: String,
     /// Topic name for geometry_msgs/PoseWithCovarianceStamped.
     pub amcl_pose_topic_name: String,
+    /// Frame which the poses are expected in. See
+    /// [`Ros2LocalizationClient::set_frame_id`].
+    #[serde(default)]
+    pub frame_id: Option<String>,
+}
+
+impl Ros2LocalizationClientConfig {
+    /// Creates a `Ros2LocalizationClient` from this configuration.
+    pub fn build(&self, node: Node) -> Result<Ros2LocalizationClient, Error> {
+        let mut localization = Ros2LocalizationClient::new(
+            node,
+            self.request_final_nomotion_update_hack,
+            &self.nomotion_update_service_name,
+            &self.amcl_pose_topic_name,
+        )?;
+        localization.set_frame_id(self.frame_id.clone());
+        Ok(localization)
+    }
 }
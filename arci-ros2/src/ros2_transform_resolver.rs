@@ -1,20 +1,26 @@
-use std::time::Duration;
+use std::{collections::HashMap, time::Duration};
 
 use arci::{
     nalgebra::{Quaternion, Translation3},
     Isometry3, TransformResolver, UnitQuaternion,
 };
-use r2r::builtin_interfaces::msg as builtin_msg;
 use tf_r2r::{TfBuffer, TfListener};
 use tracing::{debug, warn};
 
-use crate::{utils::convert_system_time_to_ros2_time, Node};
+use crate::{to_ros2_duration, utils::convert_system_time_to_ros2_time, Node};
 
 /// `arci::TransformResolver` implementation for ROS2.
+///
+/// In addition to the transforms published to TF, static transforms not
+/// published to TF (e.g., the result of hand-eye calibration) can be
+/// registered with [`add_static`](Self::add_static). They are composed with
+/// the transforms looked up from TF.
 pub struct Ros2TransformResolver {
     retry_rate: f64,
     max_retry: usize,
     tf_listener: TfListener,
+    /// Map from a child frame to its parent frame and the transform between them.
+    static_transforms: HashMap<String, (String, Isometry3<f64>)>,
     // keep not to be dropped
     _node: Node,
 }
@@ -23,24 +29,53 @@ impl Ros2TransformResolver {
     /// Creates a new `Ros2TransformResolver`.
     #[track_caller]
     pub fn new(node: Node, cache_duration: Duration, retry_rate: f64, max_retry: usize) -> Self {
-        let duration = builtin_msg::Duration {
-            sec: cache_duration.as_secs() as i32,
-            nanosec: cache_duration.subsec_nanos(),
-        };
-
-        let tf_listener =
-            TfListener::new_with_buffer(&mut node.r2r(), TfBuffer::new_with_duration(duration));
+        let tf_listener = TfListener::new_with_buffer(
+            &mut node.r2r(),
+            TfBuffer::new_with_duration(to_ros2_duration(cache_duration)),
+        );
         Self {
             retry_rate,
             max_retry,
             tf_listener,
+            static_transforms: HashMap::new(),
             _node: node,
         }
     }
-}
 
-impl TransformResolver for Ros2TransformResolver {
-    fn resolve_transformation(
+    /// Registers a static transform from `from` frame to `to` frame.
+    ///
+    /// `transform` is what [`resolve_transformation(from, to, _)`](TransformResolver::resolve_transformation)
+    /// returns. `to` is treated as a child frame of `from` which is not
+    /// published to TF, so `to` must not be the child of another static
+    /// transform; registering it again replaces the previous one. For example,
+    /// after `add_static("tool0", "camera_optical", calibration)`, resolving
+    /// `base_link` -> `camera_optical` looks up `base_link` -> `tool0` from TF
+    /// and composes `calibration` with it.
+    pub fn add_static(&mut self, from: &str, to: &str, transform: Isometry3<f64>) {
+        self.static_transforms
+            .insert(to.to_owned(), (from.to_owned(), transform));
+    }
+
+    /// Returns the nearest ancestor of `frame` which is not a child of a
+    /// static transform, and the transform from it to `frame`.
+    fn resolve_static<'a>(
+        &'a self,
+        mut frame: &'a str,
+    ) -> Result<(&'a str, Isometry3<f64>), arci::Error> {
+        let mut transform = Isometry3::identity();
+        let mut depth = 0;
+        while let Some((parent, parent_to_frame)) = self.static_transforms.get(frame) {
+            depth += 1;
+            if depth > self.static_transforms.len() {
+                return Err(anyhow::anyhow!("Static transforms have a cycle at {frame}").into());
+            }
+            transform = parent_to_frame * transform;
+            frame = parent;
+        }
+        Ok((frame, transform))
+    }
+
+    fn lookup_transform(
         &self,
         from: &str,
         to: &str,
@@ -88,3 +123,24 @@ impl TransformResolver for Ros2TransformResolver {
         }
     }
 }
+
+impl TransformResolver for Ros2TransformResolver {
+    fn resolve_transformation(
+        &self,
+        from: &str,
+        to: &str,
+        time: std::time::SystemTime,
+    ) -> Result<Isometry3<f64>, arci::Error> {
+        if self.static_transforms.is_empty() {
+            return self.lookup_transform(from, to, time);
+        }
+        let (from_root, from_root_to_from) = self.resolve_static(from)?;
+        let (to_root, to_root_to_to) = self.resolve_static(to)?;
+        let roots = if from_root == to_root {
+            Isometry3::identity()
+        } else {
+            self.lookup_transform(from_root, to_root, time)?
+        };
+        Ok(from_root_to_from.inverse() * roots * to_root_to_to)
+    }
+}
@@ -0,0 +1,56 @@
+use std::sync::Mutex;
+
+use arci::{Error, Isometry3};
+use r2r::tf2_msgs::msg::TFMessage;
+
+use crate::{to_ros2_time, to_ros2_transform_stamped, Node};
+
+/// Broadcasts transforms to `/tf` as tf2_msgs/TFMessage.
+///
+/// This is useful to visualize poses computed in Rust (e.g., the target
+/// and the achieved end-effector poses of IK) in RViz.
+pub struct Ros2TfBroadcaster {
+    publisher: r2r::Publisher<TFMessage>,
+    clock: Mutex<r2r::Clock>,
+    // keep not to be dropped
+    _node: Node,
+}
+
+impl Ros2TfBroadcaster {
+    /// Creates a new `Ros2TfBroadcaster`.
+    pub fn new(node: Node) -> Result<Self, Error> {
+        let publisher = node
+            .r2r()
+            .create_publisher::<TFMessage>("/tf", r2r::QosProfile::default())
+            .map_err(anyhow::Error::from)?;
+        let clock = r2r::Clock::create(r2r::ClockType::RosTime).map_err(anyhow::Error::from)?;
+        Ok(Self {
+            publisher,
+            clock: Mutex::new(clock),
+            _node: node,
+        })
+    }
+
+    /// Publishes `transform` from `parent` frame to `child` frame, stamped
+    /// with the current time.
+    pub fn publish_tf(
+        &self,
+        parent: &str,
+        child: &str,
+        transform: &Isometry3<f64>,
+    ) -> Result<(), Error> {
+        let now = self
+            .clock
+            .lock()
+            .unwrap()
+            .get_now()
+            .map_err(anyhow::Error::from)?;
+        let stamp = to_ros2_time(now)?;
+        let msg = TFMessage {
+            transforms: vec![to_ros2_transform_stamped(transform, parent, child, stamp)],
+        };
+        self.publisher.publish(&msg).map_err(|e| Error::Connection {
+            message: format!("r2r publish error: {e:?}"),
+        })
+    }
+}
@@ -1,41 +1,96 @@
 use std::{
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc, RwLock,
-    },
+    sync::{Arc, RwLock},
     time::{Duration, Instant, SystemTime},
 };
 
 use futures::{
-    future::FutureExt,
+    future::{Future, FutureExt},
     stream::{Stream, StreamExt},
 };
 use r2r::builtin_interfaces::msg::Time;
+use tokio::{runtime::Handle, sync::watch, task::JoinHandle};
 
 const BILLION: u128 = 1_000_000_000;
 
-// TODO: timeout
-pub(crate) async fn wait(is_done: Arc<AtomicBool>) {
-    loop {
-        if is_done.load(Ordering::Relaxed) {
-            break;
-        }
-        tokio::time::sleep(Duration::from_millis(10)).await;
+static RUNTIME: RwLock<Option<Handle>> = RwLock::new(None);
+
+/// Sets the tokio runtime on which the tasks of this crate are spawned.
+///
+/// By default, tasks are spawned on the runtime of the calling context, so
+/// methods that spawn tasks (e.g. `send_joint_trajectory`) panic with "no
+/// reactor running" when called outside of a tokio runtime. Set the handle of
+/// the application's runtime to call them from any context.
+pub fn set_runtime(handle: Handle) {
+    *RUNTIME.write().unwrap() = Some(handle);
+}
+
+/// Spawns a task on the runtime set by [`set_runtime`], or on the runtime of
+/// the calling context if it is not set.
+pub(crate) fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    match &*RUNTIME.read().unwrap() {
+        Some(handle) => handle.spawn(future),
+        None => tokio::spawn(future),
     }
 }
 
+/// Blocks the current thread until `future` completes.
+///
+/// Within a runtime, the worker thread is handed off by
+/// `tokio::task::block_in_place` so that the other tasks keep running, which
+/// panics on a current-thread runtime. Otherwise, `future` runs on the runtime
+/// set by [`set_runtime`].
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    if let Ok(handle) = Handle::try_current() {
+        return tokio::task::block_in_place(|| handle.block_on(future));
+    }
+    let handle = RUNTIME
+        .read()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(Handle::current);
+    handle.block_on(future)
+}
+
+/// Keeps `buf` updated with the latest message of `subscriber` converted by
+/// `f`, until `buf` is no longer referenced by others or the stream ends.
+///
+/// Every message is handled as soon as it arrives, so `buf` does not lag
+/// behind topics published faster than it is read.
 pub(crate) fn subscribe_thread<T: Send + 'static, U: Send + Sync + 'static>(
     mut subscriber: impl Stream<Item = T> + Send + Unpin + 'static,
     buf: Arc<RwLock<U>>,
     mut f: impl FnMut(T) -> U + Send + 'static,
 ) {
-    tokio::spawn(async move {
+    spawn(async move {
         while Arc::strong_count(&buf) > 1 {
-            if let Some(val) = subscriber.next().await {
-                let res = f(val);
-                *buf.write().unwrap() = res;
+            let Some(val) = subscriber.next().await else {
+                break;
+            };
+            let res = f(val);
+            *buf.write().unwrap() = res;
+        }
+    });
+}
+
+/// Keeps `sender` updated with the latest message of `subscriber` converted
+/// by `f`, until all the receivers are dropped or the stream ends.
+///
+/// Unlike [`subscribe_thread`], the receivers can wait for a new message
+/// without polling.
+pub(crate) fn subscribe_watch<T: Send + 'static, U: Send + Sync + 'static>(
+    mut subscriber: impl Stream<Item = T> + Send + Unpin + 'static,
+    sender: watch::Sender<U>,
+    mut f: impl FnMut(T) -> U + Send + 'static,
+) {
+    spawn(async move {
+        while let Some(val) = subscriber.next().await {
+            if sender.send(f(val)).is_err() {
+                break;
             }
-            tokio::time::sleep(Duration::from_millis(100)).await;
         }
     });
 }
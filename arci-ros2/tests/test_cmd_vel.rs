@@ -2,6 +2,11 @@
 
 mod shared;
 
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
 use arci::{BaseVelocity, MoveBase};
 use arci_ros2::{r2r, Ros2CmdVelMoveBase};
 use assert_approx_eq::assert_approx_eq;
@@ -54,3 +59,86 @@ async fn test_pub() {
         println!("{count}, {vel:?}");
     }
 }
+
+#[tokio::test]
+async fn test_enabled_flag() {
+    let node = test_node();
+    let mut c = Ros2CmdVelMoveBase::new(node.clone(), "/cmd_vel_test_enabled");
+    let enabled = Arc::new(AtomicBool::new(true));
+    c.set_enabled_flag(enabled.clone());
+
+    let mut sub = node
+        .r2r()
+        .subscribe::<Twist>("/cmd_vel_test_enabled", r2r::QosProfile::default())
+        .unwrap();
+
+    let vel = BaseVelocity::new(0.1, 0.2, 0.3);
+    c.send_velocity(&vel).unwrap();
+    node.spin_once(std::time::Duration::from_millis(10)).await;
+    let v = sub.next().await.unwrap();
+    assert_approx_eq!(v.linear.x, vel.x);
+
+    enabled.store(false, Ordering::SeqCst);
+    assert!(c.send_velocity(&vel).is_err());
+    node.spin_once(std::time::Duration::from_millis(10)).await;
+    let v = sub.next().await.unwrap();
+    assert_approx_eq!(v.linear.x, 0.0);
+    assert_approx_eq!(v.linear.y, 0.0);
+    assert_approx_eq!(v.angular.z, 0.0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_max_publish_rate() {
+    let node = test_node();
+    let mut c = Ros2CmdVelMoveBase::new(node.clone(), "/cmd_vel_test_rate");
+    c.set_max_publish_rate(Some(10.0)).unwrap();
+
+    let mut sub = node
+        .r2r()
+        .subscribe::<Twist>("/cmd_vel_test_rate", r2r::QosProfile::default())
+        .unwrap();
+    node.run_spin_thread(std::time::Duration::from_millis(10));
+
+    // send faster than the publish rate
+    for i in 0..=10 {
+        c.send_velocity(&BaseVelocity::new(0.01 * i as f64, 0.0, 0.0))
+            .unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+    }
+    // only the latest one is published
+    let v = sub.next().await.unwrap();
+    assert_approx_eq!(v.linear.x, 0.1);
+    assert!(
+        tokio::time::timeout(std::time::Duration::from_millis(300), sub.next())
+            .await
+            .is_err()
+    );
+
+    for rate in [0.0, -1.0, f64::NAN, f64::INFINITY, f64::MAX] {
+        assert!(c.set_max_publish_rate(Some(rate)).is_err());
+    }
+    c.set_max_publish_rate(None).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_max_publish_rate_disabled() {
+    let node = test_node();
+    let mut c = Ros2CmdVelMoveBase::new(node.clone(), "/cmd_vel_test_rate_disabled");
+    let enabled = Arc::new(AtomicBool::new(true));
+    c.set_enabled_flag(enabled.clone());
+    c.set_max_publish_rate(Some(10.0)).unwrap();
+
+    let mut sub = node
+        .r2r()
+        .subscribe::<Twist>("/cmd_vel_test_rate_disabled", r2r::QosProfile::default())
+        .unwrap();
+    node.run_spin_thread(std::time::Duration::from_millis(10));
+
+    // The pending velocity is not published after the flag flips.
+    c.send_velocity(&BaseVelocity::new(0.1, 0.0, 0.0)).unwrap();
+    enabled.store(false, Ordering::SeqCst);
+    for _ in 0..3 {
+        let v = sub.next().await.unwrap();
+        assert_approx_eq!(v.linear.x, 0.0);
+    }
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "ros2")]
+
+mod shared;
+
+use std::time::Duration;
+
+use arci_ros2::{list_controllers, r2r, ControllerInfo};
+use r2r::controller_manager_msgs::msg::ControllerState;
+use shared::*;
+
+#[test]
+fn test_controller_info() {
+    let state = ControllerState {
+        name: "arm_controller".to_owned(),
+        state: "active".to_owned(),
+        type_: "joint_trajectory_controller/JointTrajectoryController".to_owned(),
+        claimed_interfaces: vec![
+            "joint1/position".to_owned(),
+            "joint1/velocity".to_owned(),
+            "joint2/position".to_owned(),
+        ],
+        required_command_interfaces: vec!["joint3/position".to_owned()],
+        ..Default::default()
+    };
+    let info = ControllerInfo::from(state);
+    assert_eq!(info.name, "arm_controller");
+    assert_eq!(
+        info.controller_type,
+        "joint_trajectory_controller/JointTrajectoryController"
+    );
+    assert_eq!(info.state, "active");
+    assert_eq!(info.joints, vec!["joint1", "joint2"]);
+
+    // Inactive controllers don't claim interfaces.
+    let state = ControllerState {
+        name: "arm_controller".to_owned(),
+        state: "inactive".to_owned(),
+        required_command_interfaces: vec!["joint3/position".to_owned()],
+        ..Default::default()
+    };
+    assert_eq!(ControllerInfo::from(state).joints, vec!["joint3"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_list_controllers_timeout() {
+    let node = test_node();
+    node.run_spin_thread(Duration::from_millis(10));
+    let result = list_controllers(
+        &node,
+        "/test_list_controllers_timeout/controller_manager",
+        Duration::from_millis(500),
+    )
+    .await;
+    assert!(matches!(result, Err(arci::Error::Connection { .. })));
+}
@@ -0,0 +1,153 @@
+#![cfg(feature = "ros2")]
+
+use std::{
+    f64::consts::{FRAC_PI_2, PI},
+    time::Duration,
+};
+
+use arci::{Isometry2, Isometry3, TrajectoryPoint, Vector2, Vector3};
+use arci_ros2::{
+    from_ros2_duration, from_ros2_pose, from_ros2_trajectory_point, to_ros2_duration, to_ros2_pose,
+    to_ros2_pose_stamped, to_ros2_time, to_ros2_trajectory_point, to_ros2_transform_stamped,
+};
+use assert_approx_eq::assert_approx_eq;
+use r2r::{
+    builtin_interfaces::msg::{self as builtin_msg, Time},
+    geometry_msgs::msg,
+};
+
+#[test]
+fn test_duration_conversion() {
+    for duration in [
+        Duration::ZERO,
+        Duration::from_nanos(1),
+        Duration::from_millis(1500),
+        Duration::new(i32::MAX as u64, 999_999_999),
+    ] {
+        assert_eq!(from_ros2_duration(&to_ros2_duration(duration)), duration);
+    }
+    let msg = to_ros2_duration(Duration::new(3, 4));
+    assert_eq!((msg.sec, msg.nanosec), (3, 4));
+
+    // overflow is saturated to i32::MAX seconds
+    let msg = to_ros2_duration(Duration::new(i32::MAX as u64 + 1, 5));
+    assert_eq!((msg.sec, msg.nanosec), (i32::MAX, 5));
+    let msg = to_ros2_duration(Duration::MAX);
+    assert_eq!(msg.sec, i32::MAX);
+
+    // negative durations are saturated to zero
+    for sec in [-1, i32::MIN] {
+        let msg = builtin_msg::Duration {
+            sec,
+            nanosec: 500_000_000,
+        };
+        assert_eq!(from_ros2_duration(&msg), Duration::ZERO);
+    }
+}
+
+#[test]
+fn test_time_conversion() {
+    let msg = to_ros2_time(Duration::new(3, 4)).unwrap();
+    assert_eq!((msg.sec, msg.nanosec), (3, 4));
+    let msg = to_ros2_time(Duration::new(i32::MAX as u64, 5)).unwrap();
+    assert_eq!((msg.sec, msg.nanosec), (i32::MAX, 5));
+
+    assert!(to_ros2_time(Duration::new(i32::MAX as u64 + 1, 0)).is_err());
+    assert!(to_ros2_time(Duration::MAX).is_err());
+}
+
+#[test]
+fn test_trajectory_point_conversion() {
+    let point = TrajectoryPoint {
+        positions: vec![1.0, -2.0],
+        velocities: Some(vec![0.5, 0.0]),
+        time_from_start: Duration::from_millis(2500),
+    };
+    let msg = to_ros2_trajectory_point(&point);
+    assert_eq!(msg.positions, vec![1.0, -2.0]);
+    assert_eq!(msg.velocities, vec![0.5, 0.0]);
+    assert_eq!(
+        (msg.time_from_start.sec, msg.time_from_start.nanosec),
+        (2, 500_000_000)
+    );
+    let converted = from_ros2_trajectory_point(&msg);
+    assert_eq!(converted.positions, point.positions);
+    assert_eq!(converted.velocities, point.velocities);
+    assert_eq!(converted.time_from_start, point.time_from_start);
+
+    // no velocities are sent as zeros, and empty velocities are received as none
+    let point = TrajectoryPoint::new(vec![1.0, -2.0, 3.0], Duration::from_secs(1));
+    let msg = to_ros2_trajectory_point(&point);
+    assert_eq!(msg.velocities, vec![0.0; 3]);
+    let mut msg = msg;
+    msg.velocities.clear();
+    msg.accelerations = vec![1.0; 3];
+    let converted = from_ros2_trajectory_point(&msg);
+    assert_eq!(converted.positions, point.positions);
+    assert_eq!(converted.velocities, None);
+    assert_eq!(converted.time_from_start, Duration::from_secs(1));
+}
+
+#[test]
+fn test_pose_conversion() {
+    for yaw in [0.0, FRAC_PI_2, -FRAC_PI_2, 3.0, -3.0] {
+        let pose = Isometry2::new(Vector2::new(1.0, -2.0), yaw);
+        let msg = to_ros2_pose(&pose);
+        assert_approx_eq!(msg.position.x, 1.0);
+        assert_approx_eq!(msg.position.y, -2.0);
+        assert_approx_eq!(msg.position.z, 0.0);
+        assert_approx_eq!(msg.orientation.x, 0.0);
+        assert_approx_eq!(msg.orientation.y, 0.0);
+        assert_approx_eq!(msg.orientation.z, (yaw / 2.0).sin());
+        assert_approx_eq!(msg.orientation.w, (yaw / 2.0).cos());
+
+        let converted = from_ros2_pose(&msg);
+        assert_approx_eq!(converted.translation.x, 1.0);
+        assert_approx_eq!(converted.translation.y, -2.0);
+        assert_approx_eq!(converted.rotation.angle(), yaw);
+    }
+
+    // yaw of PI is converted to the same rotation
+    let converted = from_ros2_pose(&to_ros2_pose(&Isometry2::new(Vector2::zeros(), PI)));
+    assert_approx_eq!(converted.rotation.angle().abs(), PI);
+
+    // not normalized orientation, and z is ignored
+    let msg = msg::Pose {
+        position: msg::Point {
+            x: 0.5,
+            y: 0.0,
+            z: 1.0,
+        },
+        orientation: msg::Quaternion {
+            x: 0.0,
+            y: 0.0,
+            z: -2.0,
+            w: 2.0,
+        },
+    };
+    let converted = from_ros2_pose(&msg);
+    assert_approx_eq!(converted.translation.x, 0.5);
+    assert_approx_eq!(converted.rotation.angle(), -FRAC_PI_2);
+
+    let stamp = Time { sec: 1, nanosec: 2 };
+    let msg = to_ros2_pose_stamped(&Isometry2::identity(), "map", stamp.clone());
+    assert_eq!(msg.header.frame_id, "map");
+    assert_eq!(msg.header.stamp, stamp);
+}
+
+#[test]
+fn test_transform_conversion() {
+    let transform = Isometry3::new(Vector3::new(1.0, 2.0, 3.0), Vector3::x() * FRAC_PI_2);
+    let stamp = Time { sec: 1, nanosec: 2 };
+    let msg = to_ros2_transform_stamped(&transform, "base", "tool", stamp.clone());
+    assert_eq!(msg.header.frame_id, "base");
+    assert_eq!(msg.header.stamp, stamp);
+    assert_eq!(msg.child_frame_id, "tool");
+    assert_approx_eq!(msg.transform.translation.x, 1.0);
+    assert_approx_eq!(msg.transform.translation.y, 2.0);
+    assert_approx_eq!(msg.transform.translation.z, 3.0);
+    assert_approx_eq!(msg.transform.rotation.x, (FRAC_PI_2 / 2.0).sin());
+    assert_approx_eq!(msg.transform.rotation.y, 0.0);
+    assert_approx_eq!(msg.transform.rotation.z, 0.0);
+    assert_approx_eq!(msg.transform.rotation.w, (FRAC_PI_2 / 2.0).cos());
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "ros2")]
+
+mod shared;
+
+use std::time::Duration;
+
+use arci::JointTrajectoryClient;
+use arci_ros2::{r2r, Ros2JointStateClient};
+use assert_approx_eq::assert_approx_eq;
+use r2r::sensor_msgs::msg::JointState;
+use shared::*;
+
+const JOINT_STATES_TOPIC: &str = "/joint_states_client_test";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_joint_state_client() {
+    let node = test_node();
+    let publisher = node
+        .r2r()
+        .create_publisher::<JointState>(JOINT_STATES_TOPIC, r2r::QosProfile::default())
+        .unwrap();
+
+    tokio::spawn(async move {
+        loop {
+            // joints published separately, in a different order
+            publisher
+                .publish(&JointState {
+                    name: vec!["j2".to_owned(), "j1".to_owned()],
+                    position: vec![2.0, 1.0],
+                    velocity: vec![0.2, 0.1],
+                    ..Default::default()
+                })
+                .unwrap();
+            publisher
+                .publish(&JointState {
+                    name: vec!["j3".to_owned()],
+                    position: vec![3.0],
+                    ..Default::default()
+                })
+                .unwrap();
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    });
+
+    node.run_spin_thread(Duration::from_millis(10));
+    let client = Ros2JointStateClient::new(
+        node.clone(),
+        JOINT_STATES_TOPIC,
+        vec!["j1".to_owned(), "j2".to_owned()],
+    )
+    .unwrap();
+    assert_eq!(client.joint_names(), vec!["j1", "j2"]);
+    let positions = client.current_joint_positions().unwrap();
+    assert_approx_eq!(positions[0], 1.0);
+    assert_approx_eq!(positions[1], 2.0);
+    let velocities = client.current_joint_velocities().unwrap();
+    assert_approx_eq!(velocities[0], 0.1);
+    assert_approx_eq!(velocities[1], 0.2);
+    assert!(client
+        .send_joint_positions(vec![0.0, 0.0], Duration::from_secs(1))
+        .is_err());
+
+    let client = Ros2JointStateClient::new(
+        node,
+        JOINT_STATES_TOPIC,
+        vec!["j1".to_owned(), "j3".to_owned()],
+    )
+    .unwrap();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let positions = client.current_joint_positions().unwrap();
+    assert_approx_eq!(positions[1], 3.0);
+    // the velocity of j3 is not reported
+    assert!(client.current_joint_velocities().is_err());
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "ros2")]
+
+mod shared;
+
+use std::{sync::Arc, time::Duration};
+
+use arci::{DummyJointTrajectoryClient, JointTrajectoryClient};
+use arci_ros2::{r2r, Ros2JointStatePublisher};
+use assert_approx_eq::assert_approx_eq;
+use futures::stream::StreamExt;
+use r2r::sensor_msgs::msg::JointState;
+use shared::*;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_joint_state_publisher() {
+    let node = test_node();
+    let client = Arc::new(DummyJointTrajectoryClient::new(vec![
+        "j1".to_owned(),
+        "j2".to_owned(),
+    ]));
+    client
+        .send_joint_positions(vec![1.0, -2.0], Duration::ZERO)
+        .unwrap()
+        .await
+        .unwrap();
+    let mut sub = node
+        .r2r()
+        .subscribe::<JointState>("/joint_states_test", r2r::QosProfile::default())
+        .unwrap();
+    let _publisher = Ros2JointStatePublisher::new(
+        node.clone(),
+        "/joint_states_test",
+        client,
+        Duration::from_millis(10),
+    )
+    .unwrap();
+    node.run_spin_thread(Duration::from_millis(10));
+
+    let msg = sub.next().await.unwrap();
+    assert_eq!(msg.name, vec!["j1", "j2"]);
+    assert_eq!(msg.position.len(), 2);
+    assert_approx_eq!(msg.position[0], 1.0);
+    assert_approx_eq!(msg.position[1], -2.0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_joint_state_publisher_zero_interval() {
+    let node = test_node();
+    let client = Arc::new(DummyJointTrajectoryClient::new(vec!["j1".to_owned()]));
+    assert!(Ros2JointStatePublisher::new(
+        node,
+        "/joint_states_zero_interval_test",
+        client,
+        Duration::ZERO,
+    )
+    .is_err());
+}
@@ -37,7 +37,11 @@ async fn test_nav() {
         .create_action_server::<NavigateToPose::Action>(action_name)
         .unwrap();
 
-    tokio::spawn(test_nav_server(node.clone(), server_requests));
+    tokio::spawn(test_nav_server(
+        node.clone(),
+        server_requests,
+        Default::default(),
+    ));
 
     node.run_spin_thread(Duration::from_millis(100));
 
@@ -63,7 +67,12 @@ async fn test_nav_timeout() {
         .unwrap();
 
     let node_cb = node.clone();
-    tokio::spawn(test_nav_server(node_cb, server_requests));
+    let goal_canceled = Arc::new(AtomicBool::new(false));
+    tokio::spawn(test_nav_server(
+        node_cb,
+        server_requests,
+        goal_canceled.clone(),
+    ));
 
     node.run_spin_thread(Duration::from_millis(100));
 
@@ -78,6 +87,8 @@ async fn test_nav_timeout() {
         .unwrap_err()
         .to_string()
         .contains("timeout"));
+    // The goal that timed out is canceled.
+    assert!(goal_canceled.load(Ordering::SeqCst));
 }
 
 #[flaky_test::flaky_test(tokio(flavor = "multi_thread"))]
@@ -91,7 +102,11 @@ async fn test_nav_cancel() {
         .create_action_server::<NavigateToPose::Action>(action_name)
         .unwrap();
 
-    tokio::spawn(test_nav_server(node.clone(), server_requests));
+    tokio::spawn(test_nav_server(
+        node.clone(),
+        server_requests,
+        Default::default(),
+    ));
 
     node.run_spin_thread(Duration::from_millis(100));
 
@@ -167,6 +182,7 @@ async fn run_goal(
 async fn test_nav_server(
     node: Node,
     mut requests: impl Stream<Item = r2r::ActionServerGoalRequest<NavigateToPose::Action>> + Unpin,
+    goal_canceled: Arc<AtomicBool>,
 ) {
     while let Some(req) = requests.next().await {
         println!(
@@ -186,6 +202,7 @@ async fn test_nav_server(
             Either::Right((request, _)) => {
                 if let Some(request) = request {
                     println!("got cancel request: {}", request.uuid);
+                    goal_canceled.store(true, Ordering::SeqCst);
                     request.accept();
                     canceled.store(true, Ordering::SeqCst);
                     g.cancel(NavigateToPose::Result {
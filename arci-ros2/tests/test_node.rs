@@ -0,0 +1,50 @@
+#![cfg(feature = "ros2")]
+
+mod shared;
+
+use std::time::Duration;
+
+use arci_ros2::r2r;
+use futures::stream::StreamExt;
+use r2r::std_msgs::msg::String as StringMsg;
+use shared::*;
+
+const TOPIC: &str = "/test_node_spin";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_spin_forever() {
+    let node = test_node();
+    assert_eq!(node.spin_period(), Duration::from_millis(100));
+    node.set_spin_period(Duration::from_millis(10));
+    assert_eq!(node.spin_period(), Duration::from_millis(10));
+
+    let publisher = node
+        .r2r()
+        .create_publisher::<StringMsg>(TOPIC, r2r::QosProfile::default())
+        .unwrap();
+    let mut subscriber = node
+        .r2r()
+        .subscribe::<StringMsg>(TOPIC, r2r::QosProfile::default())
+        .unwrap();
+    let spin = tokio::spawn({
+        let node = node.clone();
+        async move { node.spin_forever().await }
+    });
+    let msg = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            publisher
+                .publish(&StringMsg {
+                    data: "hello".to_owned(),
+                })
+                .unwrap();
+            tokio::select! {
+                msg = subscriber.next() => break msg.unwrap(),
+                _ = tokio::time::sleep(Duration::from_millis(50)) => {}
+            }
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(msg.data, "hello");
+    spin.abort();
+}
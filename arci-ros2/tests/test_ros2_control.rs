@@ -11,7 +11,7 @@ use std::{
 };
 
 use arci::*;
-use arci_ros2::{r2r, Node, Ros2ControlClient};
+use arci_ros2::{r2r, GoalStatus, Node, Ros2ControlClient};
 use futures::{
     future::{self, Either},
     stream::{Stream, StreamExt},
@@ -69,12 +69,494 @@ async fn test_control() {
 
     assert_eq!(client.joint_names(), vec!["j1".to_owned(), "j2".to_owned()]);
     assert_eq!(client.current_joint_positions().unwrap(), vec![0.0, 0.0]);
+    // The state published above does not contain effort.
+    assert!(client.current_joint_efforts().is_err());
+    assert_eq!(client.goal_status(), None);
+    let wait = client
+        .send_joint_positions(vec![1.0, 0.5], Duration::from_secs(80))
+        .unwrap();
+    // wait for the goal to be accepted
+    while client.goal_status().is_none() {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(!client.goal_status().unwrap().is_terminal());
+    wait.await.unwrap();
+    assert_eq!(client.goal_status(), Some(GoalStatus::Succeeded));
+    assert_eq!(client.current_joint_positions().unwrap(), vec![1.0, 0.5]);
     client
+        .send_joint_trajectory_async(vec![TrajectoryPoint::new(
+            vec![0.5, 1.0],
+            Duration::from_secs(80),
+        )])
+        .await
+        .unwrap();
+    assert_eq!(
+        client.current_joint_positions_async().await.unwrap(),
+        vec![0.5, 1.0]
+    );
+    // An empty trajectory completes immediately without sending a goal.
+    client.send_joint_trajectory(vec![]).unwrap().await.unwrap();
+    assert_eq!(client.current_joint_positions().unwrap(), vec![0.5, 1.0]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_control_wait_for_first_feedback() {
+    let action_name = &action_name();
+    let node = test_node();
+    let server_requests = node
+        .r2r()
+        .create_action_server::<FollowJointTrajectory::Action>(&format!(
+            "{action_name}/follow_joint_trajectory"
+        ))
+        .unwrap();
+    let publisher = node
+        .r2r()
+        .create_publisher::<JointTrajectoryControllerState>(
+            &format!("{action_name}/state"),
+            r2r::QosProfile::default(),
+        )
+        .unwrap();
+    let state = Arc::new(Mutex::new(JointTrajectoryControllerState {
+        joint_names: vec!["j1".to_owned(), "j2".to_owned()],
+        actual: trajectory_msg::JointTrajectoryPoint {
+            positions: vec![0.0; 2],
+            ..Default::default()
+        },
+        ..Default::default()
+    }));
+    tokio::spawn(test_control_server(
+        node.clone(),
+        server_requests,
+        state.clone(),
+    ));
+    tokio::spawn(async move {
+        loop {
+            publisher.publish(&state.lock().unwrap()).unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    });
+    node.run_spin_thread(Duration::from_millis(100));
+    let mut client = Ros2ControlClient::new(node, action_name).unwrap();
+    client.set_wait_for_first_feedback(Some(Duration::from_secs(1)));
+
+    // The test server sends no feedback, so sending returns on completion or
+    // timeout without stalling the runtime driving the action.
+    let wait = client
         .send_joint_positions(vec![1.0, 0.5], Duration::from_secs(80))
+        .unwrap();
+    assert!(client.goal_status().is_some());
+    wait.await.unwrap();
+    assert_eq!(client.current_joint_positions().unwrap(), vec![1.0, 0.5]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_control_joint_names() {
+    let action_name = &action_name();
+    let node = test_node();
+    let server_requests = node
+        .r2r()
+        .create_action_server::<FollowJointTrajectory::Action>(&format!(
+            "{action_name}/follow_joint_trajectory"
+        ))
+        .unwrap();
+    let publisher = node
+        .r2r()
+        .create_publisher::<JointTrajectoryControllerState>(
+            &format!("{action_name}/state"),
+            r2r::QosProfile::default(),
+        )
+        .unwrap();
+    let state = Arc::new(Mutex::new(JointTrajectoryControllerState {
+        joint_names: vec!["j1".to_owned(), "j2".to_owned()],
+        actual: trajectory_msg::JointTrajectoryPoint {
+            positions: vec![0.0; 2],
+            ..Default::default()
+        },
+        ..Default::default()
+    }));
+    tokio::spawn(test_control_server(
+        node.clone(),
+        server_requests,
+        state.clone(),
+    ));
+    let state_clone = state.clone();
+    tokio::spawn(async move {
+        loop {
+            publisher.publish(&state_clone.lock().unwrap()).unwrap();
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    });
+    node.run_spin_thread(Duration::from_millis(10));
+    let mut client = Ros2ControlClient::new(node, action_name).unwrap();
+
+    let err = client.set_joint_names(vec!["j3".to_owned()]).unwrap_err();
+    assert!(matches!(err, Error::NoJoint(name) if name == "j3"));
+    assert_eq!(client.joint_names(), vec!["j1".to_owned(), "j2".to_owned()]);
+
+    // A subset of the joints, the others are held.
+    client.set_joint_names(vec!["j2".to_owned()]).unwrap();
+    assert_eq!(client.joint_names(), vec!["j2".to_owned()]);
+    client
+        .send_joint_positions(vec![0.7], Duration::from_secs(1))
         .unwrap()
         .await
         .unwrap();
-    assert_eq!(client.current_joint_positions().unwrap(), vec![1.0, 0.5]);
+    assert_eq!(state.lock().unwrap().actual.positions, vec![0.0, 0.7]);
+
+    // The joints in a different order.
+    client
+        .set_joint_names(vec!["j2".to_owned(), "j1".to_owned()])
+        .unwrap();
+    client
+        .send_joint_positions(vec![1.0, 2.0], Duration::from_secs(1))
+        .unwrap()
+        .await
+        .unwrap();
+    assert_eq!(state.lock().unwrap().actual.positions, vec![2.0, 1.0]);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(client.current_joint_positions().unwrap(), vec![1.0, 2.0]);
+
+    // Empty falls back to all joints of the controller.
+    client.set_joint_names(vec![]).unwrap();
+    assert_eq!(client.joint_names(), vec!["j1".to_owned(), "j2".to_owned()]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_control_motion_limits() {
+    let action_name = &action_name();
+    let node = test_node();
+    let mut server_requests = node
+        .r2r()
+        .create_action_server::<FollowJointTrajectory::Action>(&format!(
+            "{action_name}/follow_joint_trajectory"
+        ))
+        .unwrap();
+    let durations = Arc::new(Mutex::new(vec![]));
+    let durations_clone = durations.clone();
+    // A server which records the duration of the goals and succeeds immediately.
+    tokio::spawn(async move {
+        while let Some(req) = server_requests.next().await {
+            let point = req.goal.trajectory.points.last().unwrap();
+            durations_clone.lock().unwrap().push(
+                Duration::from_secs(point.time_from_start.sec as u64)
+                    + Duration::from_nanos(point.time_from_start.nanosec as u64),
+            );
+            let (mut goal, _cancel) = req.accept().expect("could not accept goal");
+            goal.succeed(FollowJointTrajectory::Result::default())
+                .expect("could not send result");
+        }
+    });
+    spawn_state_publisher(&node, action_name);
+    node.run_spin_thread(Duration::from_millis(100));
+    let mut client = Ros2ControlClient::new(node, action_name).unwrap();
+
+    let err = client.set_motion_limits(Some(vec![0.5]), None).unwrap_err();
+    assert!(matches!(err, Error::LengthMismatch { model: 2, input: 1 }));
+    for limit in [0.0, -0.5, f64::NAN] {
+        assert!(client
+            .set_motion_limits(Some(vec![0.5, limit]), None)
+            .is_err());
+        assert!(client
+            .set_motion_limits(None, Some(vec![limit, 1.0]))
+            .is_err());
+    }
+    client
+        .set_motion_limits(Some(vec![0.5, 0.5]), Some(vec![1.0, 1.0]))
+        .unwrap();
+
+    // Accelerates for 0.5 s to 0.5 rad/s, moves for 1.5 s, and decelerates for 0.5 s.
+    client
+        .send_joint_positions(vec![1.0, 0.5], Duration::from_millis(100))
+        .unwrap()
+        .await
+        .unwrap();
+    // Slow enough moves are not changed.
+    client
+        .send_joint_positions(vec![0.1, 0.0], Duration::from_secs(10))
+        .unwrap()
+        .await
+        .unwrap();
+    // The velocity from the current positions is limited.
+    client
+        .send_joint_trajectory(vec![TrajectoryPoint::new(
+            vec![0.0, 0.2],
+            Duration::from_millis(100),
+        )])
+        .unwrap()
+        .await
+        .unwrap();
+    // The limits are kept by joint name.
+    client
+        .set_motion_limits(Some(vec![10.0, 0.5]), Some(vec![10.0, 1.0]))
+        .unwrap();
+    client.set_joint_names(vec!["j2".to_owned()]).unwrap();
+    client
+        .send_joint_positions(vec![1.0], Duration::from_millis(100))
+        .unwrap()
+        .await
+        .unwrap();
+    let durations = durations.lock().unwrap();
+    assert_eq!(durations.len(), 4);
+    assert!(
+        (durations[3].as_secs_f64() - 2.5).abs() < 1e-6,
+        "{durations:?}"
+    );
+    assert!(
+        (durations[0].as_secs_f64() - 2.5).abs() < 1e-6,
+        "{durations:?}"
+    );
+    assert_eq!(durations[1], Duration::from_secs(10));
+    assert!(
+        (durations[2].as_secs_f64() - 0.4).abs() < 1e-6,
+        "{durations:?}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_control_trajectory_limits() {
+    let action_name = &action_name();
+    let node = test_node();
+    spawn_state_publisher(&node, action_name);
+    node.run_spin_thread(Duration::from_millis(100));
+    let mut client = Ros2ControlClient::new(node, action_name).unwrap();
+
+    let err = client
+        .set_trajectory_limits(vec![JointPositionLimit::none()], vec![1.0, 1.0])
+        .unwrap_err();
+    assert!(matches!(err, Error::LengthMismatch { model: 2, input: 1 }));
+    client
+        .set_trajectory_limits(
+            vec![
+                JointPositionLimit::new(-1.0, 1.0),
+                JointPositionLimit::none(),
+            ],
+            vec![10.0, 0.1],
+        )
+        .unwrap();
+    assert!(client
+        .send_joint_trajectory(vec![TrajectoryPoint::new(
+            vec![2.0, 0.0],
+            Duration::from_secs(10),
+        )])
+        .is_err());
+
+    // The limits follow the joints after their order is changed.
+    client
+        .set_joint_names(vec!["j2".to_owned(), "j1".to_owned()])
+        .unwrap();
+    assert!(client
+        .send_joint_trajectory(vec![TrajectoryPoint::new(
+            vec![0.0, 2.0],
+            Duration::from_secs(10),
+        )])
+        .is_err());
+    assert!(client
+        .send_joint_trajectory(vec![
+            TrajectoryPoint::new(vec![0.0, 0.0], Duration::from_secs(1)),
+            TrajectoryPoint::new(vec![2.0, 0.0], Duration::from_secs(2)),
+        ])
+        .is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_control_abort() {
+    let action_name = &action_name();
+    let node = test_node();
+    let mut server_requests = node
+        .r2r()
+        .create_action_server::<FollowJointTrajectory::Action>(&format!(
+            "{action_name}/follow_joint_trajectory"
+        ))
+        .unwrap();
+    // A server which never completes goals unless they are canceled.
+    tokio::spawn(async move {
+        while let Some(req) = server_requests.next().await {
+            let (mut goal, mut cancel) = req.accept().expect("could not accept goal");
+            tokio::spawn(async move {
+                if let Some(request) = cancel.next().await {
+                    request.accept();
+                    goal.cancel(FollowJointTrajectory::Result::default())
+                        .expect("could not cancel goal");
+                }
+            });
+        }
+    });
+    spawn_state_publisher(&node, action_name);
+    node.run_spin_thread(Duration::from_millis(100));
+    let client = Ros2ControlClient::new(node, action_name).unwrap();
+
+    let wait = client
+        .send_joint_positions(vec![1.0, 0.5], Duration::from_secs(80))
+        .unwrap();
+    // wait for the goal to be accepted
+    while client.goal_status().is_none() {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    client.abort().unwrap();
+    let err = tokio::time::timeout(Duration::from_secs(10), wait)
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert!(matches!(err, Error::Canceled { .. }), "{err:?}");
+
+    // Aborting affects only the trajectories sent before it.
+    let wait = client
+        .send_joint_positions(vec![1.0, 0.5], Duration::from_secs(80))
+        .unwrap();
+    client.abort().unwrap();
+    let err = tokio::time::timeout(Duration::from_secs(10), wait)
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert!(matches!(err, Error::Canceled { .. }), "{err:?}");
+    assert!(client
+        .send_joint_positions(vec![1.0, 0.5], Duration::from_secs(80))
+        .is_ok());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_control_aborted_by_controller() {
+    let action_name = &action_name();
+    let node = test_node();
+    let mut server_requests = node
+        .r2r()
+        .create_action_server::<FollowJointTrajectory::Action>(&format!(
+            "{action_name}/follow_joint_trajectory"
+        ))
+        .unwrap();
+    // A server which aborts all goals.
+    tokio::spawn(async move {
+        while let Some(req) = server_requests.next().await {
+            let (mut goal, _cancel) = req.accept().expect("could not accept goal");
+            goal.abort(FollowJointTrajectory::Result {
+                error_code: FollowJointTrajectory::Result::PATH_TOLERANCE_VIOLATED,
+                error_string: "deviated from the path".to_owned(),
+            })
+            .expect("could not abort goal");
+        }
+    });
+    spawn_state_publisher(&node, action_name);
+    node.run_spin_thread(Duration::from_millis(100));
+    let client = Ros2ControlClient::new(node, action_name).unwrap();
+
+    let err = client
+        .send_joint_positions(vec![1.0, 0.5], Duration::from_secs(1))
+        .unwrap()
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("path tolerance violated"), "{err}");
+    assert!(err.to_string().contains("deviated from the path"), "{err}");
+    assert_eq!(client.goal_status(), Some(GoalStatus::Aborted));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_control_state_cache() {
+    let action_name = &action_name();
+    let node = test_node();
+    let _server_requests = node
+        .r2r()
+        .create_action_server::<FollowJointTrajectory::Action>(&format!(
+            "{action_name}/follow_joint_trajectory"
+        ))
+        .unwrap();
+    let publisher = node
+        .r2r()
+        .create_publisher::<JointTrajectoryControllerState>(
+            &format!("{action_name}/state"),
+            r2r::QosProfile::default(),
+        )
+        .unwrap();
+    let position = Arc::new(Mutex::new(0.0));
+    let position_clone = position.clone();
+    // Publish the state much faster than it is read.
+    tokio::spawn(async move {
+        loop {
+            let state = JointTrajectoryControllerState {
+                joint_names: vec!["j1".to_owned(), "j2".to_owned()],
+                actual: trajectory_msg::JointTrajectoryPoint {
+                    positions: vec![*position_clone.lock().unwrap(), 0.0],
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            publisher.publish(&state).unwrap();
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    });
+    node.run_spin_thread(Duration::from_millis(1));
+    let client = Ros2ControlClient::new(node, action_name).unwrap();
+    assert_eq!(client.current_joint_positions().unwrap(), vec![0.0, 0.0]);
+
+    *position.lock().unwrap() = 1.0;
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    // The latest state is returned without waiting for a new message.
+    for _ in 0..100 {
+        assert_eq!(client.current_joint_positions().unwrap(), vec![1.0, 0.0]);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_control_invalid_state() {
+    let action_name = &action_name();
+    let node = test_node();
+    let _server_requests = node
+        .r2r()
+        .create_action_server::<FollowJointTrajectory::Action>(&format!(
+            "{action_name}/follow_joint_trajectory"
+        ))
+        .unwrap();
+    let publisher = node
+        .r2r()
+        .create_publisher::<JointTrajectoryControllerState>(
+            &format!("{action_name}/state"),
+            r2r::QosProfile::default(),
+        )
+        .unwrap();
+    // The position of j2 is missing.
+    let state = JointTrajectoryControllerState {
+        joint_names: vec!["j1".to_owned(), "j2".to_owned()],
+        actual: trajectory_msg::JointTrajectoryPoint {
+            positions: vec![0.0],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    tokio::spawn(async move {
+        loop {
+            publisher.publish(&state).unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    });
+    node.run_spin_thread(Duration::from_millis(100));
+    let err = Ros2ControlClient::new(node, action_name)
+        .err()
+        .expect("the invalid state should be rejected");
+    assert!(matches!(err, Error::Connection { .. }), "{err:?}");
+}
+
+/// Publishes the state of the joints `j1` and `j2` at zero.
+fn spawn_state_publisher(node: &Node, action_name: &str) {
+    let publisher = node
+        .r2r()
+        .create_publisher::<JointTrajectoryControllerState>(
+            &format!("{action_name}/state"),
+            r2r::QosProfile::default(),
+        )
+        .unwrap();
+    let state = JointTrajectoryControllerState {
+        joint_names: vec!["j1".to_owned(), "j2".to_owned()],
+        actual: trajectory_msg::JointTrajectoryPoint {
+            positions: vec![0.0; 2],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    tokio::spawn(async move {
+        loop {
+            publisher.publish(&state).unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    });
 }
 
 async fn run_goal(
@@ -0,0 +1,70 @@
+#![cfg(feature = "ros2")]
+
+mod shared;
+
+use std::time::Duration;
+
+use arci_ros2::{image_to_rgb8, r2r, Ros2Image};
+use r2r::{sensor_msgs::msg::Image, std_msgs::msg::Header, QosProfile};
+use shared::*;
+
+const IMAGE_TOPIC: &str = "/image_raw";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_image() {
+    let node = test_node();
+    let image_publisher = node
+        .r2r()
+        .create_publisher::<Image>(IMAGE_TOPIC, QosProfile::sensor_data())
+        .unwrap();
+
+    tokio::spawn(async move {
+        loop {
+            image_publisher
+                .publish(&Image {
+                    header: Header::default(),
+                    height: 2,
+                    width: 2,
+                    encoding: "bgr8".to_owned(),
+                    is_bigendian: 0,
+                    // each row is padded to 8 bytes
+                    step: 8,
+                    data: vec![1, 2, 3, 4, 5, 6, 0, 0, 7, 8, 9, 10, 11, 12, 0, 0],
+                })
+                .unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    });
+
+    node.run_spin_thread(Duration::from_millis(100));
+    let client = Ros2Image::with_qos(node, IMAGE_TOPIC, QosProfile::sensor_data()).unwrap();
+
+    let (width, height, step, encoding, data) = client.current_image().unwrap();
+    assert_eq!((width, height, step, encoding.as_str()), (2, 2, 8, "bgr8"));
+    assert_eq!(data.len(), 16);
+    assert_eq!(
+        image_to_rgb8(width, height, step, &encoding, &data).unwrap(),
+        vec![3, 2, 1, 6, 5, 4, 9, 8, 7, 12, 11, 10]
+    );
+}
+
+#[test]
+fn test_image_to_rgb8() {
+    assert_eq!(
+        image_to_rgb8(2, 1, 6, "rgb8", &[1, 2, 3, 4, 5, 6]).unwrap(),
+        vec![1, 2, 3, 4, 5, 6]
+    );
+    assert_eq!(
+        image_to_rgb8(2, 2, 2, "mono8", &[1, 2, 3, 4]).unwrap(),
+        vec![1, 1, 1, 2, 2, 2, 3, 3, 3, 4, 4, 4]
+    );
+    // the padding of the rows is skipped by the step, even if the last row
+    // is not padded
+    assert_eq!(
+        image_to_rgb8(1, 2, 4, "mono8", &[1, 0, 0, 0, 2]).unwrap(),
+        vec![1, 1, 1, 2, 2, 2]
+    );
+    assert!(image_to_rgb8(1, 1, 3, "rgb8", &[1, 2]).is_err());
+    assert!(image_to_rgb8(2, 1, 3, "rgb8", &[0; 6]).is_err());
+    assert!(image_to_rgb8(1, 1, 2, "16UC1", &[1, 2]).is_err());
+}
@@ -4,12 +4,13 @@ mod shared;
 
 use std::time::Duration;
 
-use arci::{LaserScan2D, Scan2D};
-use arci_ros2::{r2r, Ros2LaserScan2D};
+use arci::{Isometry3, LaserScan2D, Scan2D, TransformResolver, Vector3};
+use arci_ros2::{r2r, QosPreset, Ros2LaserScan2D};
 use r2r::{sensor_msgs::msg::LaserScan, std_msgs::msg::Header, QosProfile};
 use shared::*;
 
 const LASER_SCAN_TOPIC: &str = "/scan";
+const FRAME_LASER_SCAN_TOPIC: &str = "/scan_with_frame";
 
 const ANGLE_MAX: f32 = std::f32::consts::FRAC_PI_2;
 const ANGLE_INCREMENT: f32 = 0.1;
@@ -47,22 +48,259 @@ async fn test_laser_scan() {
     });
 
     node.run_spin_thread(Duration::from_millis(100));
-    let client = Ros2LaserScan2D::new(node, LASER_SCAN_TOPIC).unwrap();
+    let mut client = Ros2LaserScan2D::new(node, LASER_SCAN_TOPIC).unwrap();
 
     let current_scan = client.current_scan().unwrap();
+    assert_eq!(current_scan, client.current_scan_async().await.unwrap());
 
+    // too many beams
+    client.set_max_beams(Some(3));
+    assert_eq!(client.max_beams(), Some(3));
+    assert!(client.current_scan().is_err());
+    client.set_max_beams(Some((ANGLE_MAX / ANGLE_INCREMENT) as usize));
+    assert_eq!(current_scan, client.current_scan().unwrap());
+
+    let mut expected = Scan2D::default();
+    expected.angle_min = 0.;
+    expected.angle_max = ANGLE_MAX as f64;
+    expected.angle_increment = ANGLE_INCREMENT as f64;
+    expected.time_increment = TIME_INCREMENT as f64;
+    expected.scan_time = SCAN_TIME as f64;
+    expected.range_min = RANGE_MIN as f64;
+    expected.range_max = RANGE_MAX as f64;
+    expected.ranges = vec![1.; (ANGLE_MAX / ANGLE_INCREMENT) as usize];
+    assert_eq!(current_scan, expected)
+}
+
+struct TestTransformResolver;
+
+impl TransformResolver for TestTransformResolver {
+    fn resolve_transformation(
+        &self,
+        from: &str,
+        to: &str,
+        _time: std::time::SystemTime,
+    ) -> Result<Isometry3<f64>, arci::Error> {
+        assert_eq!(from, "base_link");
+        assert_eq!(to, "laser");
+        Ok(Isometry3::translation(1.0, 0.0, 0.5))
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_laser_scan_target_frame() {
+    let node = test_node();
+    let scan_publisher = node
+        .r2r()
+        .create_publisher::<LaserScan>(FRAME_LASER_SCAN_TOPIC, QosProfile::default())
+        .unwrap();
+
+    tokio::spawn(async move {
+        loop {
+            scan_publisher
+                .publish(&LaserScan {
+                    header: Header {
+                        frame_id: "laser".to_owned(),
+                        ..Default::default()
+                    },
+                    angle_min: 0.,
+                    angle_max: ANGLE_INCREMENT,
+                    angle_increment: ANGLE_INCREMENT,
+                    range_min: RANGE_MIN,
+                    range_max: RANGE_MAX,
+                    // the second beam is invalid
+                    ranges: vec![2., f32::NAN],
+                    ..Default::default()
+                })
+                .unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    });
+
+    node.run_spin_thread(Duration::from_millis(100));
+    let mut client = Ros2LaserScan2D::new(node, FRAME_LASER_SCAN_TOPIC).unwrap();
+    assert_eq!(client.frame_id().unwrap(), "laser");
+    // the target frame is not set
+    assert!(client.current_points(&TestTransformResolver).is_err());
+
+    client.set_target_frame(Some("base_link".to_owned()));
+    assert_eq!(client.target_frame(), Some("base_link"));
+    let points = client.current_points(&TestTransformResolver).unwrap();
+    assert_eq!(points.len(), 1);
+    assert!((points[0] - Vector3::new(3.0, 0.0, 0.5)).norm() < 1e-6);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_laser_scan_min_intensity() {
+    const INTENSITY_LASER_SCAN_TOPIC: &str = "/scan_with_intensities";
+    const NO_INTENSITY_LASER_SCAN_TOPIC: &str = "/scan_without_intensities";
+
+    let node = test_node();
+    for (topic, intensities) in [
+        (INTENSITY_LASER_SCAN_TOPIC, vec![10., 1., 5.]),
+        (NO_INTENSITY_LASER_SCAN_TOPIC, vec![]),
+    ] {
+        let scan_publisher = node
+            .r2r()
+            .create_publisher::<LaserScan>(topic, QosProfile::default())
+            .unwrap();
+        tokio::spawn(async move {
+            loop {
+                scan_publisher
+                    .publish(&LaserScan {
+                        angle_min: 0.,
+                        angle_max: ANGLE_INCREMENT * 2.,
+                        angle_increment: ANGLE_INCREMENT,
+                        range_min: RANGE_MIN,
+                        range_max: RANGE_MAX,
+                        ranges: vec![1., 2., 3.],
+                        intensities: intensities.clone(),
+                        ..Default::default()
+                    })
+                    .unwrap();
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        });
+    }
+
+    node.run_spin_thread(Duration::from_millis(100));
+    let mut client = Ros2LaserScan2D::new(node.clone(), INTENSITY_LASER_SCAN_TOPIC).unwrap();
+    assert_eq!(client.current_scan().unwrap().ranges, vec![1., 2., 3.]);
+
+    client.set_min_intensity(Some(5.));
+    assert_eq!(client.min_intensity(), Some(5.));
+    let ranges = client.current_scan().unwrap().ranges;
+    assert_eq!(ranges[0], 1.);
+    assert!(ranges[1].is_nan());
+    assert_eq!(ranges[2], 3.);
+
+    // no-op for scans without intensities
+    let mut client = Ros2LaserScan2D::new(node, NO_INTENSITY_LASER_SCAN_TOPIC).unwrap();
+    client.set_min_intensity(Some(5.));
+    assert_eq!(client.current_scan().unwrap().ranges, vec![1., 2., 3.]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_laser_scan_late_publisher() {
+    const TOPIC: &str = "/scan_late";
+    let node = test_node();
+    node.run_spin_thread(Duration::from_millis(10));
+    // No scan has arrived yet.
+    let client =
+        Ros2LaserScan2D::with_timeout(node.clone(), TOPIC, Duration::from_millis(300)).unwrap();
+    assert!(matches!(
+        client.current_scan(),
+        Err(arci::Error::Connection { .. })
+    ));
+
+    let scan_publisher = node
+        .r2r()
+        .create_publisher::<LaserScan>(TOPIC, QosProfile::default())
+        .unwrap();
+    tokio::spawn(async move {
+        loop {
+            scan_publisher
+                .publish(&LaserScan {
+                    header: Header {
+                        stamp: r2r::builtin_interfaces::msg::Time {
+                            sec: 12,
+                            nanosec: 5,
+                        },
+                        frame_id: "laser".to_owned(),
+                    },
+                    ranges: vec![1.; 3],
+                    angle_max: 0.2,
+                    angle_increment: 0.1,
+                    ..Default::default()
+                })
+                .unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    });
+    // The subscription created on construction receives the scans.
+    let mut scan = client.current_scan();
+    for _ in 0..30 {
+        if scan.is_ok() {
+            break;
+        }
+        scan = client.current_scan();
+    }
+    let scan = scan.unwrap();
+    assert_eq!(scan.ranges, vec![1.; 3]);
     assert_eq!(
-        current_scan,
-        Scan2D {
-            angle_min: 0.,
-            angle_max: ANGLE_MAX as f64,
-            angle_increment: ANGLE_INCREMENT as f64,
-            time_increment: TIME_INCREMENT as f64,
-            scan_time: SCAN_TIME as f64,
-            range_min: RANGE_MIN as f64,
-            range_max: RANGE_MAX as f64,
-            ranges: vec![1.; (ANGLE_MAX / ANGLE_INCREMENT) as usize],
-            intensities: vec![]
+        scan.stamp,
+        Some(std::time::SystemTime::UNIX_EPOCH + Duration::new(12, 5))
+    );
+    assert_eq!(scan.frame_id.as_deref(), Some("laser"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_laser_scan_sensor_data_qos() {
+    const TOPIC: &str = "/scan_sensor_data";
+    let node = test_node();
+    let scan_publisher = node
+        .r2r()
+        .create_publisher::<LaserScan>(TOPIC, QosProfile::sensor_data())
+        .unwrap();
+    tokio::spawn(async move {
+        loop {
+            scan_publisher
+                .publish(&LaserScan {
+                    ranges: vec![2.; 3],
+                    angle_max: 0.2,
+                    angle_increment: 0.1,
+                    ..Default::default()
+                })
+                .unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
         }
+    });
+    node.run_spin_thread(Duration::from_millis(100));
+
+    let client = Ros2LaserScan2D::with_qos(
+        node,
+        TOPIC,
+        Duration::from_secs(1),
+        QosPreset::SensorData.into(),
     )
+    .unwrap();
+    assert_eq!(client.current_scan().unwrap().ranges, vec![2.; 3]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_laser_scan_negative_stamp() {
+    const TOPIC: &str = "/scan_negative_stamp";
+    let node = test_node();
+    let scan_publisher = node
+        .r2r()
+        .create_publisher::<LaserScan>(TOPIC, QosProfile::default())
+        .unwrap();
+    tokio::spawn(async move {
+        loop {
+            scan_publisher
+                .publish(&LaserScan {
+                    header: Header {
+                        stamp: r2r::builtin_interfaces::msg::Time {
+                            sec: -12,
+                            nanosec: 5,
+                        },
+                        frame_id: "laser".to_owned(),
+                    },
+                    ranges: vec![1.; 3],
+                    angle_max: 0.2,
+                    angle_increment: 0.1,
+                    ..Default::default()
+                })
+                .unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    });
+    node.run_spin_thread(Duration::from_millis(10));
+    let client = Ros2LaserScan2D::with_timeout(node, TOPIC, Duration::from_secs(3)).unwrap();
+
+    let scan = client.current_scan_async().await.unwrap();
+    assert_eq!(
+        scan.stamp,
+        Some(std::time::SystemTime::UNIX_EPOCH - Duration::from_secs(12) + Duration::from_nanos(5))
+    );
 }
@@ -86,3 +86,56 @@ async fn test_localization_client_nomotion_update() {
 
     client.request_nomotion_update().await;
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_localization_client_frame_id() {
+    const TOPIC: &str = "/amcl_pose_in_map";
+    let node = test_node();
+
+    let pose_publisher = node
+        .r2r()
+        .create_publisher::<PoseWithCovarianceStamped>(TOPIC, QosProfile::default())
+        .unwrap();
+
+    tokio::spawn(async move {
+        loop {
+            pose_publisher
+                .publish(&PoseWithCovarianceStamped {
+                    header: Header {
+                        frame_id: "map".to_owned(),
+                        ..Default::default()
+                    },
+                    pose: PoseWithCovariance {
+                        pose: Pose {
+                            position: Point {
+                                x: 1.0,
+                                y: 0.0,
+                                z: 0.0,
+                            },
+                            orientation: Quaternion {
+                                w: 1.0,
+                                ..Default::default()
+                            },
+                        },
+                        covariance: vec![0f64; 36],
+                    },
+                })
+                .unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    });
+
+    node.run_spin_thread(Duration::from_millis(100));
+    let mut client =
+        Ros2LocalizationClient::new(node, false, NO_MOTION_UPDATE_SERVICE, TOPIC).unwrap();
+
+    assert_approx_eq!(client.current_pose("").unwrap().translation.x, 1.0);
+    assert_approx_eq!(client.current_pose("map").unwrap().translation.x, 1.0);
+    assert!(client.current_pose("odom").is_err());
+
+    client.set_frame_id(Some("map".to_owned()));
+    assert_eq!(client.frame_id(), Some("map"));
+    assert!(client.current_pose("").is_ok());
+    client.set_frame_id(Some("odom".to_owned()));
+    assert!(client.current_pose("").is_err());
+}
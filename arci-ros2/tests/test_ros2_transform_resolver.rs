@@ -2,7 +2,10 @@
 
 use std::time::Duration;
 
-use arci::TransformResolver;
+use arci::{
+    nalgebra::{Translation3, UnitQuaternion},
+    Isometry3, TransformResolver,
+};
 use arci_ros2::{
     r2r::{
         builtin_interfaces::msg::Time, geometry_msgs::msg::TransformStamped,
@@ -48,7 +51,7 @@ async fn test_transform_resolver() {
 
     tf_node.run_spin_thread(Duration::from_millis(100));
 
-    let ros2_transform_resolver = arci_ros2::Ros2TransformResolver::new(
+    let mut ros2_transform_resolver = arci_ros2::Ros2TransformResolver::new(
         tf_node.clone(),
         Duration::from_millis(100),
         RETRY_RATE,
@@ -120,6 +123,26 @@ async fn test_transform_resolver() {
     assert_approx_eq!(tf_received.rotation.i, EXPECTED_ROT_X);
     assert_approx_eq!(tf_received.rotation.j, EXPECTED_ROT_Y);
     assert_approx_eq!(tf_received.rotation.k, EXPECTED_ROT_Z);
+
+    // Compose a static transform not published to TF.
+    let camera = Isometry3::from_parts(
+        Translation3::new(0.1, 0.2, 0.3),
+        UnitQuaternion::from_euler_angles(0.0, 0.0, std::f64::consts::FRAC_PI_2),
+    );
+    ros2_transform_resolver.add_static(FRAME_ID_3, "camera", camera);
+    let camera_received = ros2_transform_resolver
+        .resolve_transformation("camera", FRAME_ID_1, dummy_time_middle)
+        .unwrap();
+    let expected = camera.inverse() * tf_received;
+    assert_approx_eq!(camera_received.translation.x, expected.translation.x);
+    assert_approx_eq!(camera_received.translation.y, expected.translation.y);
+    assert_approx_eq!(camera_received.translation.z, expected.translation.z);
+    assert_approx_eq!(camera_received.rotation.angle_to(&expected.rotation), 0.0);
+    let camera_received = ros2_transform_resolver
+        .resolve_transformation(FRAME_ID_3, "camera", dummy_time_middle)
+        .unwrap();
+    assert_approx_eq!(camera_received.translation.x, camera.translation.x);
+    assert_approx_eq!(camera_received.rotation.angle_to(&camera.rotation), 0.0);
 }
 
 fn ros2_time_from_duration(duration: Duration) -> Time {
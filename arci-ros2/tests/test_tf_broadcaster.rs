@@ -0,0 +1,44 @@
+#![cfg(feature = "ros2")]
+
+mod shared;
+
+use std::time::Duration;
+
+use arci::{Isometry3, Vector3};
+use arci_ros2::{r2r, Ros2TfBroadcaster};
+use assert_approx_eq::assert_approx_eq;
+use futures::stream::StreamExt;
+use r2r::tf2_msgs::msg::TFMessage;
+use shared::*;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_publish_tf() {
+    let node = test_node();
+    let mut sub = node
+        .r2r()
+        .subscribe::<TFMessage>("/tf", r2r::QosProfile::default())
+        .unwrap();
+    let broadcaster = Ros2TfBroadcaster::new(node.clone()).unwrap();
+    node.run_spin_thread(Duration::from_millis(10));
+
+    let transform = Isometry3::new(Vector3::new(1.0, -2.0, 0.5), Vector3::z() * 0.3);
+    tokio::spawn(async move {
+        loop {
+            broadcaster
+                .publish_tf("base_link", "ik_target", &transform)
+                .unwrap();
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    });
+
+    let msg = sub.next().await.unwrap();
+    assert_eq!(msg.transforms.len(), 1);
+    let tf = &msg.transforms[0];
+    assert_eq!(tf.header.frame_id, "base_link");
+    assert_eq!(tf.child_frame_id, "ik_target");
+    assert_approx_eq!(tf.transform.translation.x, 1.0);
+    assert_approx_eq!(tf.transform.translation.y, -2.0);
+    assert_approx_eq!(tf.transform.translation.z, 0.5);
+    assert_approx_eq!(tf.transform.rotation.z, (0.3_f64 / 2.0).sin());
+    assert_approx_eq!(tf.transform.rotation.w, (0.3_f64 / 2.0).cos());
+}
@@ -1,3 +1,6 @@
+mod cached_sensor;
+mod clamp_to_limits_joint_trajectory_client;
+mod direction_inverting_joint_trajectory_client;
 mod dummy_gamepad;
 mod dummy_laser_scan;
 mod dummy_localization;
@@ -7,13 +10,19 @@ mod dummy_navigation;
 mod dummy_speaker;
 mod dummy_trajectory_client;
 mod dummy_transform_resolver;
+mod filtered_joint_trajectory_client;
 mod joint_position_difference_limiter;
 mod joint_position_limiter;
 mod joint_trajectory_clients_container;
 mod joint_velocity_limiter;
 mod lazy;
 mod partial_joint_trajectory_client;
+mod switchable_joint_trajectory_client;
+mod tee_joint_trajectory_client;
 
+pub use cached_sensor::*;
+pub use clamp_to_limits_joint_trajectory_client::*;
+pub use direction_inverting_joint_trajectory_client::*;
 pub use dummy_gamepad::*;
 pub use dummy_laser_scan::*;
 pub use dummy_localization::*;
@@ -23,9 +32,12 @@ pub use dummy_navigation::*;
 pub use dummy_speaker::*;
 pub use dummy_trajectory_client::*;
 pub use dummy_transform_resolver::*;
+pub use filtered_joint_trajectory_client::*;
 pub use joint_position_difference_limiter::*;
 pub use joint_position_limiter::*;
 pub use joint_trajectory_clients_container::*;
 pub use joint_velocity_limiter::*;
 pub use lazy::*;
 pub use partial_joint_trajectory_client::*;
+pub use switchable_joint_trajectory_client::*;
+pub use tee_joint_trajectory_client::*;
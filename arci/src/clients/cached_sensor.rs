@@ -0,0 +1,145 @@
+use std::{
+    marker::PhantomData,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tracing::debug;
+
+use crate::{
+    error::Error,
+    traits::{LaserScan2D, Scan2D, Sensor},
+};
+
+/// Sensor which returns the last measurement while the inner sensor fails.
+///
+/// The measurements successfully read from `sensor` are cached. If reading
+/// `sensor` fails, the cached measurement is returned instead as long as it
+/// is not older than `max_age`; otherwise, the error of `sensor` is returned.
+/// The age of the cached measurement can be checked with
+/// [`age`](Self::age) and [`is_stale`](Self::is_stale).
+#[derive(Debug)]
+pub struct CachedSensor<S, Output>
+where
+    S: Sensor<Output>,
+{
+    sensor: S,
+    max_age: Duration,
+    cache: Mutex<Option<(Output, Instant)>>,
+    _output: PhantomData<fn() -> Output>,
+}
+
+impl<S, Output> CachedSensor<S, Output>
+where
+    S: Sensor<Output>,
+{
+    /// Creates a new `CachedSensor` which returns the cached measurement of
+    /// `sensor` for up to `max_age`.
+    pub fn new(sensor: S, max_age: Duration) -> Self {
+        Self {
+            sensor,
+            max_age,
+            cache: Mutex::new(None),
+            _output: PhantomData,
+        }
+    }
+
+    /// Returns the time elapsed since the cached measurement was read, or
+    /// `None` if nothing has been read yet.
+    pub fn age(&self) -> Option<Duration> {
+        self.cache
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|(_, time)| time.elapsed())
+    }
+
+    /// Returns `true` if there is no cached measurement younger than `max_age`.
+    pub fn is_stale(&self) -> bool {
+        self.age().map_or(true, |age| age > self.max_age)
+    }
+}
+
+impl<S, Output> Sensor<Output> for CachedSensor<S, Output>
+where
+    S: Sensor<Output>,
+    Output: Clone + Send,
+{
+    fn read(&self) -> Result<Output, Error> {
+        // Don't hold the lock while reading, which may block.
+        let result = self.sensor.read();
+        let mut cache = self.cache.lock().unwrap();
+        match result {
+            Ok(output) => {
+                *cache = Some((output.clone(), Instant::now()));
+                Ok(output)
+            }
+            Err(e) => match &*cache {
+                Some((output, time)) if time.elapsed() <= self.max_age => {
+                    debug!("Returning the cached measurement: {e}");
+                    Ok(output.clone())
+                }
+                _ => Err(e),
+            },
+        }
+    }
+}
+
+impl<S> LaserScan2D for CachedSensor<S, Scan2D>
+where
+    S: Sensor<Scan2D>,
+{
+    fn current_scan(&self) -> Result<Scan2D, Error> {
+        self.read()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct TestSensor {
+        value: Mutex<Option<f64>>,
+    }
+
+    impl Sensor<f64> for TestSensor {
+        fn read(&self) -> Result<f64, Error> {
+            self.value.lock().unwrap().ok_or_else(|| Error::Connection {
+                message: "no value".to_owned(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_cached_laser_scan() {
+        let laser_scan = crate::DummyLaserScan2D::uniform(0.0, 1.0, 0.5, 2.0);
+        let laser_scan = CachedSensor::new(laser_scan, Duration::from_secs(1));
+        assert_eq!(laser_scan.current_scan().unwrap().ranges, vec![2.0; 3]);
+    }
+
+    #[test]
+    fn test_cached_sensor() {
+        let sensor = CachedSensor::new(TestSensor::default(), Duration::from_millis(100));
+        assert!(sensor.read().is_err());
+        assert!(sensor.age().is_none());
+        assert!(sensor.is_stale());
+
+        *sensor.sensor.value.lock().unwrap() = Some(1.0);
+        assert_eq!(sensor.read().unwrap(), 1.0);
+        assert!(!sensor.is_stale());
+
+        // the inner sensor fails
+        *sensor.sensor.value.lock().unwrap() = None;
+        assert_eq!(sensor.read().unwrap(), 1.0);
+
+        std::thread::sleep(Duration::from_millis(150));
+        assert!(sensor.is_stale());
+        assert!(sensor.age().unwrap() >= Duration::from_millis(150));
+        assert!(sensor.read().is_err());
+
+        *sensor.sensor.value.lock().unwrap() = Some(2.0);
+        assert_eq!(sensor.read().unwrap(), 2.0);
+        assert!(!sensor.is_stale());
+    }
+}
@@ -0,0 +1,155 @@
+use super::joint_position_limiter::joint_position_limits_from_urdf;
+use crate::{
+    error::Error,
+    traits::{JointTrajectoryClient, TrajectoryPoint},
+    waits::WaitFuture,
+    JointPositionLimit,
+};
+
+/// JointTrajectoryClient which clamps the reported joint positions into the limits.
+///
+/// This is the read-side counterpart of [`JointPositionLimiter`](crate::JointPositionLimiter):
+/// positions slightly outside the limits (e.g., due to floating point errors or
+/// calibration noise) returned by `current_joint_positions` of `client` are
+/// clamped. Commands are passed through as is.
+#[derive(Debug, Clone)]
+pub struct ClampToLimitsJointTrajectoryClient<C>
+where
+    C: JointTrajectoryClient,
+{
+    client: C,
+    limits: Vec<JointPositionLimit>,
+}
+
+impl<C> ClampToLimitsJointTrajectoryClient<C>
+where
+    C: JointTrajectoryClient,
+{
+    /// Creates a new `ClampToLimitsJointTrajectoryClient` with the given position limits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths of `limits` and joints that `client` handles are different.
+    #[track_caller]
+    pub fn new(client: C, limits: Vec<JointPositionLimit>) -> Self {
+        assert!(client.joint_names().len() == limits.len());
+        Self { client, limits }
+    }
+
+    /// Creates a new `ClampToLimitsJointTrajectoryClient` with the position limits defined in URDF.
+    pub fn from_urdf(client: C, joints: &[urdf_rs::Joint]) -> Result<Self, Error> {
+        let limits = joint_position_limits_from_urdf(&client.joint_names(), joints)?;
+        Ok(Self { client, limits })
+    }
+}
+
+impl<C> JointTrajectoryClient for ClampToLimitsJointTrajectoryClient<C>
+where
+    C: JointTrajectoryClient,
+{
+    fn joint_names(&self) -> Vec<String> {
+        self.client.joint_names()
+    }
+
+    fn current_joint_positions(&self) -> Result<Vec<f64>, Error> {
+        let mut positions = self.client.current_joint_positions()?;
+        for (position, limit) in positions.iter_mut().zip(&self.limits) {
+            if let (Some(lower), Some(upper)) = (limit.lower(), limit.upper()) {
+                *position = position.clamp(lower, upper);
+            }
+        }
+        Ok(positions)
+    }
+
+    fn send_joint_positions(
+        &self,
+        positions: Vec<f64>,
+        duration: std::time::Duration,
+    ) -> Result<WaitFuture, Error> {
+        self.client.send_joint_positions(positions, duration)
+    }
+
+    fn send_joint_trajectory(&self, trajectory: Vec<TrajectoryPoint>) -> Result<WaitFuture, Error> {
+        self.client.send_joint_trajectory(trajectory)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DummyJointTrajectoryClient;
+
+    #[test]
+    #[should_panic]
+    fn mismatch_size() {
+        let client = DummyJointTrajectoryClient::new(vec!["a".to_owned()]);
+        ClampToLimitsJointTrajectoryClient::new(
+            client,
+            vec![(1.0..=2.0).into(), (2.0..=3.0).into()],
+        );
+    }
+
+    #[test]
+    fn clamp_current_joint_positions() {
+        let client =
+            DummyJointTrajectoryClient::new(vec!["a".to_owned(), "b".to_owned(), "c".to_owned()]);
+        *client.positions.lock().unwrap() = vec![-1.0001, 1.0001, 5.0];
+        let client = ClampToLimitsJointTrajectoryClient::new(
+            client,
+            vec![
+                (-1.0..=1.0).into(),
+                (-1.0..=1.0).into(),
+                JointPositionLimit::none(),
+            ],
+        );
+        assert_eq!(
+            client.current_joint_positions().unwrap(),
+            vec![-1.0, 1.0, 5.0]
+        );
+
+        // commands are not clamped
+        drop(
+            client
+                .send_joint_positions(vec![2.0, 0.0, 0.0], std::time::Duration::from_secs(1))
+                .unwrap(),
+        );
+        assert_eq!(
+            *client.client.positions.lock().unwrap(),
+            vec![2.0, 0.0, 0.0]
+        );
+        assert_eq!(
+            client.current_joint_positions().unwrap(),
+            vec![1.0, 0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn from_urdf() {
+        let s = r#"
+            <robot name="robot">
+                <joint name="a" type="revolute">
+                    <origin xyz="0.0 0.0 0.0" />
+                    <parent link="b" />
+                    <child link="c" />
+                    <axis xyz="0 1 0" />
+                    <limit lower="-1" upper="1.0" effort="0" velocity="1.0"/>
+                </joint>
+                <joint name="b" type="continuous">
+                    <origin xyz="0.0 0.0 0.0" />
+                    <parent link="c" />
+                    <child link="d" />
+                    <axis xyz="0 1 0" />
+                </joint>
+            </robot>
+        "#;
+        let urdf_robot = urdf_rs::read_from_string(s).unwrap();
+        let client = DummyJointTrajectoryClient::new(vec!["a".to_owned(), "b".to_owned()]);
+        *client.positions.lock().unwrap() = vec![2.0, 10.0];
+        let client =
+            ClampToLimitsJointTrajectoryClient::from_urdf(client, &urdf_robot.joints).unwrap();
+        assert_eq!(client.current_joint_positions().unwrap(), vec![1.0, 10.0]);
+
+        let client = DummyJointTrajectoryClient::new(vec!["c".to_owned()]);
+        assert!(ClampToLimitsJointTrajectoryClient::from_urdf(client, &urdf_robot.joints).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use crate::{
+    error::Error,
+    traits::{JointTrajectoryClient, TrajectoryPoint},
+    waits::WaitFuture,
+};
+
+/// JointTrajectoryClient which inverts the direction of the specified joints.
+///
+/// The commanded positions and velocities of the inverted joints are negated
+/// before sending to `client`, and the positions read from `client` are negated
+/// back. This is useful when the direction of a joint of the hardware is
+/// opposite to that of the model (e.g., URDF).
+#[derive(Debug)]
+pub struct DirectionInvertingJointTrajectoryClient<C>
+where
+    C: JointTrajectoryClient,
+{
+    client: C,
+    inverted: Vec<bool>,
+}
+
+impl<C> DirectionInvertingJointTrajectoryClient<C>
+where
+    C: JointTrajectoryClient,
+{
+    /// Creates a new `DirectionInvertingJointTrajectoryClient`.
+    ///
+    /// `inverted[i]` is `true` if the direction of the i-th joint is inverted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths of `inverted` and joints that `client` handles are different.
+    #[track_caller]
+    pub fn new(client: C, inverted: Vec<bool>) -> Self {
+        assert!(client.joint_names().len() == inverted.len());
+        Self { client, inverted }
+    }
+
+    /// Creates a new `DirectionInvertingJointTrajectoryClient` which inverts
+    /// the joints of the given names.
+    pub fn from_inverted_joint_names(
+        client: C,
+        inverted_joint_names: &[String],
+    ) -> Result<Self, Error> {
+        let joint_names = client.joint_names();
+        if let Some(name) = inverted_joint_names
+            .iter()
+            .find(|name| !joint_names.contains(name))
+        {
+            return Err(Error::NoJoint(name.clone()));
+        }
+        let inverted = joint_names
+            .iter()
+            .map(|name| inverted_joint_names.contains(name))
+            .collect();
+        Ok(Self { client, inverted })
+    }
+
+    fn invert(&self, values: &mut [f64]) {
+        for (value, &inverted) in values.iter_mut().zip(&self.inverted) {
+            if inverted {
+                *value = -*value;
+            }
+        }
+    }
+}
+
+impl<C> JointTrajectoryClient for DirectionInvertingJointTrajectoryClient<C>
+where
+    C: JointTrajectoryClient,
+{
+    fn joint_names(&self) -> Vec<String> {
+        self.client.joint_names()
+    }
+
+    fn current_joint_positions(&self) -> Result<Vec<f64>, Error> {
+        let mut positions = self.client.current_joint_positions()?;
+        self.invert(&mut positions);
+        Ok(positions)
+    }
+
+    fn send_joint_positions(
+        &self,
+        mut positions: Vec<f64>,
+        duration: std::time::Duration,
+    ) -> Result<WaitFuture, Error> {
+        self.invert(&mut positions);
+        self.client.send_joint_positions(positions, duration)
+    }
+
+    fn send_joint_trajectory(
+        &self,
+        mut trajectory: Vec<TrajectoryPoint>,
+    ) -> Result<WaitFuture, Error> {
+        for point in &mut trajectory {
+            self.invert(&mut point.positions);
+            if let Some(velocities) = &mut point.velocities {
+                self.invert(velocities);
+            }
+        }
+        self.client.send_joint_trajectory(trajectory)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::DummyJointTrajectoryClient;
+
+    fn dummy() -> DummyJointTrajectoryClient {
+        DummyJointTrajectoryClient::new(vec!["a".to_owned(), "b".to_owned(), "c".to_owned()])
+    }
+
+    #[test]
+    fn invert() {
+        let client = DirectionInvertingJointTrajectoryClient::new(dummy(), vec![true, false, true]);
+        drop(
+            client
+                .send_joint_positions(vec![1.0, 2.0, 3.0], Duration::from_secs(1))
+                .unwrap(),
+        );
+        assert_eq!(
+            *client.client.positions.lock().unwrap(),
+            vec![-1.0, 2.0, -3.0]
+        );
+        assert_eq!(
+            client.current_joint_positions().unwrap(),
+            vec![1.0, 2.0, 3.0]
+        );
+
+        drop(
+            client
+                .send_joint_trajectory(vec![TrajectoryPoint {
+                    positions: vec![0.5, 0.5, -0.5],
+                    velocities: Some(vec![0.1, 0.2, 0.3]),
+                    time_from_start: Duration::from_secs(1),
+                }])
+                .unwrap(),
+        );
+        let last_trajectory = client.client.last_trajectory.lock().unwrap();
+        assert_eq!(last_trajectory[0].positions, vec![-0.5, 0.5, 0.5]);
+        assert_eq!(last_trajectory[0].velocities, Some(vec![-0.1, 0.2, -0.3]));
+    }
+
+    #[test]
+    fn from_inverted_joint_names() {
+        let client = DirectionInvertingJointTrajectoryClient::from_inverted_joint_names(
+            dummy(),
+            &["c".to_owned()],
+        )
+        .unwrap();
+        assert_eq!(client.inverted, vec![false, false, true]);
+        assert!(
+            DirectionInvertingJointTrajectoryClient::from_inverted_joint_names(
+                dummy(),
+                &["d".to_owned()]
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn mismatch_size() {
+        DirectionInvertingJointTrajectoryClient::new(dummy(), vec![true]);
+    }
+}
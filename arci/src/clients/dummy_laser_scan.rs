@@ -1,6 +1,6 @@
 use std::sync::Mutex;
 
-use crate::{Error, LaserScan2D, Scan2D};
+use crate::{Error, LaserScan2D, Scan2D, Sensor};
 
 #[derive(Debug, Default)]
 pub struct DummyLaserScan2D {
@@ -12,17 +12,109 @@ impl DummyLaserScan2D {
         Self::default()
     }
 
+    /// Creates a new `DummyLaserScan2D` which returns `scan`.
+    pub fn with_scan(scan: Scan2D) -> Self {
+        Self {
+            scan: Mutex::new(scan),
+        }
+    }
+
+    /// Creates a new `DummyLaserScan2D` which sees a wall at `range` in every
+    /// direction from `angle_min` to `angle_max` (inclusive).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `angle_increment` is not positive and finite, or if
+    /// `angle_min` is greater than `angle_max`.
+    #[track_caller]
+    pub fn uniform(angle_min: f64, angle_max: f64, angle_increment: f64, range: f64) -> Self {
+        Self::with_scan(synthetic_scan(
+            angle_min,
+            angle_max,
+            angle_increment,
+            |_| range,
+        ))
+    }
+
+    /// Creates a new `DummyLaserScan2D` which sees only an obstacle at
+    /// `range`, within `half_width` (in radians) of `angle`.
+    ///
+    /// The other beams have no return (infinite range), which is reported as
+    /// invalid by [`Scan2D::finite_ranges`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `angle_increment` is not positive and finite, or if
+    /// `angle_min` is greater than `angle_max`.
+    #[track_caller]
+    pub fn single_obstacle(
+        angle_min: f64,
+        angle_max: f64,
+        angle_increment: f64,
+        angle: f64,
+        half_width: f64,
+        range: f64,
+    ) -> Self {
+        Self::with_scan(synthetic_scan(
+            angle_min,
+            angle_max,
+            angle_increment,
+            |beam_angle| {
+                if (beam_angle - angle).abs() <= half_width {
+                    range
+                } else {
+                    f64::INFINITY
+                }
+            },
+        ))
+    }
+
     pub fn set_scan(&self, scan: Scan2D) {
         *self.scan.lock().unwrap() = scan;
     }
 }
 
+#[track_caller]
+fn synthetic_scan(
+    angle_min: f64,
+    angle_max: f64,
+    angle_increment: f64,
+    range: impl Fn(f64) -> f64,
+) -> Scan2D {
+    assert!(
+        angle_increment.is_finite() && angle_increment > 0.0,
+        "angle_increment must be positive and finite: {angle_increment}"
+    );
+    assert!(
+        angle_min <= angle_max,
+        "angle_min must not be greater than angle_max: {angle_min} > {angle_max}"
+    );
+    let num_beams = ((angle_max - angle_min) / angle_increment).round() as usize + 1;
+    Scan2D {
+        angle_min,
+        angle_max,
+        angle_increment,
+        range_min: 0.0,
+        range_max: f64::MAX,
+        ranges: (0..num_beams)
+            .map(|i| range(angle_min + i as f64 * angle_increment))
+            .collect(),
+        ..Default::default()
+    }
+}
+
 impl LaserScan2D for DummyLaserScan2D {
     fn current_scan(&self) -> Result<Scan2D, Error> {
         Ok(self.scan.lock().unwrap().clone())
     }
 }
 
+impl Sensor<Scan2D> for DummyLaserScan2D {
+    fn read(&self) -> Result<Scan2D, Error> {
+        self.current_scan()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -45,4 +137,43 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_uniform() {
+        let laser_scan = DummyLaserScan2D::uniform(-1.0, 1.0, 0.5, 2.0);
+        let scan = laser_scan.current_scan().unwrap();
+        assert_eq!(scan.ranges, vec![2.0; 5]);
+        assert_eq!(
+            scan.finite_ranges()
+                .map(|(angle, _)| angle)
+                .collect::<Vec<_>>(),
+            vec![-1.0, -0.5, 0.0, 0.5, 1.0]
+        );
+        assert_eq!(scan.min_range_in_arc(0.0, 0.1), Some(2.0));
+    }
+
+    #[test]
+    fn test_single_obstacle() {
+        let laser_scan = DummyLaserScan2D::single_obstacle(-1.0, 1.0, 0.25, 0.5, 0.3, 1.5);
+        let scan = laser_scan.current_scan().unwrap();
+        assert_eq!(scan.ranges.len(), 9);
+        assert_eq!(
+            scan.finite_ranges().collect::<Vec<_>>(),
+            vec![(0.25, 1.5), (0.5, 1.5), (0.75, 1.5)]
+        );
+        assert_eq!(scan.min_range_in_arc(0.5, 0.1), Some(1.5));
+        assert_eq!(scan.min_range_in_arc(-0.5, 0.3), None);
+    }
+
+    #[test]
+    #[should_panic = "angle_increment must be positive and finite"]
+    fn test_uniform_zero_increment() {
+        DummyLaserScan2D::uniform(-1.0, 1.0, 0.0, 2.0);
+    }
+
+    #[test]
+    #[should_panic = "angle_min must not be greater than angle_max"]
+    fn test_uniform_reversed_angles() {
+        DummyLaserScan2D::uniform(1.0, -1.0, 0.5, 2.0);
+    }
 }
@@ -0,0 +1,176 @@
+use std::{collections::VecDeque, sync::Mutex};
+
+use crate::{
+    error::Error,
+    traits::{JointTrajectoryClient, TrajectoryPoint},
+    waits::WaitFuture,
+};
+
+/// Filter applied by [`FilteredJointTrajectoryClient`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum JointPositionFilter {
+    /// Mean of the positions in the window.
+    #[default]
+    MovingAverage,
+    /// Median of the positions in the window. This is more robust against
+    /// outliers than the moving average.
+    Median,
+}
+
+/// JointTrajectoryClient which smooths the reported joint positions.
+///
+/// The positions returned by `current_joint_positions` of `client` are kept
+/// in a window of the last `window_size` reads, and the filtered positions of
+/// each joint in the window are returned. This reduces the noise of encoders,
+/// at the cost of delay. Commands are passed through as is.
+#[derive(Debug)]
+pub struct FilteredJointTrajectoryClient<C>
+where
+    C: JointTrajectoryClient,
+{
+    client: C,
+    window_size: usize,
+    filter: JointPositionFilter,
+    window: Mutex<VecDeque<Vec<f64>>>,
+}
+
+impl<C> FilteredJointTrajectoryClient<C>
+where
+    C: JointTrajectoryClient,
+{
+    /// Creates a new `FilteredJointTrajectoryClient` with the given window size and filter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window_size` is zero.
+    #[track_caller]
+    pub fn new(client: C, window_size: usize, filter: JointPositionFilter) -> Self {
+        assert!(window_size > 0, "window_size must be positive");
+        Self {
+            client,
+            window_size,
+            filter,
+            window: Mutex::new(VecDeque::with_capacity(window_size)),
+        }
+    }
+
+    /// Clears the positions in the window, e.g., after the robot is moved by
+    /// something other than this client.
+    pub fn clear(&self) {
+        self.window.lock().unwrap().clear();
+    }
+}
+
+impl<C> JointTrajectoryClient for FilteredJointTrajectoryClient<C>
+where
+    C: JointTrajectoryClient,
+{
+    fn joint_names(&self) -> Vec<String> {
+        self.client.joint_names()
+    }
+
+    fn current_joint_positions(&self) -> Result<Vec<f64>, Error> {
+        let positions = self.client.current_joint_positions()?;
+        let mut window = self.window.lock().unwrap();
+        if window.front().is_some_and(|p| p.len() != positions.len()) {
+            window.clear();
+        }
+        if window.len() == self.window_size {
+            window.pop_front();
+        }
+        window.push_back(positions);
+        let dof = window[0].len();
+        Ok((0..dof)
+            .map(|i| {
+                let mut values = window.iter().map(|p| p[i]).collect::<Vec<_>>();
+                match self.filter {
+                    JointPositionFilter::MovingAverage => {
+                        values.iter().sum::<f64>() / values.len() as f64
+                    }
+                    JointPositionFilter::Median => {
+                        values.sort_by(f64::total_cmp);
+                        let mid = values.len() / 2;
+                        if values.len() % 2 == 0 {
+                            (values[mid - 1] + values[mid]) / 2.0
+                        } else {
+                            values[mid]
+                        }
+                    }
+                }
+            })
+            .collect())
+    }
+
+    fn send_joint_positions(
+        &self,
+        positions: Vec<f64>,
+        duration: std::time::Duration,
+    ) -> Result<WaitFuture, Error> {
+        self.client.send_joint_positions(positions, duration)
+    }
+
+    fn send_joint_trajectory(&self, trajectory: Vec<TrajectoryPoint>) -> Result<WaitFuture, Error> {
+        self.client.send_joint_trajectory(trajectory)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DummyJointTrajectoryClient;
+
+    #[test]
+    #[should_panic]
+    fn zero_window_size() {
+        let client = DummyJointTrajectoryClient::new(vec!["a".to_owned()]);
+        FilteredJointTrajectoryClient::new(client, 0, JointPositionFilter::MovingAverage);
+    }
+
+    #[test]
+    fn moving_average() {
+        let client = DummyJointTrajectoryClient::new(vec!["a".to_owned(), "b".to_owned()]);
+        let client = FilteredJointTrajectoryClient::new(client, 3, JointPositionFilter::default());
+        let mut results = vec![];
+        for positions in [[1.0, 0.0], [2.0, 3.0], [3.0, 0.0], [7.0, 0.0]] {
+            *client.client.positions.lock().unwrap() = positions.to_vec();
+            results.push(client.current_joint_positions().unwrap());
+        }
+        assert_eq!(
+            results,
+            vec![
+                vec![1.0, 0.0],
+                vec![1.5, 1.5],
+                vec![2.0, 1.0],
+                vec![4.0, 1.0]
+            ]
+        );
+
+        client.clear();
+        assert_eq!(client.current_joint_positions().unwrap(), vec![7.0, 0.0]);
+    }
+
+    #[test]
+    fn median() {
+        let client = DummyJointTrajectoryClient::new(vec!["a".to_owned()]);
+        let client = FilteredJointTrajectoryClient::new(client, 3, JointPositionFilter::Median);
+        let mut results = vec![];
+        for position in [1.0, 100.0, 2.0, 3.0, -50.0] {
+            *client.client.positions.lock().unwrap() = vec![position];
+            results.push(client.current_joint_positions().unwrap()[0]);
+        }
+        assert_eq!(results, vec![1.0, 50.5, 2.0, 3.0, 2.0]);
+    }
+
+    #[test]
+    fn pass_through_commands() {
+        let client = DummyJointTrajectoryClient::new(vec!["a".to_owned()]);
+        let client = FilteredJointTrajectoryClient::new(client, 2, JointPositionFilter::default());
+        drop(
+            client
+                .send_joint_positions(vec![2.0], std::time::Duration::from_secs(1))
+                .unwrap(),
+        );
+        assert_eq!(*client.client.positions.lock().unwrap(), vec![2.0]);
+    }
+}
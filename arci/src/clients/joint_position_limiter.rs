@@ -59,22 +59,7 @@ where
         joints: &[urdf_rs::Joint],
         strategy: JointPositionLimiterStrategy,
     ) -> Result<Self, Error> {
-        let mut limits = Vec::new();
-        for joint_name in client.joint_names() {
-            if let Some(i) = joints.iter().position(|j| j.name == *joint_name) {
-                let joint = &joints[i];
-                let limit = if JointType::Continuous == joint.joint_type {
-                    // Continuous joint has no limit.
-                    JointPositionLimit::none()
-                } else {
-                    (joint.limit.lower..=joint.limit.upper).into()
-                };
-                limits.push(limit);
-            } else {
-                return Err(Error::NoJoint(joint_name));
-            }
-        }
-
+        let limits = joint_position_limits_from_urdf(&client.joint_names(), joints)?;
         Ok(Self {
             client,
             limits,
@@ -157,6 +142,29 @@ where
     }
 }
 
+/// Returns the position limits of the joints defined in URDF.
+pub(crate) fn joint_position_limits_from_urdf(
+    joint_names: &[String],
+    joints: &[urdf_rs::Joint],
+) -> Result<Vec<JointPositionLimit>, Error> {
+    let mut limits = Vec::new();
+    for joint_name in joint_names {
+        if let Some(i) = joints.iter().position(|j| j.name == *joint_name) {
+            let joint = &joints[i];
+            let limit = if JointType::Continuous == joint.joint_type {
+                // Continuous joint has no limit.
+                JointPositionLimit::none()
+            } else {
+                (joint.limit.lower..=joint.limit.upper).into()
+            };
+            limits.push(limit);
+        } else {
+            return Err(Error::NoJoint(joint_name.clone()));
+        }
+    }
+    Ok(limits)
+}
+
 #[derive(Debug, Clone, Copy, Default)]
 pub struct JointPositionLimit(Option<JointPositionLimitInner>);
 
@@ -0,0 +1,199 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    error::Error,
+    traits::{JointTrajectoryClient, TrajectoryPoint},
+    waits::WaitFuture,
+};
+
+/// JointTrajectoryClient which routes all calls to one of the named backends,
+/// switchable at runtime (e.g., from hardware to simulation on a fault).
+#[derive(Debug)]
+pub struct SwitchableJointTrajectoryClient<C = Box<dyn JointTrajectoryClient>>
+where
+    C: JointTrajectoryClient,
+{
+    joint_names: Vec<String>,
+    clients: Vec<(String, C)>,
+    active_index: Arc<Mutex<usize>>,
+}
+
+impl<C> SwitchableJointTrajectoryClient<C>
+where
+    C: JointTrajectoryClient,
+{
+    /// Creates a new `SwitchableJointTrajectoryClient` with the first client active.
+    ///
+    /// Returns an error if `clients` is empty or the joint names of the clients
+    /// are different.
+    pub fn new(clients: Vec<(String, C)>) -> Result<Self, Error> {
+        let Some((_, first)) = clients.first() else {
+            return Err(Error::Other(anyhow::format_err!(
+                "SwitchableJointTrajectoryClient requires at least one client"
+            )));
+        };
+        let joint_names = first.joint_names();
+        for (_, client) in &clients[1..] {
+            let client_joint_names = client.joint_names();
+            if client_joint_names != joint_names {
+                return Err(Error::JointNamesMismatch {
+                    partial: client_joint_names,
+                    full: joint_names,
+                });
+            }
+        }
+        Ok(Self {
+            joint_names,
+            clients,
+            active_index: Arc::new(Mutex::new(0)),
+        })
+    }
+
+    /// Returns the shared index of the active client, which can be used to
+    /// switch the client from another place.
+    pub fn active_index_handle(&self) -> Arc<Mutex<usize>> {
+        self.active_index.clone()
+    }
+
+    /// Returns the index of the active client.
+    pub fn active_index(&self) -> usize {
+        *self.active_index.lock().unwrap()
+    }
+
+    /// Returns the name of the active client.
+    pub fn active_name(&self) -> Result<&str, Error> {
+        let index = self.active_index();
+        self.clients
+            .get(index)
+            .map(|(name, _)| name.as_str())
+            .ok_or_else(|| index_out_of_range(index, self.clients.len()))
+    }
+
+    /// Activates the client of the given index.
+    pub fn set_active_index(&self, index: usize) -> Result<(), Error> {
+        if index >= self.clients.len() {
+            return Err(index_out_of_range(index, self.clients.len()));
+        }
+        *self.active_index.lock().unwrap() = index;
+        Ok(())
+    }
+
+    /// Activates the client of the given name.
+    pub fn set_active(&self, name: &str) -> Result<(), Error> {
+        let index = self
+            .clients
+            .iter()
+            .position(|(n, _)| n == name)
+            .ok_or_else(|| Error::Other(anyhow::format_err!("No client named {name}")))?;
+        self.set_active_index(index)
+    }
+
+    fn active(&self) -> Result<&C, Error> {
+        let index = self.active_index();
+        self.clients
+            .get(index)
+            .map(|(_, client)| client)
+            .ok_or_else(|| index_out_of_range(index, self.clients.len()))
+    }
+}
+
+fn index_out_of_range(index: usize, len: usize) -> Error {
+    Error::Other(anyhow::format_err!(
+        "Client index {index} is out of range (number of clients = {len})"
+    ))
+}
+
+impl<C> JointTrajectoryClient for SwitchableJointTrajectoryClient<C>
+where
+    C: JointTrajectoryClient,
+{
+    fn joint_names(&self) -> Vec<String> {
+        self.joint_names.clone()
+    }
+
+    fn current_joint_positions(&self) -> Result<Vec<f64>, Error> {
+        self.active()?.current_joint_positions()
+    }
+
+    fn send_joint_positions(
+        &self,
+        positions: Vec<f64>,
+        duration: std::time::Duration,
+    ) -> Result<WaitFuture, Error> {
+        self.active()?.send_joint_positions(positions, duration)
+    }
+
+    fn send_joint_trajectory(&self, trajectory: Vec<TrajectoryPoint>) -> Result<WaitFuture, Error> {
+        self.active()?.send_joint_trajectory(trajectory)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::DummyJointTrajectoryClient;
+
+    fn dummy(joint_names: &[&str]) -> DummyJointTrajectoryClient {
+        DummyJointTrajectoryClient::new(joint_names.iter().map(|s| s.to_string()).collect())
+    }
+
+    #[test]
+    fn switch() {
+        let client = SwitchableJointTrajectoryClient::new(vec![
+            ("real".to_owned(), dummy(&["a", "b"])),
+            ("sim".to_owned(), dummy(&["a", "b"])),
+        ])
+        .unwrap();
+        assert_eq!(client.joint_names(), vec!["a", "b"]);
+        assert_eq!(client.active_name().unwrap(), "real");
+
+        drop(
+            client
+                .send_joint_positions(vec![1.0, 2.0], Duration::from_secs(1))
+                .unwrap(),
+        );
+        assert_eq!(client.current_joint_positions().unwrap(), vec![1.0, 2.0]);
+
+        client.set_active("sim").unwrap();
+        assert_eq!(client.active_index(), 1);
+        assert_eq!(client.current_joint_positions().unwrap(), vec![0.0, 0.0]);
+        drop(
+            client
+                .send_joint_trajectory(vec![TrajectoryPoint::new(
+                    vec![3.0, 4.0],
+                    Duration::from_secs(1),
+                )])
+                .unwrap(),
+        );
+        assert_eq!(
+            *client.clients[1].1.positions.lock().unwrap(),
+            vec![3.0, 4.0]
+        );
+        assert_eq!(
+            *client.clients[0].1.positions.lock().unwrap(),
+            vec![1.0, 2.0]
+        );
+
+        *client.active_index_handle().lock().unwrap() = 0;
+        assert_eq!(client.active_name().unwrap(), "real");
+
+        assert!(client.set_active("unknown").is_err());
+        assert!(client.set_active_index(2).is_err());
+        *client.active_index_handle().lock().unwrap() = 2;
+        assert!(client.current_joint_positions().is_err());
+    }
+
+    #[test]
+    fn joint_names_mismatch() {
+        assert!(SwitchableJointTrajectoryClient::new(vec![
+            ("real".to_owned(), dummy(&["a", "b"])),
+            ("sim".to_owned(), dummy(&["a", "c"])),
+        ])
+        .is_err());
+        assert!(
+            SwitchableJointTrajectoryClient::<DummyJointTrajectoryClient>::new(vec![]).is_err()
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use std::time::Duration;
+
+use futures::future::{self, Either};
+use tracing::warn;
+
+use crate::{
+    error::Error,
+    traits::{JointTrajectoryClient, TrajectoryPoint},
+    waits::WaitFuture,
+};
+
+/// JointTrajectoryClient which sends every command to a primary client and
+/// one or more secondary clients (e.g., a simulator or a logger).
+///
+/// Commands rejected by the primary are not sent to the secondaries. The
+/// returned `WaitFuture` waits only for the primary. Errors of the
+/// secondaries (on sending, or of their `WaitFuture`s completed before the
+/// primary) are logged and ignored. The current joint positions are read from
+/// the primary. This is useful for shadow execution and telemetry capture.
+#[derive(Debug)]
+pub struct TeeJointTrajectoryClient<P, S = Box<dyn JointTrajectoryClient>>
+where
+    P: JointTrajectoryClient,
+    S: JointTrajectoryClient,
+{
+    primary: P,
+    secondaries: Vec<S>,
+}
+
+impl<P, S> TeeJointTrajectoryClient<P, S>
+where
+    P: JointTrajectoryClient,
+    S: JointTrajectoryClient,
+{
+    /// Creates a new `TeeJointTrajectoryClient`.
+    ///
+    /// Returns an error if the joint names of the secondaries are different
+    /// from the ones of the primary.
+    pub fn new(primary: P, secondaries: Vec<S>) -> Result<Self, Error> {
+        let joint_names = primary.joint_names();
+        for secondary in &secondaries {
+            let secondary_joint_names = secondary.joint_names();
+            if secondary_joint_names != joint_names {
+                return Err(Error::JointNamesMismatch {
+                    partial: secondary_joint_names,
+                    full: joint_names,
+                });
+            }
+        }
+        Ok(Self {
+            primary,
+            secondaries,
+        })
+    }
+
+    /// Returns the primary client.
+    pub fn primary(&self) -> &P {
+        &self.primary
+    }
+
+    /// Returns the secondary clients.
+    pub fn secondaries(&self) -> &[S] {
+        &self.secondaries
+    }
+
+    fn tee(
+        &self,
+        primary: WaitFuture,
+        mut send_secondary: impl FnMut(&S) -> Result<WaitFuture, Error>,
+    ) -> WaitFuture {
+        let secondaries = self
+            .secondaries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, secondary)| match send_secondary(secondary) {
+                Ok(wait) => Some(async move {
+                    if let Err(e) = wait.await {
+                        warn!("Secondary client {i} failed: {e}");
+                    }
+                }),
+                Err(e) => {
+                    warn!("Failed to send to secondary client {i}: {e}");
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        WaitFuture::new(async move {
+            let secondaries = future::join_all(secondaries);
+            futures::pin_mut!(secondaries);
+            // Drive the secondaries while the primary is running to log their errors.
+            match future::select(primary, secondaries).await {
+                Either::Left((result, _)) => result,
+                Either::Right((_, primary)) => primary.await,
+            }
+        })
+    }
+}
+
+impl<P, S> JointTrajectoryClient for TeeJointTrajectoryClient<P, S>
+where
+    P: JointTrajectoryClient,
+    S: JointTrajectoryClient,
+{
+    fn joint_names(&self) -> Vec<String> {
+        self.primary.joint_names()
+    }
+
+    fn current_joint_positions(&self) -> Result<Vec<f64>, Error> {
+        self.primary.current_joint_positions()
+    }
+
+    fn send_joint_positions(
+        &self,
+        positions: Vec<f64>,
+        duration: Duration,
+    ) -> Result<WaitFuture, Error> {
+        let primary = self
+            .primary
+            .send_joint_positions(positions.clone(), duration)?;
+        Ok(self.tee(primary, |secondary| {
+            secondary.send_joint_positions(positions.clone(), duration)
+        }))
+    }
+
+    fn send_joint_trajectory(&self, trajectory: Vec<TrajectoryPoint>) -> Result<WaitFuture, Error> {
+        let primary = self.primary.send_joint_trajectory(trajectory.clone())?;
+        Ok(self.tee(primary, |secondary| {
+            secondary.send_joint_trajectory(trajectory.clone())
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DummyJointTrajectoryClient;
+
+    fn dummy(joint_names: &[&str]) -> DummyJointTrajectoryClient {
+        DummyJointTrajectoryClient::new(joint_names.iter().map(|s| s.to_string()).collect())
+    }
+
+    #[tokio::test]
+    async fn tee() {
+        let client = TeeJointTrajectoryClient::new(
+            dummy(&["a", "b"]),
+            vec![dummy(&["a", "b"]), dummy(&["a", "b"])],
+        )
+        .unwrap();
+        assert_eq!(client.joint_names(), vec!["a", "b"]);
+
+        client
+            .send_joint_positions(vec![1.0, 2.0], Duration::from_secs(1))
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(client.current_joint_positions().unwrap(), vec![1.0, 2.0]);
+        for secondary in client.secondaries() {
+            assert_eq!(*secondary.positions.lock().unwrap(), vec![1.0, 2.0]);
+        }
+
+        client
+            .send_joint_trajectory(vec![TrajectoryPoint::new(
+                vec![3.0, 4.0],
+                Duration::from_secs(1),
+            )])
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(*client.primary().positions.lock().unwrap(), vec![3.0, 4.0]);
+        for secondary in client.secondaries() {
+            assert_eq!(*secondary.positions.lock().unwrap(), vec![3.0, 4.0]);
+        }
+
+        // errors of the secondaries are ignored
+        let client = TeeJointTrajectoryClient::new(
+            dummy(&["a"]),
+            vec![Box::new(ErrorClient) as Box<dyn JointTrajectoryClient>],
+        )
+        .unwrap();
+        client
+            .send_joint_positions(vec![1.0], Duration::from_secs(1))
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(*client.primary().positions.lock().unwrap(), vec![1.0]);
+
+        assert!(TeeJointTrajectoryClient::new(dummy(&["a"]), vec![dummy(&["b"])]).is_err());
+    }
+
+    struct ErrorClient;
+
+    impl JointTrajectoryClient for ErrorClient {
+        fn joint_names(&self) -> Vec<String> {
+            vec!["a".to_owned()]
+        }
+
+        fn current_joint_positions(&self) -> Result<Vec<f64>, Error> {
+            Err(Error::Uninitialized {
+                message: "error".to_owned(),
+            })
+        }
+
+        fn send_joint_positions(
+            &self,
+            _positions: Vec<f64>,
+            _duration: Duration,
+        ) -> Result<WaitFuture, Error> {
+            Err(Error::Uninitialized {
+                message: "error".to_owned(),
+            })
+        }
+
+        fn send_joint_trajectory(
+            &self,
+            _trajectory: Vec<TrajectoryPoint>,
+        ) -> Result<WaitFuture, Error> {
+            Ok(WaitFuture::new(async {
+                Err(Error::Uninitialized {
+                    message: "error".to_owned(),
+                })
+            }))
+        }
+    }
+}
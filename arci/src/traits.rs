@@ -5,6 +5,7 @@ mod localization;
 mod motor_drive;
 mod move_base;
 mod navigation;
+mod sensor;
 mod speaker;
 mod transform_resolver;
 
@@ -15,5 +16,6 @@ pub use localization::*;
 pub use motor_drive::*;
 pub use move_base::*;
 pub use navigation::*;
+pub use sensor::*;
 pub use speaker::*;
 pub use transform_resolver::*;
@@ -64,6 +64,17 @@ pub trait JointTrajectoryClient: Send + Sync {
     /// See the "Implementation" section of the
     /// [`send_joint_positions`](Self::send_joint_positions) method.
     fn send_joint_trajectory(&self, trajectory: Vec<TrajectoryPoint>) -> Result<WaitFuture, Error>;
+
+    /// Commands the joints to hold the current positions for the specified
+    /// duration, and returns a future that waits until complete.
+    ///
+    /// This is useful to stop the running motion, e.g., on mode transitions.
+    /// The default implementation sends the current joint positions as a
+    /// single trajectory point.
+    fn hold_current_position(&self, duration: std::time::Duration) -> Result<WaitFuture, Error> {
+        let positions = self.current_joint_positions()?;
+        self.send_joint_trajectory(vec![TrajectoryPoint::new(positions, duration)])
+    }
 }
 
 #[cfg(test)]
@@ -90,6 +101,24 @@ mod tests {
         assert_eq!(tp.time_from_start, std::time::Duration::from_secs(2));
     }
 
+    #[test]
+    fn test_hold_current_position() {
+        let client = crate::DummyJointTrajectoryClient::new(vec!["a".to_owned(), "b".to_owned()]);
+        *client.positions.lock().unwrap() = vec![1.0, 2.0];
+        drop(
+            client
+                .hold_current_position(std::time::Duration::from_secs(1))
+                .unwrap(),
+        );
+        let last_trajectory = client.last_trajectory.lock().unwrap();
+        assert_eq!(last_trajectory.len(), 1);
+        assert_eq!(last_trajectory[0].positions, vec![1.0, 2.0]);
+        assert_eq!(
+            last_trajectory[0].time_from_start,
+            std::time::Duration::from_secs(1)
+        );
+    }
+
     #[test]
     fn test_trajectory_point_debug() {
         let tp = TrajectoryPoint::new(vec![1.0, -1.0], std::time::Duration::from_secs(1));
@@ -1,8 +1,16 @@
+use std::time::SystemTime;
+
 use auto_impl::auto_impl;
 
 use crate::error::Error;
 
+/// A 2D laser scan.
+///
+/// This is `#[non_exhaustive]` so that fields can be added without breaking
+/// changes. Use [`new`](Self::new) or [`Default`] to create one, and set the
+/// optional fields (e.g., `stamp` and `frame_id`) afterwards.
 #[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
 pub struct Scan2D {
     /// The angle (in radians) where the scan starts, typically 0 for positive X-axis.
     pub angle_min: f64,
@@ -19,12 +27,322 @@ pub struct Scan2D {
     /// The maximum range (in meters) of valid distance measurements.
     pub range_max: f64,
     /// An array of distance measurements (in meters) for each angle.
+    ///
+    /// Values are passed through from the sensor as is, so they may be NaN,
+    /// infinite or outside `[range_min, range_max]` for invalid measurements.
+    /// Use [`finite_ranges`](Self::finite_ranges) to get only the valid ones.
     pub ranges: Vec<f64>,
     /// (Optional) An array of intensity values for each angle, not supported by all LIDAR sensors.
     pub intensities: Vec<f64>,
+    /// (Optional) The time when the scan was acquired, e.g., to check how old it is.
+    pub stamp: Option<SystemTime>,
+    /// (Optional) The frame of the sensor which the ranges are measured in.
+    pub frame_id: Option<String>,
+}
+
+impl Scan2D {
+    /// Creates a new `Scan2D`, validating that the fields are consistent.
+    ///
+    /// Returns an error if `intensities` is neither empty nor the same length
+    /// as `ranges`, or if the number of `ranges` does not match the count
+    /// implied by `angle_min`, `angle_max` and `angle_increment`.
+    /// `stamp` and `frame_id` are `None`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        angle_min: f64,
+        angle_max: f64,
+        angle_increment: f64,
+        time_increment: f64,
+        scan_time: f64,
+        range_min: f64,
+        range_max: f64,
+        ranges: Vec<f64>,
+        intensities: Vec<f64>,
+    ) -> Result<Self, Error> {
+        if !intensities.is_empty() && intensities.len() != ranges.len() {
+            return Err(Error::LengthMismatch {
+                model: ranges.len(),
+                input: intensities.len(),
+            });
+        }
+        if !ranges.is_empty() {
+            let span = angle_max - angle_min;
+            if !angle_increment.is_finite()
+                || !span.is_finite()
+                || (span != 0.0
+                    && (angle_increment == 0.0 || span.signum() != angle_increment.signum()))
+            {
+                return Err(Error::Other(anyhow::format_err!(
+                    "invalid scan angles: angle_min={angle_min}, angle_max={angle_max}, angle_increment={angle_increment}"
+                )));
+            }
+            // Allow one beam of slack, since drivers disagree on whether
+            // angle_max is inclusive.
+            let expected = if span == 0.0 {
+                0.0
+            } else {
+                span / angle_increment
+            };
+            let actual = (ranges.len() - 1) as f64;
+            if (actual - expected).abs() > 1.0 {
+                return Err(Error::LengthMismatch {
+                    model: expected.round() as usize + 1,
+                    input: ranges.len(),
+                });
+            }
+        }
+        Ok(Self {
+            angle_min,
+            angle_max,
+            angle_increment,
+            time_increment,
+            scan_time,
+            range_min,
+            range_max,
+            ranges,
+            intensities,
+            stamp: None,
+            frame_id: None,
+        })
+    }
+
+    /// Returns an iterator over the angles and ranges of the valid beams.
+    ///
+    /// Beams whose range is NaN, infinite or outside `[range_min, range_max]`
+    /// are skipped.
+    pub fn finite_ranges(&self) -> impl Iterator<Item = (f64, f64)> + '_ {
+        self.ranges
+            .iter()
+            .enumerate()
+            .filter(|(_, range)| {
+                range.is_finite() && (self.range_min..=self.range_max).contains(*range)
+            })
+            .map(|(i, &range)| (self.angle_min + i as f64 * self.angle_increment, range))
+    }
+
+    /// Returns the minimum valid range among the beams whose angle is within
+    /// `half_width` (in radians) of `center_angle`.
+    ///
+    /// Invalid ranges are ignored as in [`finite_ranges`](Self::finite_ranges).
+    /// Angles are compared modulo 2π, so windows crossing ±π are handled on
+    /// 360° scanners. Returns `None` if no valid beam is in the window.
+    pub fn min_range_in_arc(&self, center_angle: f64, half_width: f64) -> Option<f64> {
+        self.finite_ranges()
+            .filter(|(angle, _)| {
+                let diff = (angle - center_angle)
+                    .sin()
+                    .atan2((angle - center_angle).cos());
+                diff.abs() <= half_width
+            })
+            .map(|(_, range)| range)
+            .min_by(|a, b| a.total_cmp(b))
+    }
+
+    /// Resamples the scan to `n_beams` beams evenly spaced from `angle_min` to
+    /// `angle_max` (both inclusive).
+    ///
+    /// Each beam takes the range (and intensity) of the nearest beam of this
+    /// scan, without interpolation, so invalid ranges are never mixed with
+    /// valid ones. Beams outside the field of view of this scan are filled
+    /// with NaN (zero for intensities). `angle_increment` and `time_increment`
+    /// are recomputed for the new resolution.
+    pub fn resample(&self, n_beams: usize, angle_min: f64, angle_max: f64) -> Scan2D {
+        let angle_increment = if n_beams > 1 {
+            (angle_max - angle_min) / (n_beams - 1) as f64
+        } else {
+            0.0
+        };
+        let nearest = |i: usize| {
+            if self.ranges.is_empty() || self.angle_increment == 0.0 {
+                return None;
+            }
+            let angle = angle_min + i as f64 * angle_increment;
+            let index = ((angle - self.angle_min) / self.angle_increment).round();
+            (index >= 0.0 && index < self.ranges.len() as f64).then_some(index as usize)
+        };
+        let indices = (0..n_beams).map(nearest).collect::<Vec<_>>();
+        Scan2D {
+            angle_min,
+            angle_max,
+            angle_increment,
+            time_increment: if self.angle_increment == 0.0 {
+                0.0
+            } else {
+                self.time_increment * angle_increment / self.angle_increment
+            },
+            scan_time: self.scan_time,
+            range_min: self.range_min,
+            range_max: self.range_max,
+            ranges: indices
+                .iter()
+                .map(|index| index.map_or(f64::NAN, |index| self.ranges[index]))
+                .collect(),
+            intensities: if self.intensities.len() == self.ranges.len() {
+                indices
+                    .iter()
+                    .map(|index| index.map_or(0.0, |index| self.intensities[index]))
+                    .collect()
+            } else {
+                vec![]
+            },
+            stamp: self.stamp,
+            frame_id: self.frame_id.clone(),
+        }
+    }
 }
 
 #[auto_impl(Box, Arc)]
 pub trait LaserScan2D: Send + Sync {
     fn current_scan(&self) -> Result<Scan2D, Error>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_scan(
+        angle_increment: f64,
+        num_ranges: usize,
+        num_intensities: usize,
+    ) -> Result<Scan2D, Error> {
+        Scan2D::new(
+            0.0,
+            1.0,
+            angle_increment,
+            0.0,
+            0.1,
+            0.1,
+            10.0,
+            vec![1.0; num_ranges],
+            vec![0.5; num_intensities],
+        )
+    }
+
+    #[test]
+    fn test_scan2d_new() {
+        let scan = new_scan(0.25, 5, 0).unwrap();
+        assert_eq!(scan.ranges.len(), 5);
+        assert!(scan.intensities.is_empty());
+        assert!(new_scan(0.25, 4, 0).is_ok());
+        assert!(new_scan(0.25, 5, 5).is_ok());
+        assert!(new_scan(0.0, 0, 0).is_ok());
+    }
+
+    #[test]
+    fn test_scan2d_new_error() {
+        // intensities length mismatch
+        assert!(new_scan(0.25, 5, 3).is_err());
+        // too many / too few beams
+        assert!(new_scan(0.25, 10, 0).is_err());
+        assert!(new_scan(0.25, 2, 0).is_err());
+        // increment in the wrong direction
+        assert!(new_scan(-0.25, 5, 0).is_err());
+        assert!(new_scan(0.0, 5, 0).is_err());
+    }
+
+    #[test]
+    fn test_finite_ranges() {
+        let scan = Scan2D {
+            angle_min: 0.0,
+            angle_max: 1.25,
+            angle_increment: 0.25,
+            range_min: 0.1,
+            range_max: f64::INFINITY,
+            ranges: vec![5.0, f64::NAN, 3.0, 0.05, f64::INFINITY, 4.0],
+            ..Default::default()
+        };
+        assert_eq!(
+            scan.finite_ranges().collect::<Vec<_>>(),
+            vec![(0.0, 5.0), (0.5, 3.0), (1.25, 4.0)]
+        );
+    }
+
+    #[test]
+    fn test_resample() {
+        let scan = Scan2D {
+            angle_min: -1.0,
+            angle_max: 1.0,
+            angle_increment: 0.5,
+            time_increment: 0.1,
+            range_min: 0.1,
+            range_max: 10.0,
+            ranges: vec![1.0, 2.0, 3.0, 4.0, 5.0],
+            intensities: vec![10.0, 20.0, 30.0, 40.0, 50.0],
+            ..Default::default()
+        };
+
+        // downsample
+        let resampled = scan.resample(3, -1.0, 1.0);
+        assert_eq!(resampled.angle_increment, 1.0);
+        assert_eq!(resampled.time_increment, 0.2);
+        assert_eq!(resampled.ranges, vec![1.0, 3.0, 5.0]);
+        assert_eq!(resampled.intensities, vec![10.0, 30.0, 50.0]);
+
+        // upsample to a wider field of view
+        let resampled = scan.resample(7, -1.5, 1.5);
+        assert_eq!(resampled.angle_min, -1.5);
+        assert_eq!(resampled.angle_max, 1.5);
+        assert_eq!(resampled.angle_increment, 0.5);
+        assert!(resampled.ranges[0].is_nan());
+        assert_eq!(resampled.ranges[1..6], [1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert!(resampled.ranges[6].is_nan());
+        assert_eq!(resampled.intensities[0], 0.0);
+        assert_eq!(resampled.finite_ranges().count(), 5);
+
+        // the header is kept
+        let scan = Scan2D {
+            stamp: Some(std::time::SystemTime::UNIX_EPOCH),
+            frame_id: Some("laser".to_owned()),
+            ..scan
+        };
+        let resampled = scan.resample(3, -1.0, 1.0);
+        assert_eq!(resampled.stamp, scan.stamp);
+        assert_eq!(resampled.frame_id, scan.frame_id);
+
+        // nearest beam
+        let resampled = scan.resample(2, -0.3, 0.8);
+        assert_eq!(resampled.ranges, vec![2.0, 5.0]);
+
+        assert!(scan.resample(0, -1.0, 1.0).ranges.is_empty());
+        assert!(Scan2D::default().resample(3, -1.0, 1.0).ranges[0].is_nan());
+    }
+
+    #[test]
+    fn test_min_range_in_arc() {
+        let scan = Scan2D {
+            angle_min: 0.0,
+            angle_max: 1.0,
+            angle_increment: 0.25,
+            range_min: 0.1,
+            range_max: 10.0,
+            ranges: vec![5.0, f64::NAN, 3.0, 0.05, 4.0],
+            ..Default::default()
+        };
+        assert_eq!(scan.min_range_in_arc(0.5, 0.5), Some(3.0));
+        assert_eq!(scan.min_range_in_arc(0.875, 0.2), Some(4.0));
+        assert_eq!(scan.min_range_in_arc(0.0, 0.1), Some(5.0));
+        // only NaN and too-close beams in the window
+        assert_eq!(scan.min_range_in_arc(0.25, 0.1), None);
+        assert_eq!(scan.min_range_in_arc(0.75, 0.1), None);
+        assert_eq!(scan.min_range_in_arc(-2.0, 0.5), None);
+    }
+
+    #[test]
+    fn test_min_range_in_arc_wrap_around() {
+        use std::f64::consts::PI;
+
+        let scan = Scan2D {
+            angle_min: -PI,
+            angle_max: PI - PI / 2.0,
+            angle_increment: PI / 2.0,
+            range_min: 0.1,
+            range_max: 10.0,
+            // -PI, -PI/2, 0, PI/2
+            ranges: vec![1.0, 2.0, 3.0, 4.0],
+            ..Default::default()
+        };
+        assert_eq!(scan.min_range_in_arc(PI, 0.1), Some(1.0));
+        assert_eq!(scan.min_range_in_arc(PI * 0.75, PI / 4.0 + 1e-6), Some(1.0));
+        assert_eq!(scan.min_range_in_arc(0.0, PI / 2.0 + 1e-6), Some(2.0));
+    }
+}
@@ -0,0 +1,16 @@
+use auto_impl::auto_impl;
+
+use crate::error::Error;
+
+/// A sensor which returns its latest measurement of type `Output`.
+///
+/// See [`CachedSensor`](crate::CachedSensor) to keep returning the last
+/// measurement while the sensor is temporarily unavailable.
+#[auto_impl(Box, Arc)]
+pub trait Sensor<Output>: Send + Sync {
+    /// Returns the latest measurement.
+    ///
+    /// This may block until a measurement is available, and returns an error
+    /// if the sensor can't be read.
+    fn read(&self) -> Result<Output, Error>;
+}
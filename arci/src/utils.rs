@@ -1,9 +1,9 @@
-use std::time::Duration;
+use std::{ops::RangeInclusive, time::Duration};
 
 use tokio::time::interval as AsyncInterval;
 use tracing::{debug, info};
 
-use crate::{Error, JointTrajectoryClient, TrajectoryPoint};
+use crate::{Error, JointPositionLimit, JointTrajectoryClient, TrajectoryPoint, WaitFuture};
 
 pub fn get_joint_index<J>(joint_trajectory_client: &J, joint_name: &str) -> Result<usize, Error>
 where
@@ -71,3 +71,559 @@ where
         .await?;
     Ok(stopped_position)
 }
+
+/// Error returned by [`validate_trajectory`].
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[non_exhaustive]
+pub enum TrajectoryError {
+    #[error(
+        "arci: Length mismatch at point {} (model = {}, input = {})",
+        point_index,
+        model,
+        input
+    )]
+    LengthMismatch {
+        point_index: usize,
+        model: usize,
+        input: usize,
+    },
+    #[error(
+        "arci: Position out of limit at point {}: joint={}, position={}, limit={:?}",
+        point_index,
+        joint_index,
+        position,
+        limit
+    )]
+    PositionOutOfLimit {
+        point_index: usize,
+        joint_index: usize,
+        position: f64,
+        limit: RangeInclusive<f64>,
+    },
+    #[error(
+        "arci: Velocity out of limit at point {}: joint={}, velocity={}, limit={}",
+        point_index,
+        joint_index,
+        velocity,
+        limit
+    )]
+    VelocityOutOfLimit {
+        point_index: usize,
+        joint_index: usize,
+        velocity: f64,
+        limit: f64,
+    },
+    #[error(
+        "arci: Acceleration out of limit at point {}: joint={}, acceleration={}, limit={}",
+        point_index,
+        joint_index,
+        acceleration,
+        limit
+    )]
+    AccelerationOutOfLimit {
+        point_index: usize,
+        joint_index: usize,
+        acceleration: f64,
+        limit: f64,
+    },
+    #[error(
+        "arci: Length mismatch of limits (model = {}, input = {})",
+        model,
+        input
+    )]
+    LimitsLengthMismatch { model: usize, input: usize },
+}
+
+impl From<TrajectoryError> for Error {
+    fn from(e: TrajectoryError) -> Self {
+        match e {
+            TrajectoryError::LimitsLengthMismatch { model, input } => {
+                Error::LengthMismatch { model, input }
+            }
+            e => Error::Other(e.into()),
+        }
+    }
+}
+
+/// Checks that every point of the trajectory respects the position limits and
+/// that the velocities implied by consecutive points (difference of positions
+/// divided by difference of `time_from_start`) respect the velocity limits.
+///
+/// Returns the first offending point and joint, or
+/// [`TrajectoryError::LimitsLengthMismatch`] (converted to
+/// `Error::LengthMismatch`) if the lengths of `position_limits` and
+/// `velocity_limits` are different.
+pub fn validate_trajectory(
+    trajectory: &[TrajectoryPoint],
+    position_limits: &[JointPositionLimit],
+    velocity_limits: &[f64],
+) -> Result<(), TrajectoryError> {
+    if position_limits.len() != velocity_limits.len() {
+        return Err(TrajectoryError::LimitsLengthMismatch {
+            model: position_limits.len(),
+            input: velocity_limits.len(),
+        });
+    }
+    let mut prev: Option<&TrajectoryPoint> = None;
+    for (point_index, point) in trajectory.iter().enumerate() {
+        if point.positions.len() != position_limits.len() {
+            return Err(TrajectoryError::LengthMismatch {
+                point_index,
+                model: position_limits.len(),
+                input: point.positions.len(),
+            });
+        }
+        for (joint_index, (&position, limit)) in
+            point.positions.iter().zip(position_limits).enumerate()
+        {
+            if let Some(limit) = limit.range() {
+                if !limit.contains(&position) {
+                    return Err(TrajectoryError::PositionOutOfLimit {
+                        point_index,
+                        joint_index,
+                        position,
+                        limit,
+                    });
+                }
+            }
+        }
+        if let Some(prev) = prev {
+            let dt = point
+                .time_from_start
+                .saturating_sub(prev.time_from_start)
+                .as_secs_f64();
+            for (joint_index, ((&p0, &p1), &limit)) in prev
+                .positions
+                .iter()
+                .zip(&point.positions)
+                .zip(velocity_limits)
+                .enumerate()
+            {
+                let diff = (p1 - p0).abs();
+                // Infinite if the point is reached without time.
+                let velocity = if diff == 0.0 { 0.0 } else { diff / dt };
+                if velocity > limit {
+                    return Err(TrajectoryError::VelocityOutOfLimit {
+                        point_index,
+                        joint_index,
+                        velocity,
+                        limit,
+                    });
+                }
+            }
+        }
+        prev = Some(point);
+    }
+    Ok(())
+}
+
+/// Checks the trajectory as [`validate_trajectory`], and also that the
+/// accelerations respect the acceleration limits.
+///
+/// Between consecutive points which both have explicit velocities, the
+/// acceleration is the difference of the velocities divided by the difference
+/// of `time_from_start`, and a violation is reported at the latter point. At a
+/// point without explicit velocities, the acceleration is the difference of
+/// the velocities implied by the segments before and after it, divided by the
+/// time between the midpoints of the segments. The position and velocity
+/// limits (and the lengths of the explicit velocities) are checked first, and
+/// then the first offending point and joint of the accelerations is returned.
+/// Returns [`TrajectoryError::LimitsLengthMismatch`] if the lengths of the
+/// limits are different.
+pub fn validate_trajectory_with_acceleration(
+    trajectory: &[TrajectoryPoint],
+    position_limits: &[JointPositionLimit],
+    velocity_limits: &[f64],
+    acceleration_limits: &[f64],
+) -> Result<(), TrajectoryError> {
+    if velocity_limits.len() != acceleration_limits.len() {
+        return Err(TrajectoryError::LimitsLengthMismatch {
+            model: velocity_limits.len(),
+            input: acceleration_limits.len(),
+        });
+    }
+    validate_trajectory(trajectory, position_limits, velocity_limits)?;
+    let dt = |i: usize| {
+        trajectory[i]
+            .time_from_start
+            .saturating_sub(trajectory[i - 1].time_from_start)
+            .as_secs_f64()
+    };
+    // Velocity of each joint implied by the segment ending at point `i`
+    let implied_velocity = |i: usize, joint_index: usize| {
+        let diff = trajectory[i].positions[joint_index] - trajectory[i - 1].positions[joint_index];
+        // validate_trajectory rejects motion without time.
+        if diff == 0.0 {
+            0.0
+        } else {
+            diff / dt(i)
+        }
+    };
+    for (point_index, point) in trajectory.iter().enumerate() {
+        if let Some(velocities) = &point.velocities {
+            if velocities.len() != acceleration_limits.len() {
+                return Err(TrajectoryError::LengthMismatch {
+                    point_index,
+                    model: acceleration_limits.len(),
+                    input: velocities.len(),
+                });
+            }
+        }
+    }
+    for (point_index, point) in trajectory.iter().enumerate().skip(1) {
+        for (joint_index, &limit) in acceleration_limits.iter().enumerate() {
+            let acceleration = match (&trajectory[point_index - 1].velocities, &point.velocities) {
+                (Some(v0), Some(v1)) if dt(point_index) > 0.0 => {
+                    (v1[joint_index] - v0[joint_index]) / dt(point_index)
+                }
+                (_, None) if point_index + 1 < trajectory.len() => {
+                    let dt = (dt(point_index) + dt(point_index + 1)) / 2.0;
+                    if dt <= 0.0 {
+                        continue;
+                    }
+                    (implied_velocity(point_index + 1, joint_index)
+                        - implied_velocity(point_index, joint_index))
+                        / dt
+                }
+                _ => continue,
+            };
+            if acceleration.abs() > limit {
+                return Err(TrajectoryError::AccelerationOutOfLimit {
+                    point_index,
+                    joint_index,
+                    acceleration: acceleration.abs(),
+                    limit,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Uniformly slows down the trajectory by the minimum factor that makes it
+/// respect the velocity and acceleration limits.
+///
+/// The velocities are those implied by consecutive points (as in
+/// [`validate_trajectory`]) and the explicit velocities of the points. The
+/// accelerations are computed from the changes of the explicit velocities
+/// between consecutive points which both have them, and from the changes of
+/// the implied velocities around each point without explicit velocities. Every
+/// `time_from_start` is multiplied by the factor and the velocities are
+/// divided by it. The trajectory is never sped up, so it is returned as is if
+/// it already respects the limits. Segments with motion but without time
+/// can't be made feasible by scaling and are ignored.
+///
+/// A joint is not limited if its limit is `f64::INFINITY`. Returns an error
+/// if a limit is not positive (including NaN), or if the scaled time is too
+/// long to be represented. Returns `Error::LengthMismatch` if the lengths of
+/// `velocity_limits` and `acceleration_limits` are different.
+pub fn scale_trajectory_to_limits(
+    trajectory: Vec<TrajectoryPoint>,
+    velocity_limits: &[f64],
+    acceleration_limits: &[f64],
+) -> Result<Vec<TrajectoryPoint>, Error> {
+    if velocity_limits.len() != acceleration_limits.len() {
+        return Err(Error::LengthMismatch {
+            model: velocity_limits.len(),
+            input: acceleration_limits.len(),
+        });
+    }
+    for (name, limits) in [
+        ("velocity", velocity_limits),
+        ("acceleration", acceleration_limits),
+    ] {
+        if let Some((joint_index, limit)) = limits
+            .iter()
+            .enumerate()
+            .find(|(_, &limit)| limit.is_nan() || limit <= 0.0)
+        {
+            return Err(Error::Other(anyhow::format_err!(
+                "The {name} limit of joint {joint_index} must be positive, but got {limit}"
+            )));
+        }
+    }
+    let mut scale: f64 = 1.0;
+    let mut update_scale = |value: f64, limit: f64, power: f64| {
+        if value > limit {
+            scale = scale.max((value / limit).powf(power));
+        }
+    };
+    let segment_dts = trajectory
+        .windows(2)
+        .map(|points| {
+            points[1]
+                .time_from_start
+                .saturating_sub(points[0].time_from_start)
+                .as_secs_f64()
+        })
+        .collect::<Vec<_>>();
+    // Velocities implied by each segment, or `None` if the segment has no time.
+    let segment_velocities = trajectory
+        .windows(2)
+        .zip(&segment_dts)
+        .map(|(points, &dt)| {
+            (dt > 0.0).then(|| {
+                points[0]
+                    .positions
+                    .iter()
+                    .zip(&points[1].positions)
+                    .map(|(p0, p1)| (p1 - p0) / dt)
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for velocities in segment_velocities.iter().flatten() {
+        for (v, &limit) in velocities.iter().zip(velocity_limits) {
+            update_scale(v.abs(), limit, 1.0);
+        }
+    }
+    for velocities in trajectory.iter().filter_map(|p| p.velocities.as_ref()) {
+        for (v, &limit) in velocities.iter().zip(velocity_limits) {
+            update_scale(v.abs(), limit, 1.0);
+        }
+    }
+    for (i, points) in trajectory.windows(2).enumerate() {
+        if let (Some(v0), Some(v1)) = (&points[0].velocities, &points[1].velocities) {
+            if segment_dts[i] > 0.0 {
+                for ((v0, v1), &limit) in v0.iter().zip(v1).zip(acceleration_limits) {
+                    update_scale((v1 - v0).abs() / segment_dts[i], limit, 0.5);
+                }
+            }
+        }
+    }
+    for i in 1..segment_velocities.len() {
+        // The change of the velocity at a point without explicit velocities,
+        // between the midpoints of the segments before and after it.
+        if let (Some(v0), Some(v1), None) = (
+            &segment_velocities[i - 1],
+            &segment_velocities[i],
+            &trajectory[i].velocities,
+        ) {
+            let dt = (segment_dts[i - 1] + segment_dts[i]) / 2.0;
+            for ((v0, v1), &limit) in v0.iter().zip(v1).zip(acceleration_limits) {
+                update_scale((v1 - v0).abs() / dt, limit, 0.5);
+            }
+        }
+    }
+
+    if scale <= 1.0 {
+        return Ok(trajectory);
+    }
+    trajectory
+        .into_iter()
+        .map(|point| {
+            let time_from_start =
+                Duration::try_from_secs_f64(point.time_from_start.as_secs_f64() * scale).map_err(
+                    |e| Error::Other(anyhow::format_err!("Failed to scale the trajectory: {e}")),
+                )?;
+            Ok(TrajectoryPoint {
+                positions: point.positions,
+                velocities: point
+                    .velocities
+                    .map(|velocities| velocities.iter().map(|v| v / scale).collect()),
+                time_from_start,
+            })
+        })
+        .collect()
+}
+
+/// Inserts points at every `dt` using cubic Hermite interpolation between
+/// consecutive points, starting from `start` at rest.
+///
+/// The original points are kept as is, and the velocities of the points
+/// without explicit velocities are treated as zero. Points whose number of
+/// positions differs from the previous one are passed through without
+/// interpolation. The trajectory is returned as is if `dt` is zero.
+pub fn resample_trajectory(
+    start: &[f64],
+    trajectory: Vec<TrajectoryPoint>,
+    dt: Duration,
+) -> Vec<TrajectoryPoint> {
+    if dt.is_zero() {
+        return trajectory;
+    }
+    let mut resampled = Vec::with_capacity(trajectory.len());
+    let mut prev = TrajectoryPoint {
+        positions: start.to_vec(),
+        velocities: None,
+        time_from_start: Duration::ZERO,
+    };
+    for point in trajectory {
+        if point.positions.len() != prev.positions.len() {
+            // Leave malformed points to the receiver to reject.
+            resampled.push(point.clone());
+            prev = point;
+            continue;
+        }
+        let t0 = prev.time_from_start.as_secs_f64();
+        let h = point.time_from_start.as_secs_f64() - t0;
+        let zeros = vec![0.0; point.positions.len()];
+        let v0 = prev.velocities.as_deref().unwrap_or(&zeros);
+        let v1 = point.velocities.as_deref().unwrap_or(&zeros);
+        let mut t = prev.time_from_start + dt;
+        while t < point.time_from_start {
+            let s = (t.as_secs_f64() - t0) / h;
+            let (s2, s3) = (s * s, s * s * s);
+            let positions = (0..point.positions.len())
+                .map(|i| {
+                    (2.0 * s3 - 3.0 * s2 + 1.0) * prev.positions[i]
+                        + (s3 - 2.0 * s2 + s) * h * v0[i]
+                        + (-2.0 * s3 + 3.0 * s2) * point.positions[i]
+                        + (s3 - s2) * h * v1[i]
+                })
+                .collect();
+            let velocities = (0..point.positions.len())
+                .map(|i| {
+                    (6.0 * s2 - 6.0 * s) / h * (prev.positions[i] - point.positions[i])
+                        + (3.0 * s2 - 4.0 * s + 1.0) * v0[i]
+                        + (3.0 * s2 - 2.0 * s) * v1[i]
+                })
+                .collect();
+            resampled.push(TrajectoryPoint {
+                positions,
+                velocities: Some(velocities),
+                time_from_start: t,
+            });
+            t += dt;
+        }
+        resampled.push(point.clone());
+        prev = point;
+    }
+    resampled
+}
+
+/// Converts a path of joint positions (e.g., solved by IK for each pose of a
+/// Cartesian path) into a trajectory timed by `max_joint_velocity`.
+///
+/// The duration of each segment is the largest joint move in it divided by
+/// `max_joint_velocity`, and the first point is at time zero, so the path
+/// should start from the current joint positions. Points which do not move
+/// from the previous one are skipped. The velocity at each intermediate point
+/// is the average of the velocities of the segments before and after it, and
+/// the velocities at both ends are zero.
+///
+/// # Panics
+///
+/// Panics if `max_joint_velocity` is not positive.
+#[track_caller]
+pub fn joint_path_to_trajectory(
+    path: &[Vec<f64>],
+    max_joint_velocity: f64,
+) -> Vec<TrajectoryPoint> {
+    assert!(
+        max_joint_velocity > 0.0,
+        "max_joint_velocity must be positive: {max_joint_velocity}"
+    );
+    let mut points: Vec<(&Vec<f64>, f64)> = Vec::with_capacity(path.len());
+    for positions in path {
+        match points.last() {
+            None => points.push((positions, 0.0)),
+            Some(&(prev, time)) => {
+                let max_move = prev
+                    .iter()
+                    .zip(positions)
+                    .map(|(p0, p1)| (p1 - p0).abs())
+                    .fold(0.0, f64::max);
+                if max_move > 0.0 {
+                    points.push((positions, time + max_move / max_joint_velocity));
+                }
+            }
+        }
+    }
+    let segment_velocities = points
+        .windows(2)
+        .map(|points| {
+            let dt = points[1].1 - points[0].1;
+            points[0]
+                .0
+                .iter()
+                .zip(points[1].0)
+                .map(|(p0, p1)| (p1 - p0) / dt)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let last = points.len().saturating_sub(1);
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, &(positions, time))| {
+            let velocities = if i == 0 || i == last {
+                vec![0.0; positions.len()]
+            } else {
+                segment_velocities[i - 1]
+                    .iter()
+                    .zip(&segment_velocities[i])
+                    .map(|(v0, v1)| (v0 + v1) / 2.0)
+                    .collect()
+            };
+            TrajectoryPoint {
+                positions: positions.clone(),
+                velocities: Some(velocities),
+                time_from_start: Duration::from_secs_f64(time),
+            }
+        })
+        .collect()
+}
+
+/// Builds a two-phase trajectory: moves to `approach` in `approach_duration`,
+/// then to `goal` in `settle_duration`.
+///
+/// This is useful for contact tasks, moving fast to a pre-contact pose and then
+/// slowly to the final pose. The velocity at `approach` is the average velocity
+/// of the settle phase, so that the velocity is continuous at the junction and
+/// the settle phase moves monotonically. The velocity at `goal` is zero.
+pub fn two_phase_trajectory(
+    approach: Vec<f64>,
+    goal: Vec<f64>,
+    approach_duration: Duration,
+    settle_duration: Duration,
+) -> Result<Vec<TrajectoryPoint>, Error> {
+    if approach.len() != goal.len() {
+        return Err(Error::LengthMismatch {
+            model: approach.len(),
+            input: goal.len(),
+        });
+    }
+    let settle_secs = settle_duration.as_secs_f64();
+    let junction_velocities = approach
+        .iter()
+        .zip(&goal)
+        .map(|(a, g)| {
+            if settle_secs > 0.0 {
+                (g - a) / settle_secs
+            } else {
+                0.0
+            }
+        })
+        .collect();
+    let dof = goal.len();
+    Ok(vec![
+        TrajectoryPoint {
+            positions: approach,
+            velocities: Some(junction_velocities),
+            time_from_start: approach_duration,
+        },
+        TrajectoryPoint {
+            positions: goal,
+            velocities: Some(vec![0.0; dof]),
+            time_from_start: approach_duration + settle_duration,
+        },
+    ])
+}
+
+/// Sends the two-phase trajectory built by [`two_phase_trajectory`] and
+/// returns a future that waits until complete.
+pub fn send_two_phase<J>(
+    joint_trajectory_client: &J,
+    approach: Vec<f64>,
+    goal: Vec<f64>,
+    approach_duration: Duration,
+    settle_duration: Duration,
+) -> Result<WaitFuture, Error>
+where
+    J: JointTrajectoryClient,
+{
+    let trajectory = two_phase_trajectory(approach, goal, approach_duration, settle_duration)?;
+    joint_trajectory_client.send_joint_trajectory(trajectory)
+}
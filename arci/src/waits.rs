@@ -51,6 +51,32 @@ impl WaitFuture {
         })
     }
 
+    /// Waits until all of the `futures` are complete.
+    ///
+    /// Unlike [`from_stream`](Self::from_stream), this waits for all futures
+    /// even if some of them fail, and then returns the first error (in the
+    /// order of `futures`), if any.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), arci::Error> {
+    /// use arci::WaitFuture;
+    ///
+    /// WaitFuture::join_all(vec![WaitFuture::ready(), WaitFuture::ready()]).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn join_all(futures: Vec<WaitFuture>) -> Self {
+        Self::new(async move {
+            future::join_all(futures)
+                .await
+                .into_iter()
+                .collect::<Result<(), Error>>()
+        })
+    }
+
     /// Creates a new `WaitFuture` which immediately complete.
     pub fn ready() -> Self {
         Self::new(future::ready(Ok(())))
@@ -77,6 +103,51 @@ impl fmt::Debug for WaitFuture {
     }
 }
 
+/// Handle to cancel an operation, such as the motion waited by a [`WaitFuture`].
+///
+/// The operation is canceled when [`cancel`](Self::cancel) is called or the
+/// handle is dropped. Call [`detach`](Self::detach) to drop the handle
+/// without canceling.
+#[must_use = "The operation is canceled when the handle is dropped"]
+pub struct CancelHandle {
+    cancel: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl CancelHandle {
+    /// Creates a new `CancelHandle` which calls `cancel` to cancel the operation.
+    pub fn new(cancel: impl FnOnce() + Send + 'static) -> Self {
+        Self {
+            cancel: Some(Box::new(cancel)),
+        }
+    }
+
+    /// Cancels the operation.
+    pub fn cancel(mut self) {
+        if let Some(cancel) = self.cancel.take() {
+            cancel();
+        }
+    }
+
+    /// Drops the handle without canceling the operation.
+    pub fn detach(mut self) {
+        self.cancel = None;
+    }
+}
+
+impl Drop for CancelHandle {
+    fn drop(&mut self) {
+        if let Some(cancel) = self.cancel.take() {
+            cancel();
+        }
+    }
+}
+
+impl fmt::Debug for CancelHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancelHandle").finish()
+    }
+}
+
 #[async_trait]
 #[auto_impl(Box, Arc)]
 pub trait CompleteCondition: Send + Sync {
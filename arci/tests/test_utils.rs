@@ -1,8 +1,13 @@
 use std::time::Duration;
 
 use arci::{
-    utils::{get_joint_index, move_joint_until_stop},
-    DummyJointTrajectoryClient, Error, JointTrajectoryClient, TrajectoryPoint, WaitFuture,
+    utils::{
+        get_joint_index, joint_path_to_trajectory, move_joint_until_stop, resample_trajectory,
+        scale_trajectory_to_limits, send_two_phase, two_phase_trajectory, validate_trajectory,
+        validate_trajectory_with_acceleration, TrajectoryError,
+    },
+    DummyJointTrajectoryClient, Error, JointPositionLimit, JointTrajectoryClient, TrajectoryPoint,
+    WaitFuture,
 };
 use assert_approx_eq::assert_approx_eq;
 
@@ -99,3 +104,378 @@ async fn test_move_joint_until_stop() {
     assert!(stopped_position.is_ok());
     assert_approx_eq!(stopped_position.unwrap(), 1.0);
 }
+
+#[test]
+fn test_validate_trajectory() {
+    let position_limits = [
+        JointPositionLimit::new(-1.0, 1.0),
+        JointPositionLimit::none(),
+    ];
+    let velocity_limits = [1.0, 2.0];
+    let trajectory = vec![
+        TrajectoryPoint::new(vec![0.0, 0.0], Duration::from_secs(1)),
+        TrajectoryPoint::new(vec![1.0, 5.0], Duration::from_secs(4)),
+        TrajectoryPoint::new(vec![0.5, 5.0], Duration::from_secs(5)),
+    ];
+    validate_trajectory(&trajectory, &position_limits, &velocity_limits).unwrap();
+    validate_trajectory(&[], &position_limits, &velocity_limits).unwrap();
+
+    let mut out_of_limit = trajectory.clone();
+    out_of_limit[2].positions[0] = 1.5;
+    assert_eq!(
+        validate_trajectory(&out_of_limit, &position_limits, &velocity_limits),
+        Err(TrajectoryError::PositionOutOfLimit {
+            point_index: 2,
+            joint_index: 0,
+            position: 1.5,
+            limit: -1.0..=1.0
+        })
+    );
+
+    let mut too_fast = trajectory.clone();
+    too_fast[2].positions[1] = 2.0;
+    assert_eq!(
+        validate_trajectory(&too_fast, &position_limits, &velocity_limits),
+        Err(TrajectoryError::VelocityOutOfLimit {
+            point_index: 2,
+            joint_index: 1,
+            velocity: 3.0,
+            limit: 2.0
+        })
+    );
+
+    let mut no_time = trajectory.clone();
+    no_time[1].time_from_start = Duration::from_secs(1);
+    assert!(matches!(
+        validate_trajectory(&no_time, &position_limits, &velocity_limits),
+        Err(TrajectoryError::VelocityOutOfLimit {
+            point_index: 1,
+            joint_index: 0,
+            ..
+        })
+    ));
+
+    let mut mismatch = trajectory;
+    mismatch[1].positions.push(0.0);
+    assert_eq!(
+        validate_trajectory(&mismatch, &position_limits, &velocity_limits),
+        Err(TrajectoryError::LengthMismatch {
+            point_index: 1,
+            model: 2,
+            input: 3
+        })
+    );
+
+    let err = validate_trajectory(&mismatch, &position_limits, &velocity_limits[..1]).unwrap_err();
+    assert_eq!(
+        err,
+        TrajectoryError::LimitsLengthMismatch { model: 2, input: 1 }
+    );
+    assert!(matches!(
+        Error::from(err),
+        Error::LengthMismatch { model: 2, input: 1 }
+    ));
+}
+
+#[test]
+fn test_validate_trajectory_with_acceleration() {
+    let position_limits = [JointPositionLimit::none(), JointPositionLimit::none()];
+    let velocity_limits = [10.0, 10.0];
+    let trajectory = vec![
+        TrajectoryPoint::new(vec![0.0, 0.0], Duration::from_secs(1)),
+        TrajectoryPoint::new(vec![1.0, 5.0], Duration::from_secs(4)),
+        TrajectoryPoint::new(vec![0.5, 5.0], Duration::from_secs(5)),
+    ];
+    validate_trajectory_with_acceleration(
+        &trajectory,
+        &position_limits,
+        &velocity_limits,
+        &[1.0, 1.0],
+    )
+    .unwrap();
+    // the velocity of joint 1 changes from 5/3 to 0 in 2 seconds around point 1
+    match validate_trajectory_with_acceleration(
+        &trajectory,
+        &position_limits,
+        &velocity_limits,
+        &[1.0, 0.5],
+    ) {
+        Err(TrajectoryError::AccelerationOutOfLimit {
+            point_index: 1,
+            joint_index: 1,
+            acceleration,
+            limit,
+        }) => {
+            assert_approx_eq!(acceleration, 5.0 / 6.0);
+            assert_approx_eq!(limit, 0.5);
+        }
+        e => panic!("{e:?}"),
+    }
+
+    // explicit velocities
+    let trajectory = vec![
+        TrajectoryPoint {
+            positions: vec![0.0, 0.0],
+            velocities: Some(vec![0.0, 0.0]),
+            time_from_start: Duration::from_secs(0),
+        },
+        TrajectoryPoint {
+            positions: vec![1.0, 0.0],
+            velocities: Some(vec![2.0, 0.0]),
+            time_from_start: Duration::from_secs(1),
+        },
+    ];
+    assert_eq!(
+        validate_trajectory_with_acceleration(
+            &trajectory,
+            &position_limits,
+            &velocity_limits,
+            &[1.0, 1.0],
+        ),
+        Err(TrajectoryError::AccelerationOutOfLimit {
+            point_index: 1,
+            joint_index: 0,
+            acceleration: 2.0,
+            limit: 1.0
+        })
+    );
+    // velocity limits are checked first
+    assert!(matches!(
+        validate_trajectory_with_acceleration(
+            &trajectory,
+            &position_limits,
+            &[0.5, 0.5],
+            &[1.0, 1.0]
+        ),
+        Err(TrajectoryError::VelocityOutOfLimit { .. })
+    ));
+    assert_eq!(
+        validate_trajectory_with_acceleration(
+            &trajectory,
+            &position_limits,
+            &velocity_limits,
+            &[1.0]
+        ),
+        Err(TrajectoryError::LimitsLengthMismatch { model: 2, input: 1 })
+    );
+}
+
+#[test]
+fn test_scale_trajectory_to_limits() {
+    let trajectory = vec![
+        TrajectoryPoint::new(vec![0.0, 0.0], Duration::from_secs(1)),
+        TrajectoryPoint::new(vec![2.0, 1.0], Duration::from_secs(2)),
+        TrajectoryPoint::new(vec![2.0, 1.0], Duration::from_secs(3)),
+    ];
+
+    // feasible
+    let scaled = scale_trajectory_to_limits(trajectory.clone(), &[2.0, 2.0], &[2.0, 2.0]).unwrap();
+    for (scaled, point) in scaled.iter().zip(&trajectory) {
+        assert_eq!(scaled.positions, point.positions);
+        assert_eq!(scaled.time_from_start, point.time_from_start);
+    }
+
+    // limited by velocity
+    let scaled =
+        scale_trajectory_to_limits(trajectory.clone(), &[1.0, 2.0], &[10.0, 10.0]).unwrap();
+    assert_eq!(
+        scaled.iter().map(|p| p.time_from_start).collect::<Vec<_>>(),
+        vec![
+            Duration::from_secs(2),
+            Duration::from_secs(4),
+            Duration::from_secs(6)
+        ]
+    );
+    assert_eq!(scaled[1].positions, vec![2.0, 1.0]);
+    validate_trajectory(
+        &scaled,
+        &[JointPositionLimit::none(), JointPositionLimit::none()],
+        &[1.0, 2.0],
+    )
+    .unwrap();
+
+    // limited by acceleration: the velocity of joint 0 changes from 2.0 to 0.0 in 1 second
+    let scaled =
+        scale_trajectory_to_limits(trajectory.clone(), &[10.0, 10.0], &[0.5, 10.0]).unwrap();
+    assert_eq!(scaled[2].time_from_start, Duration::from_secs(6));
+
+    // explicit velocities are scaled
+    let trajectory = vec![
+        TrajectoryPoint {
+            positions: vec![0.0],
+            velocities: Some(vec![0.0]),
+            time_from_start: Duration::from_secs(1),
+        },
+        TrajectoryPoint {
+            positions: vec![0.5],
+            velocities: Some(vec![1.0]),
+            time_from_start: Duration::from_secs(2),
+        },
+    ];
+    let scaled = scale_trajectory_to_limits(trajectory, &[10.0], &[0.25]).unwrap();
+    assert_eq!(scaled[0].time_from_start, Duration::from_secs(2));
+    assert_eq!(scaled[1].time_from_start, Duration::from_secs(4));
+    assert_approx_eq!(scaled[1].velocities.as_ref().unwrap()[0], 0.5);
+}
+
+#[test]
+fn test_scale_trajectory_to_limits_invalid_limits() {
+    let trajectory = vec![
+        TrajectoryPoint::new(vec![0.0, 0.0], Duration::from_secs(1)),
+        TrajectoryPoint::new(vec![2.0, 1.0], Duration::from_secs(2)),
+    ];
+    for limit in [0.0, -1.0, f64::NAN] {
+        assert!(
+            scale_trajectory_to_limits(trajectory.clone(), &[limit, 1.0], &[1.0, 1.0]).is_err(),
+            "{limit}"
+        );
+        assert!(
+            scale_trajectory_to_limits(trajectory.clone(), &[1.0, 1.0], &[1.0, limit]).is_err(),
+            "{limit}"
+        );
+    }
+    // unlimited joints
+    let scaled = scale_trajectory_to_limits(
+        trajectory.clone(),
+        &[f64::INFINITY, 0.5],
+        &[f64::INFINITY, f64::INFINITY],
+    )
+    .unwrap();
+    assert_eq!(scaled[1].time_from_start, Duration::from_secs(4));
+    // too slow to be represented
+    assert!(
+        scale_trajectory_to_limits(trajectory.clone(), &[f64::MIN_POSITIVE, 1.0], &[1.0, 1.0])
+            .is_err()
+    );
+    assert!(matches!(
+        scale_trajectory_to_limits(trajectory, &[1.0, 1.0], &[1.0]),
+        Err(Error::LengthMismatch { model: 2, input: 1 })
+    ));
+}
+
+#[test]
+fn test_resample_trajectory() {
+    // one point: interpolated from the start at rest
+    let trajectory = vec![TrajectoryPoint::new(vec![1.0], Duration::from_secs(1))];
+    let resampled = resample_trajectory(&[0.0], trajectory.clone(), Duration::from_millis(250));
+    assert_eq!(
+        resampled
+            .iter()
+            .map(|p| p.time_from_start)
+            .collect::<Vec<_>>(),
+        vec![
+            Duration::from_millis(250),
+            Duration::from_millis(500),
+            Duration::from_millis(750),
+            Duration::from_secs(1)
+        ]
+    );
+    assert_approx_eq!(resampled[0].positions[0], 0.15625);
+    assert_approx_eq!(resampled[1].positions[0], 0.5);
+    assert_approx_eq!(resampled[1].velocities.as_ref().unwrap()[0], 1.5);
+    // the end point is kept as is
+    assert_eq!(resampled[3].positions, trajectory[0].positions);
+    assert_eq!(resampled[3].velocities, None);
+
+    // two points: each segment is resampled, and the time is monotonic even
+    // if dt does not divide the segments
+    let trajectory = vec![
+        TrajectoryPoint::new(vec![1.0, 2.0], Duration::from_secs(1)),
+        TrajectoryPoint::new(vec![1.0, 0.0], Duration::from_secs(2)),
+    ];
+    let resampled =
+        resample_trajectory(&[0.0, 2.0], trajectory.clone(), Duration::from_millis(300));
+    assert_eq!(resampled.len(), 8);
+    assert_eq!(resampled[3].positions, trajectory[0].positions);
+    assert_eq!(resampled[3].time_from_start, trajectory[0].time_from_start);
+    assert_eq!(resampled[7].positions, trajectory[1].positions);
+    assert_eq!(resampled[7].time_from_start, trajectory[1].time_from_start);
+    for points in resampled.windows(2) {
+        assert!(points[0].time_from_start < points[1].time_from_start);
+    }
+    for point in &resampled[4..7] {
+        assert_approx_eq!(point.positions[0], 1.0);
+        assert!(point.positions[1] < 2.0 && point.positions[1] > 0.0);
+    }
+
+    // nothing to resample
+    assert!(resample_trajectory(&[0.0], vec![], Duration::from_millis(100)).is_empty());
+    assert_eq!(
+        resample_trajectory(&[0.0], trajectory, Duration::ZERO).len(),
+        2
+    );
+}
+
+#[tokio::test]
+async fn test_two_phase_trajectory() {
+    let trajectory = two_phase_trajectory(
+        vec![1.0, 2.0],
+        vec![1.5, 1.0],
+        Duration::from_secs(1),
+        Duration::from_secs(2),
+    )
+    .unwrap();
+    assert_eq!(trajectory.len(), 2);
+    assert_eq!(trajectory[0].positions, vec![1.0, 2.0]);
+    assert_eq!(trajectory[0].velocities, Some(vec![0.25, -0.5]));
+    assert_eq!(trajectory[0].time_from_start, Duration::from_secs(1));
+    assert_eq!(trajectory[1].positions, vec![1.5, 1.0]);
+    assert_eq!(trajectory[1].velocities, Some(vec![0.0, 0.0]));
+    assert_eq!(trajectory[1].time_from_start, Duration::from_secs(3));
+
+    assert!(matches!(
+        two_phase_trajectory(vec![1.0], vec![], Duration::ZERO, Duration::ZERO),
+        Err(Error::LengthMismatch { model: 1, input: 0 })
+    ));
+
+    let client = DummyJointTrajectoryClient::new(vec!["a".to_owned()]);
+    send_two_phase(
+        &client,
+        vec![1.0],
+        vec![2.0],
+        Duration::from_millis(1),
+        Duration::from_millis(1),
+    )
+    .unwrap()
+    .await
+    .unwrap();
+    assert_eq!(client.current_joint_positions().unwrap(), vec![2.0]);
+}
+
+#[test]
+fn test_joint_path_to_trajectory() {
+    let path = vec![
+        vec![0.0, 0.0],
+        vec![1.0, 0.5],
+        vec![1.0, 0.5],
+        vec![1.0, 2.5],
+    ];
+    let trajectory = joint_path_to_trajectory(&path, 2.0);
+    // the duplicated point is skipped
+    assert_eq!(trajectory.len(), 3);
+    assert_eq!(
+        trajectory
+            .iter()
+            .map(|p| p.time_from_start)
+            .collect::<Vec<_>>(),
+        vec![
+            Duration::from_secs(0),
+            Duration::from_millis(500),
+            Duration::from_millis(1500)
+        ]
+    );
+    assert_eq!(trajectory[2].positions, vec![1.0, 2.5]);
+    assert_eq!(trajectory[0].velocities, Some(vec![0.0, 0.0]));
+    let velocities = trajectory[1].velocities.as_ref().unwrap();
+    assert_approx_eq!(velocities[0], 1.0);
+    assert_approx_eq!(velocities[1], 1.5);
+    assert_eq!(trajectory[2].velocities, Some(vec![0.0, 0.0]));
+    validate_trajectory(
+        &trajectory,
+        &[JointPositionLimit::none(), JointPositionLimit::none()],
+        &[2.0, 2.0],
+    )
+    .unwrap();
+
+    assert!(joint_path_to_trajectory(&[], 1.0).is_empty());
+}
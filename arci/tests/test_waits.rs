@@ -1,6 +1,14 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
 use arci::{
-    CompleteCondition, DummyJointTrajectoryClient, EachJointDiffCondition, JointTrajectoryClient,
-    TotalJointDiffCondition,
+    CancelHandle, CompleteCondition, DummyJointTrajectoryClient, EachJointDiffCondition, Error,
+    JointTrajectoryClient, TotalJointDiffCondition, WaitFuture,
 };
 use assert_approx_eq::assert_approx_eq;
 
@@ -143,3 +151,66 @@ fn test_each_condition_clone() {
     assert_approx_eq!(c1.allowable_errors[1], 0.1);
     assert_approx_eq!(c1.timeout_sec, 0.1);
 }
+
+#[tokio::test]
+async fn test_join_all() {
+    WaitFuture::join_all(vec![]).await.unwrap();
+
+    let done = Arc::new(AtomicBool::new(false));
+    let done_clone = done.clone();
+    let slow = WaitFuture::new(async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        done_clone.store(true, Ordering::Relaxed);
+        Ok(())
+    });
+    WaitFuture::join_all(vec![WaitFuture::ready(), slow])
+        .await
+        .unwrap();
+    assert!(done.load(Ordering::Relaxed));
+}
+
+#[tokio::test]
+async fn test_join_all_err() {
+    let done = Arc::new(AtomicBool::new(false));
+    let done_clone = done.clone();
+    let slow = WaitFuture::new(async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        done_clone.store(true, Ordering::Relaxed);
+        Ok(())
+    });
+    let err = |message: &str| {
+        let message = message.to_owned();
+        WaitFuture::new(async move { Err(Error::Canceled { message }) })
+    };
+    let result = WaitFuture::join_all(vec![err("first"), slow, err("second")]).await;
+    // waits for all futures and returns the first error
+    assert!(done.load(Ordering::Relaxed));
+    assert!(matches!(result, Err(Error::Canceled { message }) if message == "first"));
+}
+
+#[test]
+fn test_cancel_handle() {
+    let canceled = Arc::new(AtomicBool::new(false));
+    let handle = {
+        let canceled = canceled.clone();
+        CancelHandle::new(move || canceled.store(true, Ordering::Relaxed))
+    };
+    handle.cancel();
+    assert!(canceled.load(Ordering::Relaxed));
+
+    let canceled = Arc::new(AtomicBool::new(false));
+    let handle = {
+        let canceled = canceled.clone();
+        CancelHandle::new(move || canceled.store(true, Ordering::Relaxed))
+    };
+    drop(handle);
+    assert!(canceled.load(Ordering::Relaxed));
+
+    let canceled = Arc::new(AtomicBool::new(false));
+    let handle = {
+        let canceled = canceled.clone();
+        CancelHandle::new(move || canceled.store(true, Ordering::Relaxed))
+    };
+    handle.detach();
+    assert!(!canceled.load(Ordering::Relaxed));
+}
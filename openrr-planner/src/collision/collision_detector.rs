@@ -374,6 +374,31 @@ where
     }
 }
 
+/// Returns whether the robot is in self collision at the given joint positions
+///
+/// This sets `positions` to `robot`, updates the transforms, and checks the
+/// `self_collision_pairs`, without setting up a planner.
+///
+/// robot: robot model
+/// collision_detector: collision detector created from the same robot
+/// self_collision_pairs: pairs of the names of the link(joint)
+/// positions: joint positions of `robot`
+pub fn is_self_collision<T>(
+    robot: &k::Chain<T>,
+    collision_detector: &CollisionDetector<T>,
+    self_collision_pairs: &[(String, String)],
+    positions: &[T],
+) -> Result<bool>
+where
+    T: RealField + Copy + k::SubsetOf<f64>,
+{
+    robot.set_joint_positions(positions)?;
+    Ok(collision_detector
+        .detect_self(robot, self_collision_pairs)
+        .next()
+        .is_some())
+}
+
 #[cfg(test)]
 mod tests {
     use na::{Isometry3, Vector3};
@@ -448,6 +473,20 @@ mod tests {
             .collect();
         assert_eq!(result, correct_collisions.to_vec());
     }
+
+    #[test]
+    fn test_is_self_collision() {
+        let urdf_robot = urdf_rs::read_file("sample.urdf").unwrap();
+        let robot = k::Chain::<f32>::from(&urdf_robot);
+        let detector = CollisionDetector::from_urdf_robot(&urdf_robot, 0.01);
+        let pairs = parse_colon_separated_pairs(&["root:l_shoulder_roll".to_owned()]).unwrap();
+
+        assert!(!is_self_collision(&robot, &detector, &pairs, &[0.0; 16]).unwrap());
+        let mut angles = [0.0; 16];
+        angles[0] = -1.57;
+        assert!(is_self_collision(&robot, &detector, &pairs, &angles).unwrap());
+        assert!(is_self_collision(&robot, &detector, &pairs, &[0.0; 3]).is_err());
+    }
 }
 
 /// Convert urdf object into openrr_planner/ncollide3d object
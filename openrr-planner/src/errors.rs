@@ -70,6 +70,8 @@ pub enum Error {
     MeshError(String),
     #[error("Reference robot is not set to {}", .0)]
     ReferenceRobot(String),
+    #[error("Waypoint {} is unreachable: {:?}", index, source)]
+    UnreachableWaypoint { index: usize, source: k::Error },
 }
 
 /// Result for `openrr_planner`
@@ -0,0 +1,330 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use k::{nalgebra as na, InverseKinematicsSolver, RealField, SubsetOf};
+
+use crate::ik::{ik_solve_span, masked_pose_diff};
+
+/// Cache of the end transforms of a serial chain, keyed by the quantized
+/// joint positions
+///
+/// The cache is thread-safe, so it can be shared by the parallel workers of
+/// a reachability scan. Each rayon worker thread has its own shard of the
+/// cache, so the workers don't contend for a lock, and the threads outside
+/// the rayon pool share one shard. Joint positions closer than `resolution`
+/// may share an entry, so the returned transform is approximate up to the
+/// resolution.
+#[derive(Debug)]
+pub struct ForwardKinematicsCache<T>
+where
+    T: RealField,
+{
+    resolution: f64,
+    capacity: usize,
+    shards: Vec<Mutex<CacheShard<T>>>,
+}
+
+#[derive(Debug)]
+struct CacheShard<T>
+where
+    T: RealField,
+{
+    transforms: HashMap<Vec<i64>, na::Isometry3<T>>,
+    hits: usize,
+}
+
+impl<T> ForwardKinematicsCache<T>
+where
+    T: RealField + Copy + SubsetOf<f64>,
+{
+    /// Create a cache which holds up to `capacity` entries for each thread
+    ///
+    /// When the shard of a thread is full, new transforms are computed but not
+    /// stored.
+    #[track_caller]
+    pub fn new(resolution: f64, capacity: usize) -> Self {
+        assert!(
+            resolution > 0.0,
+            "resolution must be positive, but got {resolution}"
+        );
+        // One shard for each worker of the global rayon pool, and one for the
+        // other threads
+        let shards = (0..rayon::current_num_threads() + 1)
+            .map(|_| {
+                Mutex::new(CacheShard {
+                    transforms: HashMap::new(),
+                    hits: 0,
+                })
+            })
+            .collect();
+        Self {
+            resolution,
+            capacity,
+            shards,
+        }
+    }
+
+    /// Return the shard of the current thread
+    fn shard(&self) -> &Mutex<CacheShard<T>> {
+        let index = rayon::current_thread_index().map_or(0, |i| i + 1);
+        &self.shards[index % self.shards.len()]
+    }
+
+    /// Return the end transform of `arm` at the current joint positions,
+    /// computing it only if not cached
+    pub fn end_transform(&self, arm: &k::SerialChain<T>) -> na::Isometry3<T> {
+        self.lookup(arm).0
+    }
+
+    /// Return the end transform and `true` if it was computed now (i.e., it
+    /// is exact), or `false` if it was served from the cache
+    fn lookup(&self, arm: &k::SerialChain<T>) -> (na::Isometry3<T>, bool) {
+        let key = arm
+            .joint_positions()
+            .into_iter()
+            .map(|position| (na::convert::<T, f64>(position) / self.resolution).round() as i64)
+            .collect::<Vec<_>>();
+        let mut shard = self.shard().lock().unwrap();
+        if let Some(transform) = shard.transforms.get(&key) {
+            let transform = *transform;
+            shard.hits += 1;
+            return (transform, false);
+        }
+        let transform = arm.end_transform();
+        if shard.transforms.len() < self.capacity {
+            shard.transforms.insert(key, transform);
+        }
+        (transform, true)
+    }
+
+    /// Return the number of cached transforms of all threads
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.lock().unwrap().transforms.len())
+            .sum()
+    }
+
+    /// Return `true` if no transform is cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the number of lookups served from the cache
+    pub fn hits(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.lock().unwrap().hits)
+            .sum()
+    }
+
+    /// Remove all cached transforms
+    pub fn clear(&self) {
+        for shard in &self.shards {
+            let mut shard = shard.lock().unwrap();
+            shard.transforms.clear();
+            shard.hits = 0;
+        }
+    }
+}
+
+/// IK solver which skips solving if the current joint positions already
+/// reach the target, using the cached forward kinematics to check it
+///
+/// This is opt-in: wrap the solver passed to e.g.
+/// [`get_reachable_region`](crate::get_reachable_region) to share `cache`
+/// among the workers. Since the scan resets the arm to the same initial joint
+/// positions for every point, their end transform is computed only once for
+/// each worker thread, without locking across the workers.
+/// Only this check before solving uses the cache; the iterations of the
+/// inner solver compute the forward kinematics as usual.
+///
+/// Since a cached transform is approximate up to the resolution of the
+/// cache, it is only used to find targets which are obviously not reached
+/// yet. If the cached transform reaches the target, it is verified with the
+/// exact forward kinematics before returning `Ok` without solving.
+#[derive(Debug)]
+pub struct FkCachedIkSolver<T, I>
+where
+    T: RealField,
+    I: InverseKinematicsSolver<T>,
+{
+    /// The IK solver used if the target is not reached yet
+    pub solver: I,
+    /// The shared cache
+    pub cache: Arc<ForwardKinematicsCache<T>>,
+    /// The distance (of both position and rotation) regarded as reached
+    pub allowable_target_distance: T,
+}
+
+impl<T, I> FkCachedIkSolver<T, I>
+where
+    T: RealField,
+    I: InverseKinematicsSolver<T>,
+{
+    /// Create a solver which shares `cache`
+    pub fn new(
+        solver: I,
+        cache: Arc<ForwardKinematicsCache<T>>,
+        allowable_target_distance: T,
+    ) -> Self {
+        Self {
+            solver,
+            cache,
+            allowable_target_distance,
+        }
+    }
+}
+
+impl<T, I> InverseKinematicsSolver<T> for FkCachedIkSolver<T, I>
+where
+    T: RealField + Copy + SubsetOf<f64>,
+    I: InverseKinematicsSolver<T>,
+{
+    fn solve_with_constraints(
+        &self,
+        arm: &k::SerialChain<T>,
+        target_pose: &na::Isometry3<T>,
+        constraints: &k::Constraints,
+    ) -> Result<(), k::Error> {
+        let _span = ik_solve_span("FkCachedIkSolver", target_pose).entered();
+        let is_reached = |current: &na::Isometry3<T>| {
+            let (position_diff, rotation_diff) =
+                masked_pose_diff(current, target_pose, constraints);
+            position_diff.norm() < self.allowable_target_distance
+                && rotation_diff.norm() < self.allowable_target_distance
+        };
+        let (current, is_exact) = self.cache.lookup(arm);
+        if is_reached(&current) && (is_exact || is_reached(&arm.end_transform())) {
+            tracing::debug!("[FkCachedIkSolver] Target pose is already reached");
+            return Ok(());
+        }
+        self.solver
+            .solve_with_constraints(arm, target_pose, constraints)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    struct CountingIkSolver(AtomicUsize);
+
+    impl InverseKinematicsSolver<f64> for CountingIkSolver {
+        fn solve_with_constraints(
+            &self,
+            _arm: &k::SerialChain<f64>,
+            _target_pose: &na::Isometry3<f64>,
+            _constraints: &k::Constraints,
+        ) -> Result<(), k::Error> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    fn arm() -> k::SerialChain<f64> {
+        let robot = k::Chain::<f64>::from_urdf_file("sample.urdf").unwrap();
+        let arm = k::SerialChain::from_end(robot.find("l_tool_fixed").unwrap());
+        arm.set_joint_positions(&[0.2, 0.2, 0.0, -1.0, 0.0, 0.0])
+            .unwrap();
+        arm
+    }
+
+    #[test]
+    fn cache_end_transform() {
+        let arm = arm();
+        let cache = ForwardKinematicsCache::new(1e-6, 10);
+        let expected = arm.end_transform();
+        assert_eq!(cache.end_transform(&arm), expected);
+        assert_eq!(cache.end_transform(&arm), expected);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.hits(), 1);
+
+        arm.set_joint_positions(&[0.3, 0.2, 0.0, -1.0, 0.0, 0.0])
+            .unwrap();
+        assert_eq!(cache.end_transform(&arm), arm.end_transform());
+        assert_eq!(cache.len(), 2);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.hits(), 0);
+    }
+
+    #[test]
+    fn cache_end_transform_in_parallel() {
+        use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+        let arm = arm();
+        let cache = ForwardKinematicsCache::new(1e-6, 10);
+        let expected = arm.end_transform();
+        (0..100).into_par_iter().for_each(|_| {
+            assert_eq!(cache.end_transform(&arm.clone()), expected);
+        });
+        // computed at most once for each thread
+        assert!(cache.len() <= rayon::current_num_threads() + 1);
+        assert_eq!(cache.len() + cache.hits(), 100);
+    }
+
+    #[test]
+    fn skip_solving_reached_target() {
+        let arm = arm();
+        let cache = Arc::new(ForwardKinematicsCache::new(1e-6, 10));
+        let solver = FkCachedIkSolver::new(CountingIkSolver(AtomicUsize::new(0)), cache, 0.001);
+        let constraints = k::Constraints::default();
+
+        let reached = arm.end_transform();
+        solver
+            .solve_with_constraints(&arm, &reached, &constraints)
+            .unwrap();
+        assert_eq!(solver.solver.0.load(Ordering::Relaxed), 0);
+
+        let mut target = reached;
+        target.translation.vector[0] += 0.1;
+        solver
+            .solve_with_constraints(&arm, &target, &constraints)
+            .unwrap();
+        assert_eq!(solver.solver.0.load(Ordering::Relaxed), 1);
+
+        // The difference along the ignored axis doesn't matter.
+        let constraints = k::Constraints {
+            position_x: false,
+            ..Default::default()
+        };
+        solver
+            .solve_with_constraints(&arm, &target, &constraints)
+            .unwrap();
+        assert_eq!(solver.solver.0.load(Ordering::Relaxed), 1);
+        assert_eq!(solver.cache.hits(), 2);
+    }
+
+    #[test]
+    fn verify_approximate_cached_transform() {
+        let arm = arm();
+        // Joint positions within 0.1 rad share an entry.
+        let cache = Arc::new(ForwardKinematicsCache::new(0.1, 10));
+        let solver = FkCachedIkSolver::new(CountingIkSolver(AtomicUsize::new(0)), cache, 0.001);
+        let constraints = k::Constraints::default();
+
+        let cached = solver.cache.end_transform(&arm);
+        arm.set_joint_positions(&[0.22, 0.2, 0.0, -1.0, 0.0, 0.0])
+            .unwrap();
+        assert_eq!(solver.cache.end_transform(&arm), cached);
+        // The cached transform reaches the target, but the exact one doesn't.
+        solver
+            .solve_with_constraints(&arm, &cached, &constraints)
+            .unwrap();
+        assert_eq!(solver.solver.0.load(Ordering::Relaxed), 1);
+
+        // The exact transform is used as is if it's not cached yet.
+        solver.cache.clear();
+        solver
+            .solve_with_constraints(&arm, &arm.end_transform(), &constraints)
+            .unwrap();
+        assert_eq!(solver.solver.0.load(Ordering::Relaxed), 1);
+        assert_eq!(solver.cache.hits(), 0);
+    }
+}
@@ -87,6 +87,61 @@ where
         .collect()
 }
 
+/// Generate random joint angles around `nominal` within the optional limits
+///
+/// Each joint angle is drawn from the normal distribution with the mean of
+/// the nominal angle and the standard deviation of `spread`, truncated to the
+/// limit (resampled a few times, and then clamped). This keeps the samples
+/// near a preferred posture, e.g., to avoid elbow flips of redundant arms.
+/// Returns [`Error::DofMismatch`] if the lengths of `limits` and `nominal`
+/// are different.
+///
+/// ```
+/// let limits = vec![Some(k::joint::Range::new(0.0, 0.2)), None];
+/// let a = openrr_planner::generate_random_joint_positions_from_limits_biased(
+///     &limits,
+///     &[0.1, 3.0],
+///     0.05,
+/// )
+/// .unwrap();
+/// assert!(a[0] >= 0.0 && a[0] <= 0.2);
+/// ```
+pub fn generate_random_joint_positions_from_limits_biased<T>(
+    limits: &Limits<T>,
+    nominal: &[T],
+    spread: T,
+) -> Result<Vec<T>>
+where
+    T: RealField + Copy,
+{
+    const MAX_RESAMPLE: usize = 10;
+    if nominal.len() != limits.len() {
+        return Err(Error::DofMismatch(nominal.len(), limits.len()));
+    }
+    Ok(limits
+        .iter()
+        .zip(nominal)
+        .map(|(range, &mean)| {
+            let sample = || mean + spread * na::convert(standard_normal());
+            match range {
+                Some(range) => (0..MAX_RESAMPLE)
+                    .map(|_| sample())
+                    .find(|x| *x >= range.min && *x <= range.max)
+                    .unwrap_or_else(|| sample().clamp(range.min, range.max)),
+                None => sample(),
+            }
+        })
+        .collect())
+}
+
+/// Sample from the standard normal distribution by the Box-Muller transform
+fn standard_normal() -> f64 {
+    // Avoid ln(0)
+    let u1 = 1.0 - rand::random::<f64>();
+    let u2 = rand::random::<f64>();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+}
+
 /// If the joint has no limit, select the nearest value from (x + 2pi *).
 ///
 /// ```
@@ -120,6 +175,59 @@ where
     }
 }
 
+/// Distance between two joint positions in joint space.
+///
+/// The distance is the euclidean norm of the per-joint differences. For the
+/// joints without limits (continuous joints), the difference is wrapped into
+/// `[-pi, pi]`, so that `-pi + 0.1` and `pi - 0.1` are close.
+///
+/// ```
+/// let limits = vec![Some(k::joint::Range::new(-4.0, 4.0)), None];
+/// let d = openrr_planner::joint_distance(&[3.0, 3.0], &[-3.0, -3.0], &limits);
+/// // 6.0 for the limited joint, and 2pi - 6.0 for the continuous joint
+/// assert!((d - 6.0f64.hypot(2.0 * std::f64::consts::PI - 6.0)).abs() < 1e-6);
+/// ```
+#[track_caller]
+pub fn joint_distance<T>(a: &[T], b: &[T], limits: &[Option<k::joint::Range<T>>]) -> T
+where
+    T: RealField + Copy,
+{
+    assert_eq!(a.len(), b.len());
+    assert_eq!(a.len(), limits.len());
+    let pi2 = T::two_pi();
+    a.iter()
+        .zip(b)
+        .zip(limits)
+        .map(|((&a, &b), limit)| {
+            let mut diff = a - b;
+            if limit.is_none() {
+                diff -= pi2 * (diff / pi2).round();
+            }
+            diff * diff
+        })
+        .fold(T::zero(), |sum, d| sum + d)
+        .sqrt()
+}
+
+/// Returns the index of the candidate nearest to `reference` in terms of
+/// [`joint_distance`], or `None` if `candidates` is empty.
+#[track_caller]
+pub fn nearest_configuration<T>(
+    candidates: &[Vec<T>],
+    reference: &[T],
+    limits: &[Option<k::joint::Range<T>>],
+) -> Option<usize>
+where
+    T: RealField + Copy,
+{
+    candidates
+        .iter()
+        .map(|c| joint_distance(c, reference, limits))
+        .enumerate()
+        .min_by(|(_, d1), (_, d2)| d1.partial_cmp(d2).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(i, _)| i)
+}
+
 /// Struct for a point of a trajectory with multiple dimensions.
 #[derive(Debug, Clone)]
 pub struct TrajectoryPoint<T> {
@@ -139,6 +247,31 @@ impl<T> TrajectoryPoint<T> {
     }
 }
 
+/// Forward-simulate `trajectory` and return the end transforms of `arm` at
+/// each point
+///
+/// The joint positions of `arm` are restored after the simulation, so this
+/// can be used to validate a trajectory before sending it to the hardware.
+/// Returns an error if any point violates the joint limits.
+pub fn preview_trajectory<T>(
+    arm: &k::SerialChain<T>,
+    trajectory: &[TrajectoryPoint<T>],
+) -> Result<Vec<na::Isometry3<T>>>
+where
+    T: RealField + Copy + k::SubsetOf<f64>,
+{
+    let initial_positions = arm.joint_positions();
+    let transforms = trajectory
+        .iter()
+        .map(|point| {
+            arm.set_joint_positions(&point.position)?;
+            Ok(arm.end_transform())
+        })
+        .collect::<Result<Vec<_>>>();
+    arm.set_joint_positions_unchecked(&initial_positions);
+    transforms
+}
+
 /// Interpolate position vectors
 ///
 /// returns vector of (position, velocity, acceleration)
@@ -187,6 +320,83 @@ where
     Some(ret)
 }
 
+/// The max number of points generated by [`min_jerk_trajectory`]
+pub const MAX_MIN_JERK_POINTS: usize = 1_000_000;
+
+/// Generate a minimum-jerk trajectory from `start` to `end`
+///
+/// Each joint follows the 5th-order polynomial which starts and ends with zero
+/// velocity and acceleration. Points are sampled at every `unit_duration`,
+/// and the final point at `total_duration` is added, as in [`interpolate`].
+/// Non-finite or non-positive durations, and durations which need more than
+/// [`MAX_MIN_JERK_POINTS`] points, are rejected.
+///
+/// # Example
+///
+/// ```
+/// let points = openrr_planner::min_jerk_trajectory(&[0.0, 1.0], &[2.0, 0.0], 1.0, 0.5).unwrap();
+/// assert_eq!(points.len(), 3);
+/// assert_eq!(points[1].position, vec![1.0, 0.5]);
+/// assert_eq!(points[2].position, vec![2.0, 0.0]);
+/// assert_eq!(points[2].velocity, vec![0.0, 0.0]);
+/// ```
+pub fn min_jerk_trajectory<T>(
+    start: &[T],
+    end: &[T],
+    total_duration: T,
+    unit_duration: T,
+) -> Result<Vec<TrajectoryPoint<T>>>
+where
+    T: Float,
+{
+    if start.len() != end.len() {
+        return Err(Error::DofMismatch(start.len(), end.len()));
+    }
+    if !total_duration.is_finite()
+        || !unit_duration.is_finite()
+        || total_duration <= T::zero()
+        || unit_duration <= T::zero()
+    {
+        return Err(Error::InterpolationError(
+            "durations must be positive and finite".to_owned(),
+        ));
+    }
+    let c = |v: f64| T::from(v).unwrap();
+    if (total_duration / unit_duration).ceil() >= c(MAX_MIN_JERK_POINTS as f64) {
+        return Err(Error::InterpolationError(format!(
+            "too many points, must be less than {MAX_MIN_JERK_POINTS}"
+        )));
+    }
+    let point_at = |t: T| {
+        let s = t / total_duration;
+        let (s2, s3) = (s * s, s * s * s);
+        let (s4, s5) = (s3 * s, s3 * s2);
+        let position_ratio = c(10.0) * s3 - c(15.0) * s4 + c(6.0) * s5;
+        let velocity_ratio = (c(30.0) * s2 - c(60.0) * s3 + c(30.0) * s4) / total_duration;
+        let acceleration_ratio =
+            (c(60.0) * s - c(180.0) * s2 + c(120.0) * s3) / (total_duration * total_duration);
+        let diff = start.iter().zip(end).map(|(&p0, &p1)| p1 - p0);
+        TrajectoryPoint {
+            position: start
+                .iter()
+                .zip(diff.clone())
+                .map(|(&p0, d)| p0 + d * position_ratio)
+                .collect(),
+            velocity: diff.clone().map(|d| d * velocity_ratio).collect(),
+            acceleration: diff.map(|d| d * acceleration_ratio).collect(),
+        }
+    };
+    let mut ret = vec![];
+    let mut t = T::zero();
+    while t < total_duration {
+        ret.push(point_at(t));
+        t = t + unit_duration;
+    }
+    // Add final point
+    ret.push(point_at(total_duration));
+    Ok(ret)
+}
+
 /// Set random joint angles
 pub fn set_random_joint_positions<T>(robot: &k::Chain<T>) -> ::std::result::Result<(), k::Error>
 where
@@ -196,6 +406,120 @@ where
     robot.set_joint_positions(&generate_random_joint_positions_from_limits(&limits))
 }
 
+/// Mimic relationship between joints, as `<mimic>` of URDF
+///
+/// The position of `joint_name` is `multiplier * (position of source_joint_name) + offset`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JointMimic {
+    /// Name of the mimic joint
+    pub joint_name: String,
+    /// Name of the joint to be mimicked
+    pub source_joint_name: String,
+    /// Factor applied to the position of the source joint
+    pub multiplier: f64,
+    /// Offset added after the multiplication
+    pub offset: f64,
+}
+
+/// Collect the mimic relationships defined in the URDF
+pub fn mimic_joints_from_urdf(urdf_robot: &urdf_rs::Robot) -> Vec<JointMimic> {
+    urdf_robot
+        .joints
+        .iter()
+        .filter_map(|joint| {
+            let mimic = joint.mimic.as_ref()?;
+            Some(JointMimic {
+                joint_name: joint.name.clone(),
+                source_joint_name: mimic.joint.clone(),
+                multiplier: mimic.multiplier.unwrap_or(1.0),
+                offset: mimic.offset.unwrap_or(0.0),
+            })
+        })
+        .collect()
+}
+
+/// Collect the velocity limits of the joints of `arm` defined in the URDF
+///
+/// The result has the same order as `arm.joint_positions()`. The limit is
+/// `None` if the joint is not in the URDF or its limit is not positive
+/// (e.g., `velocity="0"`).
+pub fn velocity_limits_from_urdf<T>(
+    urdf_robot: &urdf_rs::Robot,
+    arm: &k::SerialChain<T>,
+) -> Vec<Option<f64>>
+where
+    T: RealField,
+{
+    joint_limits_from_urdf(urdf_robot, arm, |limit| limit.velocity)
+}
+
+/// Collect the effort limits of the joints of `arm` defined in the URDF
+///
+/// The result has the same order as `arm.joint_positions()`. The limit is
+/// `None` if the joint is not in the URDF or its limit is not positive
+/// (e.g., `effort="0"`).
+pub fn effort_limits_from_urdf<T>(
+    urdf_robot: &urdf_rs::Robot,
+    arm: &k::SerialChain<T>,
+) -> Vec<Option<f64>>
+where
+    T: RealField,
+{
+    joint_limits_from_urdf(urdf_robot, arm, |limit| limit.effort)
+}
+
+fn joint_limits_from_urdf<T>(
+    urdf_robot: &urdf_rs::Robot,
+    arm: &k::SerialChain<T>,
+    value: impl Fn(&urdf_rs::JointLimit) -> f64,
+) -> Vec<Option<f64>>
+where
+    T: RealField,
+{
+    arm.iter_joints()
+        .map(|joint| {
+            let urdf_joint = urdf_robot.joints.iter().find(|j| j.name == joint.name)?;
+            let limit = value(&urdf_joint.limit);
+            (limit > 0.0).then_some(limit)
+        })
+        .collect()
+}
+
+/// Update the positions of the mimic joints from their source joints
+///
+/// Mimic relationships whose joints are not both in `arm` are ignored.
+/// Returns `true` if any joint position is changed.
+pub fn apply_mimic_joints<T>(arm: &k::SerialChain<T>, mimics: &[JointMimic]) -> bool
+where
+    T: RealField + Copy + k::SubsetOf<f64>,
+{
+    let names = arm
+        .iter_joints()
+        .map(|joint| joint.name.clone())
+        .collect::<Vec<_>>();
+    let index_of = |name: &str| names.iter().position(|n| n == name);
+    let mut positions = arm.joint_positions();
+    let mut changed = false;
+    for mimic in mimics {
+        let (Some(i), Some(source)) = (
+            index_of(&mimic.joint_name),
+            index_of(&mimic.source_joint_name),
+        ) else {
+            continue;
+        };
+        let position =
+            positions[source] * na::convert(mimic.multiplier) + na::convert(mimic.offset);
+        if (positions[i] - position).abs() > na::convert(1e-9) {
+            positions[i] = position;
+            changed = true;
+        }
+    }
+    if changed {
+        arm.set_joint_positions_unchecked(&positions);
+    }
+    changed
+}
+
 /// Create a sub-chain of the collision check model by a name list
 pub fn create_chain_from_joint_names<N>(
     robot: &k::Chain<N>,
@@ -15,15 +15,78 @@ limitations under the License.
 */
 #![allow(clippy::trivially_copy_pass_by_ref)]
 
-use std::sync::Mutex;
+use std::{path::Path, sync::Mutex};
 
 use k::{nalgebra as na, InverseKinematicsSolver, SubsetOf};
 use na::RealField;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
-use crate::funcs::*;
+use crate::{errors::*, funcs::*, CollisionDetector, ReachabilityScanState, SampleIndex};
+
+/// Returns the span entered by the IK solvers in this crate while solving.
+///
+/// The span carries the name of the solver and the target pose, so the logs of
+/// nested solvers can be correlated and filtered (e.g.,
+/// `RUST_LOG=[ik_solve{solver=RandomInitializeIkSolver}]=debug`).
+pub fn ik_solve_span<T>(solver: &'static str, target_pose: &na::Isometry3<T>) -> tracing::Span
+where
+    T: RealField,
+{
+    tracing::debug_span!("ik_solve", solver, target_pose = ?target_pose)
+}
+
+/// Returns the position and rotation differences from `current` to `target`,
+/// with the axes not constrained by `constraints` set to zero.
+pub(crate) fn masked_pose_diff<T>(
+    current: &na::Isometry3<T>,
+    target: &na::Isometry3<T>,
+    constraints: &k::Constraints,
+) -> (na::Vector3<T>, na::Vector3<T>)
+where
+    T: RealField + Copy,
+{
+    let position_diff = target.translation.vector - current.translation.vector;
+    let rotation_diff = (target.rotation * current.rotation.inverse()).scaled_axis();
+    let masked = |diff: na::Vector3<T>, mask: [bool; 3]| {
+        na::Vector3::from_fn(|i, _| if mask[i] { diff[i] } else { T::zero() })
+    };
+    (
+        masked(
+            position_diff,
+            [
+                constraints.position_x,
+                constraints.position_y,
+                constraints.position_z,
+            ],
+        ),
+        masked(
+            rotation_diff,
+            [
+                constraints.rotation_x,
+                constraints.rotation_y,
+                constraints.rotation_z,
+            ],
+        ),
+    )
+}
 
 /// Randomize initial joint angles before solving
+///
+/// The total iteration budget is `num_max_try` times the number of iterations
+/// of the inner `solver` per try. Many tries with few inner iterations explore
+/// more initial joint angles, while few tries with many inner iterations give
+/// each initial state more chance to converge. When the inner solver is a
+/// [`k::JacobianIkSolver`], its parameters can be set with
+/// [`inner_num_max_try`](RandomInitializeIkSolver::inner_num_max_try) and
+/// [`inner_allowable_target_distance`](RandomInitializeIkSolver::inner_allowable_target_distance).
+///
+/// If mimic joints are set by [`mimic_joints`](RandomInitializeIkSolver::mimic_joints),
+/// they are ignored by the inner `solver` and updated from their source joints,
+/// and the inner `solver` is run again until the mimic relationships hold.
+///
+/// When the inner solver is a [`PrioritizedIkSolver`] or a
+/// [`k::JacobianIkSolver`], its damping can be increased across the tries with
+/// `damping_schedule`, so that targets near singularities converge.
 #[derive(Debug)]
 pub struct RandomInitializeIkSolver<T, I>
 where
@@ -32,11 +95,69 @@ where
 {
     /// The IK solver to be used after set random joint angles
     pub solver: I,
-    /// The number to try to solve
+    /// The number to try to solve, each from different initial joint angles
     pub num_max_try: usize,
+    mimics: Vec<JointMimic>,
+    seeds: Vec<Vec<T>>,
+    nominal: Option<(Vec<T>, T)>,
+    limit_tolerance: Option<T>,
+    damping_schedule: Option<DampingSchedule<T, I>>,
+    best_attempt: Option<Mutex<Option<IkAttempt<T>>>>,
     phantom: ::std::marker::PhantomData<T>,
 }
 
+/// Result of a failed try of [`RandomInitializeIkSolver`]
+#[derive(Debug, Clone)]
+pub struct IkAttempt<T>
+where
+    T: RealField,
+{
+    /// The joint positions at the end of the try
+    ///
+    /// Note that some inner solvers restore the joint positions at the start
+    /// of the try on failure.
+    pub joint_positions: Vec<T>,
+    /// The position difference of the constrained axes from the target
+    pub position_diff: na::Vector3<T>,
+    /// The rotation difference of the constrained axes from the target
+    pub rotation_diff: na::Vector3<T>,
+}
+
+impl<T> IkAttempt<T>
+where
+    T: RealField + Copy,
+{
+    /// The sum of the norms of the position and rotation differences
+    pub fn residual(&self) -> T {
+        self.position_diff.norm() + self.rotation_diff.norm()
+    }
+}
+
+/// Damping factors for each try, and the function to solve with one of them
+struct DampingSchedule<T, I> {
+    dampings: Vec<T>,
+    #[allow(clippy::type_complexity)]
+    solve: Box<
+        dyn Fn(
+                &I,
+                &k::SerialChain<T>,
+                &na::Isometry3<T>,
+                &k::Constraints,
+                T,
+            ) -> ::std::result::Result<(), k::Error>
+            + Send
+            + Sync,
+    >,
+}
+
+impl<T: std::fmt::Debug, I> std::fmt::Debug for DampingSchedule<T, I> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DampingSchedule")
+            .field("dampings", &self.dampings)
+            .finish_non_exhaustive()
+    }
+}
+
 impl<T, I> RandomInitializeIkSolver<T, I>
 where
     T: RealField,
@@ -46,9 +167,408 @@ where
         RandomInitializeIkSolver {
             solver,
             num_max_try,
+            mimics: vec![],
+            seeds: vec![],
+            nominal: None,
+            limit_tolerance: None,
+            damping_schedule: None,
+            best_attempt: None,
             phantom: ::std::marker::PhantomData,
         }
     }
+
+    /// Create a solver which tries `seeds` before the random initial joint
+    /// angles
+    ///
+    /// After the try from the current joint angles, each seed is tried in
+    /// order, and then `num_max_try - 1` random joint angles are tried.
+    /// Seeds which can't be set to the arm (e.g., out of the joint limits)
+    /// are skipped.
+    pub fn with_seeds(solver: I, num_max_try: usize, seeds: Vec<Vec<T>>) -> Self {
+        Self::new(solver, num_max_try).seeds(seeds)
+    }
+
+    /// Set the joint angles to try before the random initial joint angles.
+    /// See [`with_seeds`](RandomInitializeIkSolver::with_seeds).
+    pub fn seeds(mut self, seeds: Vec<Vec<T>>) -> Self {
+        self.seeds = seeds;
+        self
+    }
+
+    /// Create a solver which draws the random initial joint angles around
+    /// `nominal`
+    ///
+    /// `nominal` has the joint angles of all the joints of the arm, otherwise
+    /// solving fails without trying. See
+    /// [`generate_random_joint_positions_from_limits_biased`] for `spread`.
+    /// This keeps the retries near a preferred posture instead of sampling the
+    /// whole joint ranges uniformly.
+    pub fn with_nominal(solver: I, num_max_try: usize, nominal: Vec<T>, spread: T) -> Self {
+        Self::new(solver, num_max_try).nominal(nominal, spread)
+    }
+
+    /// Set the posture to draw the random initial joint angles around. See
+    /// [`with_nominal`](RandomInitializeIkSolver::with_nominal).
+    pub fn nominal(mut self, nominal: Vec<T>, spread: T) -> Self {
+        self.nominal = Some((nominal, spread));
+        self
+    }
+
+    /// Treat solutions with any joint within `tolerance` of its limits as
+    /// failures, and try again from other initial joint angles
+    ///
+    /// Such solutions are fragile for the subsequent motion. Joints ignored by
+    /// the constraints are not checked. See [`joints_at_limits`].
+    pub fn reject_joints_at_limits(mut self, tolerance: T) -> Self {
+        self.limit_tolerance = Some(tolerance);
+        self
+    }
+
+    /// Record the best of the failed tries of each solve
+    ///
+    /// The best try is the one with the lowest [`IkAttempt::residual`] at the
+    /// joint positions it ended at. If all tries fail, the error reports its
+    /// differences, and it can be retrieved by
+    /// [`best_attempt`](RandomInitializeIkSolver::best_attempt), e.g., to tune
+    /// `num_max_try` or to warm-start another solve.
+    pub fn record_best_attempt(mut self) -> Self {
+        self.best_attempt = Some(Mutex::new(None));
+        self
+    }
+
+    /// Set the mimic relationships to keep, e.g. from [`mimic_joints_from_urdf`]
+    pub fn mimic_joints(mut self, mimics: Vec<JointMimic>) -> Self {
+        self.mimics = mimics;
+        self
+    }
+}
+
+/// The max number of times to run the inner solver to satisfy mimic relationships
+const MAX_MIMIC_ITERATIONS: usize = 10;
+
+impl<T, I> RandomInitializeIkSolver<T, I>
+where
+    T: RealField + Copy + SubsetOf<f64>,
+    I: InverseKinematicsSolver<T>,
+{
+    /// Returns the best failed try of the last solve, if recorded by
+    /// [`record_best_attempt`](RandomInitializeIkSolver::record_best_attempt)
+    ///
+    /// This is `None` if not recording, or if the last solve succeeded at the
+    /// first try. If this solver is shared between threads, this is of the
+    /// solve which finished last.
+    pub fn best_attempt(&self) -> Option<IkAttempt<T>> {
+        self.best_attempt.as_ref()?.lock().unwrap().clone()
+    }
+
+    /// Returns the residual of [`best_attempt`](RandomInitializeIkSolver::best_attempt).
+    pub fn best_residual(&self) -> Option<T> {
+        self.best_attempt().map(|attempt| attempt.residual())
+    }
+
+    /// Run the inner solver for the `try_idx`-th try
+    fn solve_inner(
+        &self,
+        arm: &k::SerialChain<T>,
+        target_pose: &na::Isometry3<T>,
+        constraints: &k::Constraints,
+        try_idx: usize,
+    ) -> ::std::result::Result<(), k::Error> {
+        match &self.damping_schedule {
+            Some(schedule) if !schedule.dampings.is_empty() => {
+                let damping = schedule.dampings[try_idx.min(schedule.dampings.len() - 1)];
+                (schedule.solve)(&self.solver, arm, target_pose, constraints, damping)
+            }
+            _ => self
+                .solver
+                .solve_with_constraints(arm, target_pose, constraints),
+        }
+    }
+
+    fn solve_with_mimics(
+        &self,
+        arm: &k::SerialChain<T>,
+        target_pose: &na::Isometry3<T>,
+        constraints: &k::Constraints,
+        try_idx: usize,
+    ) -> ::std::result::Result<(), k::Error> {
+        solve_keeping_mimics(arm, &self.mimics, || {
+            self.solve_inner(arm, target_pose, constraints, try_idx)
+        })
+    }
+}
+
+/// Run `solve` until the mimic joints of `arm` follow their source joints
+fn solve_keeping_mimics<T>(
+    arm: &k::SerialChain<T>,
+    mimics: &[JointMimic],
+    mut solve: impl FnMut() -> ::std::result::Result<(), k::Error>,
+) -> ::std::result::Result<(), k::Error>
+where
+    T: RealField + Copy + SubsetOf<f64>,
+{
+    if mimics.is_empty() {
+        return solve();
+    }
+    apply_mimic_joints(arm, mimics);
+    for _ in 0..MAX_MIMIC_ITERATIONS {
+        solve()?;
+        if !apply_mimic_joints(arm, mimics) {
+            return Ok(());
+        }
+    }
+    Err(k::Error::NotConvergedError {
+        num_tried: MAX_MIMIC_ITERATIONS,
+        position_diff: na::Vector3::new(0.0, 0.0, 0.0),
+        rotation_diff: na::Vector3::new(0.0, 0.0, 0.0),
+    })
+}
+
+/// Run the borrowed solver keeping the mimic joints
+struct MimicIkSolver<'a, I> {
+    solver: &'a I,
+    mimics: &'a [JointMimic],
+}
+
+impl<T, I> InverseKinematicsSolver<T> for MimicIkSolver<'_, I>
+where
+    T: RealField + Copy + SubsetOf<f64>,
+    I: InverseKinematicsSolver<T>,
+{
+    fn solve_with_constraints(
+        &self,
+        arm: &k::SerialChain<T>,
+        target_pose: &na::Isometry3<T>,
+        constraints: &k::Constraints,
+    ) -> ::std::result::Result<(), k::Error> {
+        solve_keeping_mimics(arm, self.mimics, || {
+            self.solver
+                .solve_with_constraints(arm, target_pose, constraints)
+        })
+    }
+}
+
+impl<T> RandomInitializeIkSolver<T, k::JacobianIkSolver<T>>
+where
+    T: RealField,
+{
+    /// Set the number of iterations of the inner solver for each try
+    pub fn inner_num_max_try(mut self, num_max_try: usize) -> Self {
+        self.solver.num_max_try = num_max_try;
+        self
+    }
+
+    /// Set the distance which the inner solver regards as reached
+    pub fn inner_allowable_target_distance(mut self, allowable_target_distance: T) -> Self {
+        self.solver.allowable_target_distance = allowable_target_distance;
+        self
+    }
+}
+
+impl<T> RandomInitializeIkSolver<T, k::JacobianIkSolver<T>>
+where
+    T: RealField + Copy + SubsetOf<f64>,
+{
+    /// Set the damping factors of the inner solver for each try
+    ///
+    /// [`k::JacobianIkSolver`] has no damping factor, so the tries are solved
+    /// by the damped least squares `dq = Jᵀ(JJᵀ + λ²I)⁻¹ e` instead, with `λ`
+    /// = `dampings[i]` for the `i`-th try and the other parameters of the
+    /// inner solver. The tries after the end of `dampings` use the last one.
+    /// The nullspace function of the inner solver can't be read from it, so
+    /// use [`damping_schedule_with_nullspace`] to keep a nullspace term. If
+    /// empty (default), the inner solver is used as is.
+    ///
+    /// [`damping_schedule_with_nullspace`]: RandomInitializeIkSolver::damping_schedule_with_nullspace
+    pub fn damping_schedule(mut self, dampings: Vec<T>) -> Self {
+        self.damping_schedule = Some(DampingSchedule {
+            dampings,
+            solve: Box::new(
+                |solver: &k::JacobianIkSolver<T>,
+                 arm: &k::SerialChain<T>,
+                 target_pose: &na::Isometry3<T>,
+                 constraints: &k::Constraints,
+                 damping: T| {
+                    solve_jacobian_with_damping(
+                        solver,
+                        arm,
+                        target_pose,
+                        constraints,
+                        damping,
+                        None,
+                    )
+                },
+            ),
+        });
+        self
+    }
+
+    /// Set the damping factors of the inner solver for each try, and the
+    /// nullspace function used by them
+    ///
+    /// Same as [`damping_schedule`](RandomInitializeIkSolver::damping_schedule),
+    /// but `(I - J⁺J)` times the output of `nullspace_function` for the
+    /// current joint positions is added to each step, as
+    /// [`k::JacobianIkSolver::set_nullspace_function`] does.
+    pub fn damping_schedule_with_nullspace<F>(
+        mut self,
+        dampings: Vec<T>,
+        nullspace_function: F,
+    ) -> Self
+    where
+        F: Fn(&[T]) -> Vec<T> + Send + Sync + 'static,
+    {
+        self.damping_schedule = Some(DampingSchedule {
+            dampings,
+            solve: Box::new(
+                move |solver: &k::JacobianIkSolver<T>,
+                      arm: &k::SerialChain<T>,
+                      target_pose: &na::Isometry3<T>,
+                      constraints: &k::Constraints,
+                      damping: T| {
+                    solve_jacobian_with_damping(
+                        solver,
+                        arm,
+                        target_pose,
+                        constraints,
+                        damping,
+                        Some(&nullspace_function),
+                    )
+                },
+            ),
+        });
+        self
+    }
+}
+
+/// Solve by the damped least squares with the parameters of `solver`
+///
+/// Each step is `jacobian_multiplier * Jᵀ(JJᵀ + λ²I)⁻¹ e` with `λ` =
+/// `damping`, plus the projection of `nullspace_function` onto the nullspace
+/// of `J` if given. Converged if the position and rotation differences are
+/// within `allowable_target_distance` and `allowable_target_angle`
+/// respectively. On failure, the joint positions are restored.
+fn solve_jacobian_with_damping<T>(
+    solver: &k::JacobianIkSolver<T>,
+    arm: &k::SerialChain<T>,
+    target_pose: &na::Isometry3<T>,
+    constraints: &k::Constraints,
+    damping: T,
+    nullspace_function: Option<&dyn Fn(&[T]) -> Vec<T>>,
+) -> ::std::result::Result<(), k::Error>
+where
+    T: RealField + Copy + SubsetOf<f64>,
+{
+    let initial_positions = arm.joint_positions();
+    let limits = arm.iter_joints().map(|j| j.limits).collect::<Vec<_>>();
+    let ignored = arm
+        .iter_joints()
+        .map(|joint| constraints.ignored_joint_names.contains(&joint.name))
+        .collect::<Vec<_>>();
+    let mask = [
+        constraints.position_x,
+        constraints.position_y,
+        constraints.position_z,
+        constraints.rotation_x,
+        constraints.rotation_y,
+        constraints.rotation_z,
+    ];
+    let rows = (0..6).filter(|&i| mask[i]).collect::<Vec<_>>();
+    let dof = initial_positions.len();
+    let reached = |position_diff: &na::Vector3<T>, rotation_diff: &na::Vector3<T>| {
+        position_diff.norm() < solver.allowable_target_distance
+            && rotation_diff.norm() < solver.allowable_target_angle
+    };
+
+    for _ in 0..solver.num_max_try {
+        arm.update_transforms();
+        let (position_diff, rotation_diff) =
+            masked_pose_diff(&arm.end_transform(), target_pose, constraints);
+        if reached(&position_diff, &rotation_diff) {
+            return Ok(());
+        }
+        let error = na::DVector::from_fn(rows.len(), |r, _| {
+            let i = rows[r];
+            if i < 3 {
+                position_diff[i]
+            } else {
+                rotation_diff[i - 3]
+            }
+        });
+        let jacobian = k::jacobian(arm);
+        let primary_jacobian = na::DMatrix::from_fn(rows.len(), dof, |r, c| {
+            if ignored[c] {
+                T::zero()
+            } else {
+                jacobian[(rows[r], c)]
+            }
+        });
+        let inverse = PrioritizedIkSolver::<T>::pseudo_inverse(&primary_jacobian, damping);
+        let positions = arm.joint_positions();
+        let mut dq = &inverse * error * solver.jacobian_multiplier;
+        if let Some(nullspace_function) = nullspace_function {
+            let gradient = nullspace_function(&positions);
+            if gradient.len() != dof {
+                arm.set_joint_positions_unchecked(&initial_positions);
+                return Err(k::Error::PreconditionError {
+                    error: format!(
+                        "nullspace function returned {} values for {dof} joints",
+                        gradient.len()
+                    ),
+                });
+            }
+            let mut nullspace = na::DMatrix::identity(dof, dof) - &inverse * &primary_jacobian;
+            for (c, &ignored) in ignored.iter().enumerate() {
+                if ignored {
+                    nullspace.row_mut(c).fill(T::zero());
+                }
+            }
+            dq += nullspace * na::DVector::from_vec(gradient);
+        }
+        let new_positions = positions
+            .iter()
+            .zip(dq.iter())
+            .zip(&limits)
+            .map(|((&q, &dq), limit)| match limit {
+                Some(range) => (q + dq).clamp(range.min, range.max),
+                None => q + dq,
+            })
+            .collect::<Vec<_>>();
+        arm.set_joint_positions_unchecked(&new_positions);
+    }
+    arm.update_transforms();
+    let (position_diff, rotation_diff) =
+        masked_pose_diff(&arm.end_transform(), target_pose, constraints);
+    if reached(&position_diff, &rotation_diff) {
+        return Ok(());
+    }
+    arm.set_joint_positions_unchecked(&initial_positions);
+    arm.update_transforms();
+    Err(k::Error::NotConvergedError {
+        num_tried: solver.num_max_try,
+        position_diff: position_diff.map(na::convert),
+        rotation_diff: rotation_diff.map(na::convert),
+    })
+}
+
+impl<T> RandomInitializeIkSolver<T, PrioritizedIkSolver<T>>
+where
+    T: RealField + Copy + SubsetOf<f64>,
+{
+    /// Set the damping factors of the inner solver for each try
+    ///
+    /// The `i`-th try uses `dampings[i]`, and the tries after the end of
+    /// `dampings` use the last one. Increasing damping factors make the tries
+    /// more robust near singularities at the cost of slower convergence. The
+    /// damping factor of the inner solver itself is not modified. If empty
+    /// (default), the damping factor of the inner solver is used.
+    pub fn damping_schedule(mut self, dampings: Vec<T>) -> Self {
+        self.damping_schedule = Some(DampingSchedule {
+            dampings,
+            solve: Box::new(PrioritizedIkSolver::solve_with_damping),
+        });
+        self
+    }
 }
 
 impl<T, I> InverseKinematicsSolver<T> for RandomInitializeIkSolver<T, I>
@@ -62,131 +582,3423 @@ where
         target_pose: &na::Isometry3<T>,
         constraints: &k::Constraints,
     ) -> ::std::result::Result<(), k::Error> {
+        let _span = ik_solve_span("RandomInitializeIkSolver", target_pose).entered();
         tracing::debug!("[RandomInitializeIkSolver] Target pose: {:?}", target_pose);
         let mut result = Err(k::Error::NotConvergedError {
             num_tried: 0,
             position_diff: na::Vector3::new(0.0, 0.0, 0.0),
             rotation_diff: na::Vector3::new(0.0, 0.0, 0.0),
         });
-        let limits = arm.iter_joints().map(|j| j.limits).collect();
+        let limits = arm.iter_joints().map(|j| j.limits).collect::<Vec<_>>();
         let initial_angles = arm.joint_positions();
+        if let Some((nominal, _)) = &self.nominal {
+            if nominal.len() != limits.len() {
+                return Err(k::Error::PreconditionError {
+                    error: format!(
+                        "nominal has {} joint angles for {} joints",
+                        nominal.len(),
+                        limits.len()
+                    ),
+                });
+            }
+        }
+        let mut inner_constraints;
+        let constraints = if self.mimics.is_empty() {
+            constraints
+        } else {
+            inner_constraints = constraints.clone();
+            inner_constraints
+                .ignored_joint_names
+                .extend(self.mimics.iter().map(|m| m.joint_name.clone()));
+            &inner_constraints
+        };
 
-        for try_idx in 0..self.num_max_try {
+        let mut best_attempt: Option<IkAttempt<T>> = None;
+        let num_max_try = self.num_max_try + self.seeds.len();
+        for try_idx in 0..num_max_try {
             tracing::debug!(
                 "[RandomInitializeIkSolver] Iteration {}/{}: Solving IK from joint state -> {:.4?}",
                 try_idx,
-                self.num_max_try,
+                num_max_try,
                 arm.joint_positions()
             );
-            result = self
-                .solver
-                .solve_with_constraints(arm, target_pose, constraints);
+            result = self.solve_with_mimics(arm, target_pose, constraints, try_idx);
+            if let (Ok(()), Some(tolerance)) = (&result, self.limit_tolerance) {
+                let joints = arm.iter_joints().collect::<Vec<_>>();
+                let locked = joints_at_limits(arm, tolerance)
+                    .into_iter()
+                    .filter(|&i| !constraints.ignored_joint_names.contains(&joints[i].name))
+                    .collect::<Vec<_>>();
+                if !locked.is_empty() {
+                    tracing::debug!(
+                        "[RandomInitializeIkSolver] Reject the solution with joints {locked:?} at limits"
+                    );
+                    result = Err(k::Error::NotConvergedError {
+                        num_tried: try_idx + 1,
+                        position_diff: na::Vector3::new(0.0, 0.0, 0.0),
+                        rotation_diff: na::Vector3::new(0.0, 0.0, 0.0),
+                    });
+                }
+            }
+            if result.is_err() && self.best_attempt.is_some() {
+                arm.update_transforms();
+                let (position_diff, rotation_diff) =
+                    masked_pose_diff(&arm.end_transform(), target_pose, constraints);
+                let attempt = IkAttempt {
+                    joint_positions: arm.joint_positions(),
+                    position_diff,
+                    rotation_diff,
+                };
+                if best_attempt
+                    .as_ref()
+                    .map_or(true, |best| attempt.residual() < best.residual())
+                {
+                    best_attempt = Some(attempt);
+                }
+            }
             if result.is_ok() {
                 tracing::debug!(
                     "[RandomInitializeIkSolver] Solved IK with joint state -> {:.4?}",
                     arm.joint_positions()
                 );
+                if let Some(recorded) = &self.best_attempt {
+                    *recorded.lock().unwrap() = best_attempt;
+                }
                 return result;
             }
-            let mut new_angles = generate_random_joint_positions_from_limits(&limits);
+            if let Some(seed) = self.seeds.get(try_idx) {
+                match arm.set_joint_positions_with_constraints(seed, constraints) {
+                    Ok(()) => continue,
+                    // try random joint angles instead
+                    Err(e) => {
+                        tracing::debug!("[RandomInitializeIkSolver] Skip seed {seed:.4?}: {e}")
+                    }
+                }
+            }
+            let mut new_angles = match &self.nominal {
+                Some((nominal, spread)) => {
+                    generate_random_joint_positions_from_limits_biased(&limits, nominal, *spread)
+                        .map_err(|e| k::Error::PreconditionError {
+                            error: e.to_string(),
+                        })?
+                }
+                None => generate_random_joint_positions_from_limits(&limits),
+            };
             modify_to_nearest_angle(&initial_angles, &mut new_angles, &limits);
             arm.set_joint_positions_with_constraints(&new_angles, constraints)?;
         }
         // failed
         tracing::debug!(
             "[RandomInitializeIkSolver] Failed to solve IK after {} tries. Set initial joint angles {:.4?}",
-            self.num_max_try,
+            num_max_try,
             initial_angles,
         );
         arm.set_joint_positions(&initial_angles)?;
+        if let Some(recorded) = &self.best_attempt {
+            if let Some(best) = &best_attempt {
+                result = Err(k::Error::NotConvergedError {
+                    num_tried: num_max_try,
+                    position_diff: best.position_diff.map(na::convert),
+                    rotation_diff: best.rotation_diff.map(na::convert),
+                });
+            }
+            *recorded.lock().unwrap() = best_attempt;
+        }
         result
     }
 }
 
-/// Check the poses which can be reached by the robot arm
-pub fn get_reachable_region<T, I>(
-    ik_solver: &I,
-    arm: &k::SerialChain<T>,
-    initial_pose: &na::Isometry3<T>,
-    constraints: &k::Constraints,
-    max_point: na::Vector3<T>,
-    min_point: na::Vector3<T>,
-    unit_check_length: T,
-) -> Vec<na::Isometry3<T>>
+/// Accept the solutions of the inner solver only within a tolerance
+///
+/// After `solver` succeeds, the pose of the end of the arm is checked against
+/// the target, and an error is returned unless the position difference (in
+/// meters) of the constrained axes is within `position_tolerance` and the
+/// rotation difference (in radians) is within `angle_tolerance`. Note that
+/// a tolerance looser than the one of the inner solver has no effect. To
+/// loosen it, reconfigure the inner solver by
+/// [`TolerantIkSolver::set_tolerance`].
+#[derive(Debug, Clone)]
+pub struct ToleranceCheckedIkSolver<T, I>
 where
-    T: RealField + Copy + k::SubsetOf<f64> + Send + Sync,
-    I: InverseKinematicsSolver<T> + Send + Sync,
+    T: RealField,
+    I: InverseKinematicsSolver<T>,
 {
-    let initial_angles = arm.joint_positions();
-    let solved_poses = Mutex::new(Vec::new());
-    let target_pose = *initial_pose;
+    /// The inner solver
+    pub solver: I,
+    /// The maximum position difference regarded as reached
+    pub position_tolerance: T,
+    /// The maximum rotation difference regarded as reached
+    pub angle_tolerance: T,
+}
 
-    let mut z_points = vec![];
-    let mut z = min_point[2];
-    while z < max_point[2] {
-        z_points.push(z);
-        z += unit_check_length;
+impl<T, I> ToleranceCheckedIkSolver<T, I>
+where
+    T: RealField,
+    I: InverseKinematicsSolver<T>,
+{
+    pub fn new(solver: I, position_tolerance: T, angle_tolerance: T) -> Self {
+        Self {
+            solver,
+            position_tolerance,
+            angle_tolerance,
+        }
     }
+}
 
-    z_points.par_iter().for_each(|&z| {
-        let arm = arm.clone();
-        let mut target_pose = target_pose;
-        target_pose.translation.vector[2] = z;
-        let mut y = min_point[1];
-        while y < max_point[1] {
-            target_pose.translation.vector[1] = y;
-            let mut x = min_point[0];
-            while x < max_point[0] {
-                target_pose.translation.vector[0] = x;
-                arm.set_joint_positions_unchecked(&initial_angles);
-                if ik_solver
-                    .solve_with_constraints(&arm, &target_pose, constraints)
-                    .is_ok()
-                {
-                    solved_poses.lock().unwrap().push(target_pose);
-                }
-                x += unit_check_length;
-            }
-            y += unit_check_length;
+impl<T, I> InverseKinematicsSolver<T> for ToleranceCheckedIkSolver<T, I>
+where
+    T: RealField + Copy + SubsetOf<f64>,
+    I: InverseKinematicsSolver<T>,
+{
+    fn solve_with_constraints(
+        &self,
+        arm: &k::SerialChain<T>,
+        target_pose: &na::Isometry3<T>,
+        constraints: &k::Constraints,
+    ) -> ::std::result::Result<(), k::Error> {
+        let _span = ik_solve_span("ToleranceCheckedIkSolver", target_pose).entered();
+        self.solver
+            .solve_with_constraints(arm, target_pose, constraints)?;
+        arm.update_transforms();
+        let (position_diff, rotation_diff) =
+            masked_pose_diff(&arm.end_transform(), target_pose, constraints);
+        if position_diff.norm() <= self.position_tolerance
+            && rotation_diff.norm() <= self.angle_tolerance
+        {
+            return Ok(());
         }
-    });
-    solved_poses.into_inner().unwrap()
+        tracing::debug!(
+            "[ToleranceCheckedIkSolver] Solved pose is out of tolerance: position_diff={:?}, rotation_diff={:?}",
+            position_diff,
+            rotation_diff
+        );
+        Err(k::Error::NotConvergedError {
+            num_tried: 0,
+            position_diff: position_diff.map(na::convert),
+            rotation_diff: rotation_diff.map(na::convert),
+        })
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// IK solver whose tolerance to regard the target as reached can be changed
+/// after construction
+pub trait TolerantIkSolver<T>: InverseKinematicsSolver<T>
+where
+    T: RealField,
+{
+    /// Returns the position (in meters) and angle (in radians) tolerances
+    fn tolerance(&self) -> (T, T);
+    /// Set the position (in meters) and angle (in radians) tolerances
+    fn set_tolerance(&mut self, position_tolerance: T, angle_tolerance: T);
+}
 
-    #[test]
-    fn get_region() {
-        let robot = k::Chain::<f32>::from_urdf_file("sample.urdf").unwrap();
-        let target_link = robot.find("l_tool_fixed").unwrap();
-        let chain = k::SerialChain::from_end(target_link);
+impl<T> TolerantIkSolver<T> for k::JacobianIkSolver<T>
+where
+    T: RealField + Copy + SubsetOf<f64>,
+{
+    fn tolerance(&self) -> (T, T) {
+        (self.allowable_target_distance, self.allowable_target_angle)
+    }
 
-        // Set initial joint angles
-        let angles = vec![0.2, 0.2, 0.0, -1.0, 0.0, 0.0];
+    fn set_tolerance(&mut self, position_tolerance: T, angle_tolerance: T) {
+        self.allowable_target_distance = position_tolerance;
+        self.allowable_target_angle = angle_tolerance;
+    }
+}
 
-        chain.set_joint_positions(&angles).unwrap();
-        println!("initial angles={:?}", chain.joint_positions());
+impl<T, I> TolerantIkSolver<T> for RandomInitializeIkSolver<T, I>
+where
+    T: RealField + Copy + SubsetOf<f64>,
+    I: TolerantIkSolver<T>,
+{
+    fn tolerance(&self) -> (T, T) {
+        self.solver.tolerance()
+    }
 
-        // Get the transform of the end of the manipulator (forward kinematics)
-        chain.update_transforms();
-        let target = target_link.world_transform().unwrap();
-        println!("{:?}", target.translation);
-        // Create IK solver with default settings
-        let solver = k::JacobianIkSolver::default();
+    fn set_tolerance(&mut self, position_tolerance: T, angle_tolerance: T) {
+        self.solver
+            .set_tolerance(position_tolerance, angle_tolerance);
+    }
+}
 
-        // Create a set of joints from end joint
-        let arm = k::SerialChain::from_end(target_link);
-        let regions = get_reachable_region(
-            &solver,
-            &arm,
-            &target,
-            &k::Constraints::default(),
-            na::Vector3::new(0.8, 0.9, 0.9),
-            na::Vector3::new(0.0, -0.9, 0.0),
-            0.1,
-        );
-        assert_eq!(regions.len(), 114);
+impl<T> TolerantIkSolver<T> for PrioritizedIkSolver<T>
+where
+    T: RealField + Copy + SubsetOf<f64>,
+{
+    fn tolerance(&self) -> (T, T) {
+        (self.allowable_target_distance, self.allowable_target_angle)
+    }
+
+    fn set_tolerance(&mut self, position_tolerance: T, angle_tolerance: T) {
+        self.allowable_target_distance = position_tolerance;
+        self.allowable_target_angle = angle_tolerance;
+    }
+}
+
+/// The prefix of the message of [`k::Error::PreconditionError`] returned by
+/// [`CollisionAvoidIkSolver`] as an [`InverseKinematicsSolver`] when all the
+/// converged solutions are in self collision
+///
+/// Use [`is_self_collision_ik_error`] to tell it from the other errors.
+pub const SELF_COLLISION_IK_ERROR: &str =
+    "[CollisionAvoidIkSolver] all the solutions are in self collision";
+
+/// Returns true if `error` is the self collision reported by
+/// [`CollisionAvoidIkSolver`] as an [`InverseKinematicsSolver`]
+pub fn is_self_collision_ik_error(error: &k::Error) -> bool {
+    matches!(
+        error,
+        k::Error::PreconditionError { error } if error.starts_with(SELF_COLLISION_IK_ERROR)
+    )
+}
+
+/// Reject the solutions of the inner solver in self collision, retrying from
+/// random initial joint angles
+///
+/// Each try runs `solver` from the current (for the first try) or random
+/// initial joint angles, as [`RandomInitializeIkSolver`], and a converged
+/// solution is accepted only if the robot is not in self collision. The
+/// collision is checked on a separate robot model, whose joints of the same
+/// names as the arm are set to the solution, and whose other joints are kept.
+pub struct CollisionAvoidIkSolver<T, I>
+where
+    T: RealField + Copy + SubsetOf<f64>,
+    I: InverseKinematicsSolver<T>,
+{
+    /// The IK solver to be used for each try
+    pub solver: I,
+    /// The number to try to solve, each from different initial joint angles
+    pub num_max_try: usize,
+    robot: Mutex<k::Chain<T>>,
+    collision_detector: CollisionDetector<T>,
+    self_collision_pairs: Vec<(String, String)>,
+}
+
+impl<T, I> std::fmt::Debug for CollisionAvoidIkSolver<T, I>
+where
+    T: RealField + Copy + SubsetOf<f64>,
+    I: InverseKinematicsSolver<T> + std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CollisionAvoidIkSolver")
+            .field("solver", &self.solver)
+            .field("num_max_try", &self.num_max_try)
+            .field("self_collision_pairs", &self.self_collision_pairs)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, I> CollisionAvoidIkSolver<T, I>
+where
+    T: RealField + Copy + SubsetOf<f64>,
+    I: InverseKinematicsSolver<T>,
+{
+    /// Create a solver checking `self_collision_pairs` of `robot` by
+    /// `collision_detector`, which is created from the same robot
+    pub fn new(
+        solver: I,
+        num_max_try: usize,
+        robot: k::Chain<T>,
+        collision_detector: CollisionDetector<T>,
+        self_collision_pairs: Vec<(String, String)>,
+    ) -> Self {
+        Self {
+            solver,
+            num_max_try,
+            robot: Mutex::new(robot),
+            collision_detector,
+            self_collision_pairs,
+        }
+    }
+
+    /// Create a solver checking `self_collision_pairs` of the robot of the URDF
+    pub fn from_urdf_robot(
+        solver: I,
+        num_max_try: usize,
+        urdf_robot: &urdf_rs::Robot,
+        self_collision_pairs: Vec<(String, String)>,
+        prediction: T,
+    ) -> Self {
+        Self::new(
+            solver,
+            num_max_try,
+            k::Chain::from(urdf_robot),
+            CollisionDetector::from_urdf_robot(urdf_robot, prediction),
+            self_collision_pairs,
+        )
+    }
+
+    /// Returns the first pair of the links in self collision at the current
+    /// joint positions of `arm`, if any
+    pub fn self_collision(&self, arm: &k::SerialChain<T>) -> Option<(String, String)> {
+        let robot = self.robot.lock().unwrap();
+        for joint in arm.iter_joints() {
+            if let (Some(node), Some(position)) = (robot.find(&joint.name), joint.joint_position())
+            {
+                node.set_joint_position_unchecked(position);
+            }
+        }
+        self.collision_detector
+            .detect_self(&robot, &self.self_collision_pairs)
+            .next()
+    }
+
+    /// Solve IK avoiding self collision
+    ///
+    /// If no try converges, [`Error::KinematicsError`] is returned. If some
+    /// tries converge but all of the solutions are in self collision,
+    /// [`Error::SelfCollision`] with the colliding links of the last solution
+    /// is returned. In both cases, the joint positions of `arm` are restored.
+    pub fn solve_avoiding_collision(
+        &self,
+        arm: &k::SerialChain<T>,
+        target_pose: &na::Isometry3<T>,
+        constraints: &k::Constraints,
+    ) -> Result<()> {
+        let _span = ik_solve_span("CollisionAvoidIkSolver", target_pose).entered();
+        let limits = arm.iter_joints().map(|j| j.limits).collect();
+        let initial_angles = arm.joint_positions();
+        let mut error = k::Error::NotConvergedError {
+            num_tried: 0,
+            position_diff: na::Vector3::new(0.0, 0.0, 0.0),
+            rotation_diff: na::Vector3::new(0.0, 0.0, 0.0),
+        };
+        let mut collision = None;
+        for try_idx in 0..self.num_max_try {
+            if try_idx != 0 {
+                let mut new_angles = generate_random_joint_positions_from_limits(&limits);
+                modify_to_nearest_angle(&initial_angles, &mut new_angles, &limits);
+                arm.set_joint_positions_with_constraints(&new_angles, constraints)?;
+            }
+            match self
+                .solver
+                .solve_with_constraints(arm, target_pose, constraints)
+            {
+                Ok(()) => match self.self_collision(arm) {
+                    Some(names) => {
+                        tracing::debug!(
+                            "[CollisionAvoidIkSolver] Reject the solution {:.4?} in self collision: {names:?}",
+                            arm.joint_positions()
+                        );
+                        collision = Some(names);
+                    }
+                    None => return Ok(()),
+                },
+                Err(e) => error = e,
+            }
+        }
+        arm.set_joint_positions_unchecked(&initial_angles);
+        match collision {
+            Some(names) => Err(Error::SelfCollision {
+                point: UnfeasibleTrajectoryPoint::Goal,
+                collision_link_names: vec![names],
+            }),
+            None => Err(error.into()),
+        }
+    }
+}
+
+impl<T, I> InverseKinematicsSolver<T> for CollisionAvoidIkSolver<T, I>
+where
+    T: RealField + Copy + SubsetOf<f64>,
+    I: InverseKinematicsSolver<T>,
+{
+    /// See [`solve_avoiding_collision`](CollisionAvoidIkSolver::solve_avoiding_collision).
+    /// Self collision is reported as [`k::Error::PreconditionError`] whose
+    /// message starts with [`SELF_COLLISION_IK_ERROR`], followed by the
+    /// colliding links.
+    fn solve_with_constraints(
+        &self,
+        arm: &k::SerialChain<T>,
+        target_pose: &na::Isometry3<T>,
+        constraints: &k::Constraints,
+    ) -> ::std::result::Result<(), k::Error> {
+        match self.solve_avoiding_collision(arm, target_pose, constraints) {
+            Ok(()) => Ok(()),
+            Err(Error::KinematicsError { source }) => Err(source),
+            Err(Error::SelfCollision {
+                collision_link_names,
+                ..
+            }) => Err(k::Error::PreconditionError {
+                error: format!("{SELF_COLLISION_IK_ERROR}: {collision_link_names:?}"),
+            }),
+            Err(e) => Err(k::Error::PreconditionError {
+                error: e.to_string(),
+            }),
+        }
+    }
+}
+
+/// Check the poses which can be reached by the robot arm
+///
+/// The points are checked from `min_point` at every `unit_check_length`, and
+/// `max_point` is exclusive: a point on the boundary of `max_point` is not
+/// checked (subject to the accumulated rounding errors of the steps). Use
+/// [`get_reachable_region_inclusive`] to include it.
+///
+/// The joint positions of `arm` are reset to the current ones before solving
+/// each pose. To keep mimic joints consistent, use
+/// [`get_reachable_region_with_mimics`], or a solver handling them such as
+/// [`RandomInitializeIkSolver::mimic_joints`].
+pub fn get_reachable_region<T, I>(
+    ik_solver: &I,
+    arm: &k::SerialChain<T>,
+    initial_pose: &na::Isometry3<T>,
+    constraints: &k::Constraints,
+    max_point: na::Vector3<T>,
+    min_point: na::Vector3<T>,
+    unit_check_length: T,
+) -> Vec<na::Isometry3<T>>
+where
+    T: RealField + Copy + k::SubsetOf<f64> + Send + Sync,
+    I: InverseKinematicsSolver<T> + Send + Sync,
+{
+    let axis_points = |axis| axis_points_with_step(&min_point, &max_point, unit_check_length, axis);
+    solve_reachable_poses(
+        ik_solver,
+        arm,
+        initial_pose,
+        constraints,
+        [axis_points(0), axis_points(1), axis_points(2)],
+    )
+}
+
+/// Check the poses which can be reached by the robot arm, keeping the mimic
+/// joints
+///
+/// This is the same as [`get_reachable_region`], except that the positions of
+/// the mimic joints of `arm` follow their source joints for each pose, e.g.,
+/// those collected by [`mimic_joints_from_urdf`]. A pose is not reachable if
+/// the solution does not converge while keeping them.
+#[allow(clippy::too_many_arguments)]
+pub fn get_reachable_region_with_mimics<T, I>(
+    ik_solver: &I,
+    arm: &k::SerialChain<T>,
+    initial_pose: &na::Isometry3<T>,
+    constraints: &k::Constraints,
+    max_point: na::Vector3<T>,
+    min_point: na::Vector3<T>,
+    unit_check_length: T,
+    mimics: &[JointMimic],
+) -> Vec<na::Isometry3<T>>
+where
+    T: RealField + Copy + k::SubsetOf<f64> + Send + Sync,
+    I: InverseKinematicsSolver<T> + Send + Sync,
+{
+    get_reachable_region(
+        &MimicIkSolver {
+            solver: ik_solver,
+            mimics,
+        },
+        arm,
+        initial_pose,
+        constraints,
+        max_point,
+        min_point,
+        unit_check_length,
+    )
+}
+
+/// Check the poses which can be reached by the robot arm, passing each
+/// reachable pose to `callback` as soon as it is found
+///
+/// This is the same as [`get_reachable_region`], except that the poses are
+/// not collected, so that a fine scan of a large workspace can be written to
+/// disk or voxelized on the fly. `callback` is called from the worker threads
+/// in parallel, and the order of the poses is not specified.
+#[allow(clippy::too_many_arguments)]
+pub fn get_reachable_region_with_callback<T, I, F>(
+    ik_solver: &I,
+    arm: &k::SerialChain<T>,
+    initial_pose: &na::Isometry3<T>,
+    constraints: &k::Constraints,
+    max_point: na::Vector3<T>,
+    min_point: na::Vector3<T>,
+    unit_check_length: T,
+    callback: F,
+) where
+    T: RealField + Copy + k::SubsetOf<f64> + Send + Sync,
+    I: InverseKinematicsSolver<T> + Send + Sync,
+    F: Fn(na::Isometry3<T>) + Send + Sync,
+{
+    let axis_points = |axis| axis_points_with_step(&min_point, &max_point, unit_check_length, axis);
+    solve_reachable_poses_with_callback(
+        ik_solver,
+        arm,
+        initial_pose,
+        constraints,
+        [axis_points(0), axis_points(1), axis_points(2)],
+        callback,
+    );
+}
+
+/// Count the orientations which can be reached by the robot arm at each
+/// position of the grid
+///
+/// The grid is the same as [`get_reachable_region`]. At each position, IK is
+/// solved for each of `orientations` from the current joint positions of
+/// `arm`. Returns the pose at each position with the rotation of
+/// `initial_pose`, and the number of the reachable orientations, only for the
+/// positions where any orientation is reachable. Dividing the number by
+/// `orientations.len()` gives the reachability index. See also
+/// [`get_orientation_coverage`] to check arbitrary positions.
+#[allow(clippy::too_many_arguments)]
+pub fn get_reachable_region_with_orientations<T, I>(
+    ik_solver: &I,
+    arm: &k::SerialChain<T>,
+    initial_pose: &na::Isometry3<T>,
+    constraints: &k::Constraints,
+    max_point: na::Vector3<T>,
+    min_point: na::Vector3<T>,
+    unit_check_length: T,
+    orientations: &[na::UnitQuaternion<T>],
+) -> Vec<(na::Isometry3<T>, usize)>
+where
+    T: RealField + Copy + k::SubsetOf<f64> + Send + Sync,
+    I: InverseKinematicsSolver<T> + Send + Sync,
+{
+    let axis_points = |axis| axis_points_with_step(&min_point, &max_point, unit_check_length, axis);
+    let [x_points, y_points, z_points] = [axis_points(0), axis_points(1), axis_points(2)];
+    let initial_angles = arm.joint_positions();
+    let counts = Mutex::new(Vec::new());
+
+    z_points.par_iter().for_each(|&z| {
+        let arm = arm.clone();
+        let mut cell_pose = *initial_pose;
+        cell_pose.translation.vector[2] = z;
+        for &y in &y_points {
+            cell_pose.translation.vector[1] = y;
+            for &x in &x_points {
+                cell_pose.translation.vector[0] = x;
+                let count = count_reachable_orientations(
+                    ik_solver,
+                    &arm,
+                    &initial_angles,
+                    &cell_pose.translation,
+                    orientations,
+                    constraints,
+                );
+                if count > 0 {
+                    counts.lock().unwrap().push((cell_pose, count));
+                }
+            }
+        }
+    });
+    counts.into_inner().unwrap()
+}
+
+/// Count the orientations which can be reached at `translation`, solving IK
+/// for each of them from `initial_angles`
+fn count_reachable_orientations<T, I>(
+    ik_solver: &I,
+    arm: &k::SerialChain<T>,
+    initial_angles: &[T],
+    translation: &na::Translation3<T>,
+    orientations: &[na::UnitQuaternion<T>],
+    constraints: &k::Constraints,
+) -> usize
+where
+    T: RealField + Copy + k::SubsetOf<f64>,
+    I: InverseKinematicsSolver<T>,
+{
+    orientations
+        .iter()
+        .filter(|&&orientation| {
+            arm.set_joint_positions_unchecked(initial_angles);
+            let target_pose = na::Isometry3::from_parts(*translation, orientation);
+            ik_solver
+                .solve_with_constraints(arm, &target_pose, constraints)
+                .is_ok()
+        })
+        .count()
+}
+
+/// Check the poses which can be reached by the robot arm, including the
+/// boundary of `max_point`
+///
+/// This is the same as [`get_reachable_region`], except that the points on
+/// `max_point` are also checked, so that the grid is symmetric for a range
+/// symmetric about the origin. The number of points on each axis is computed
+/// from the range, so that it doesn't depend on rounding errors.
+pub fn get_reachable_region_inclusive<T, I>(
+    ik_solver: &I,
+    arm: &k::SerialChain<T>,
+    initial_pose: &na::Isometry3<T>,
+    constraints: &k::Constraints,
+    max_point: na::Vector3<T>,
+    min_point: na::Vector3<T>,
+    unit_check_length: T,
+) -> Vec<na::Isometry3<T>>
+where
+    T: RealField + Copy + k::SubsetOf<f64> + Send + Sync,
+    I: InverseKinematicsSolver<T> + Send + Sync,
+{
+    let axis_points = |axis| axis_points_inclusive(&min_point, &max_point, unit_check_length, axis);
+    solve_reachable_poses(
+        ik_solver,
+        arm,
+        initial_pose,
+        constraints,
+        [axis_points(0), axis_points(1), axis_points(2)],
+    )
+}
+
+/// Borrowed solver to be wrapped by other solvers
+struct IkSolverRef<'a, I>(&'a I);
+
+impl<T, I> InverseKinematicsSolver<T> for IkSolverRef<'_, I>
+where
+    T: RealField,
+    I: InverseKinematicsSolver<T>,
+{
+    fn solve_with_constraints(
+        &self,
+        arm: &k::SerialChain<T>,
+        target_pose: &na::Isometry3<T>,
+        constraints: &k::Constraints,
+    ) -> ::std::result::Result<(), k::Error> {
+        self.0.solve_with_constraints(arm, target_pose, constraints)
+    }
+}
+
+/// Check the poses which can be reached by the robot arm within the
+/// tolerances
+///
+/// This is the same as [`get_reachable_region`], except that `ik_solver` is
+/// reconfigured to regard the target as reached within `position_tolerance`
+/// (in meters) and `angle_tolerance` (in radians) during the scan, so that
+/// "reachable within 1mm" and "reachable within 5mm" maps can be generated
+/// from the same solver. The solved poses are also checked by
+/// [`ToleranceCheckedIkSolver`]. The original tolerances of `ik_solver` are
+/// restored before returning.
+#[allow(clippy::too_many_arguments)]
+pub fn get_reachable_region_with_tolerance<T, I>(
+    ik_solver: &mut I,
+    arm: &k::SerialChain<T>,
+    initial_pose: &na::Isometry3<T>,
+    constraints: &k::Constraints,
+    max_point: na::Vector3<T>,
+    min_point: na::Vector3<T>,
+    unit_check_length: T,
+    position_tolerance: T,
+    angle_tolerance: T,
+) -> Vec<na::Isometry3<T>>
+where
+    T: RealField + Copy + k::SubsetOf<f64> + Send + Sync,
+    I: TolerantIkSolver<T> + Send + Sync,
+{
+    let (original_position_tolerance, original_angle_tolerance) = ik_solver.tolerance();
+    ik_solver.set_tolerance(position_tolerance, angle_tolerance);
+    let region = get_reachable_region(
+        &ToleranceCheckedIkSolver::new(
+            IkSolverRef(&*ik_solver),
+            position_tolerance,
+            angle_tolerance,
+        ),
+        arm,
+        initial_pose,
+        constraints,
+        max_point,
+        min_point,
+        unit_check_length,
+    );
+    ik_solver.set_tolerance(original_position_tolerance, original_angle_tolerance);
+    region
+}
+
+/// Standard gravity used by [`gravity_torques`] in m/s^2
+pub const STANDARD_GRAVITY: f64 = 9.80665;
+
+/// Compute the joint torques (or forces for prismatic joints) required to hold
+/// the current joint positions of `arm` against gravity.
+///
+/// The masses and the centers of mass are taken from the inertial of the
+/// links, and `payload_mass` is added at the end of the arm. Gravity is along
+/// -z of the world frame. The result has the same order as
+/// `arm.joint_positions()`.
+pub fn gravity_torques<T>(arm: &k::SerialChain<T>, payload_mass: T) -> Vec<T>
+where
+    T: RealField + Copy + k::SubsetOf<f64>,
+{
+    arm.update_transforms();
+    let gravity = na::Vector3::new(T::zero(), T::zero(), -na::convert(STANDARD_GRAVITY));
+    // The mass and the center of mass in the world frame of each link
+    let mut masses = arm
+        .iter()
+        .map(|node| {
+            let link = node.link();
+            match (&*link, node.world_transform()) {
+                (Some(link), Some(transform)) => (
+                    link.inertial.mass,
+                    transform * na::Point3::from(link.inertial.origin().translation.vector),
+                ),
+                _ => (T::zero(), na::Point3::origin()),
+            }
+        })
+        .collect::<Vec<_>>();
+    masses.push((
+        payload_mass,
+        na::Point3::from(arm.end_transform().translation.vector),
+    ));
+
+    arm.iter()
+        .enumerate()
+        .filter(|(_, node)| node.joint().is_movable())
+        .map(|(i, node)| {
+            let transform = node
+                .world_transform()
+                .unwrap_or_else(na::Isometry3::identity);
+            let origin = na::Point3::from(transform.translation.vector);
+            // The links after the joint are moved by it.
+            masses[i..]
+                .iter()
+                .map(|(mass, com)| {
+                    let force = gravity * *mass;
+                    let torque = match &node.joint().joint_type {
+                        k::JointType::Rotational { axis } => (transform.rotation * **axis)
+                            .cross(&(com - origin))
+                            .dot(&force),
+                        k::JointType::Linear { axis } => (transform.rotation * **axis).dot(&force),
+                        k::JointType::Fixed => T::zero(),
+                    };
+                    // The torque to hold the joint cancels the one by gravity.
+                    -torque
+                })
+                .fold(T::zero(), |sum, torque| sum + torque)
+        })
+        .collect()
+}
+
+/// Solver which rejects the solutions exceeding the torque limits to hold the
+/// payload
+struct TorqueCheckedIkSolver<'a, T, I> {
+    solver: &'a I,
+    payload_mass: T,
+    torque_limits: &'a [T],
+}
+
+impl<T, I> InverseKinematicsSolver<T> for TorqueCheckedIkSolver<'_, T, I>
+where
+    T: RealField + Copy + SubsetOf<f64>,
+    I: InverseKinematicsSolver<T>,
+{
+    fn solve_with_constraints(
+        &self,
+        arm: &k::SerialChain<T>,
+        target_pose: &na::Isometry3<T>,
+        constraints: &k::Constraints,
+    ) -> ::std::result::Result<(), k::Error> {
+        self.solver
+            .solve_with_constraints(arm, target_pose, constraints)?;
+        let torques = gravity_torques(arm, self.payload_mass);
+        if let Some((joint, (torque, _))) = arm
+            .iter_joints()
+            .zip(torques.iter().zip(self.torque_limits))
+            .find(|(_, (torque, limit))| torque.abs() > **limit)
+        {
+            tracing::debug!(
+                "[TorqueCheckedIkSolver] Joint {} requires torque {} over the limit",
+                joint.name,
+                torque
+            );
+            return Err(k::Error::NotConvergedError {
+                num_tried: 0,
+                position_diff: na::Vector3::zeros(),
+                rotation_diff: na::Vector3::zeros(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Check the poses which can be reached by the robot arm holding a payload
+///
+/// This is the same as [`get_reachable_region`], except that a pose is
+/// reachable only if the static joint torques to hold the arm and a payload
+/// of `payload_mass` (kg) at the end, computed by [`gravity_torques`], are
+/// within `torque_limits` in absolute value.
+///
+/// Returns [`Error::DofMismatch`] if the lengths of `torque_limits` and the
+/// joints of `arm` are different.
+#[allow(clippy::too_many_arguments)]
+pub fn get_reachable_region_with_payload<T, I>(
+    ik_solver: &I,
+    arm: &k::SerialChain<T>,
+    initial_pose: &na::Isometry3<T>,
+    constraints: &k::Constraints,
+    max_point: na::Vector3<T>,
+    min_point: na::Vector3<T>,
+    unit_check_length: T,
+    payload_mass: T,
+    torque_limits: &[T],
+) -> Result<Vec<na::Isometry3<T>>>
+where
+    T: RealField + Copy + k::SubsetOf<f64> + Send + Sync,
+    I: InverseKinematicsSolver<T> + Send + Sync,
+{
+    if torque_limits.len() != arm.dof() {
+        return Err(Error::DofMismatch(torque_limits.len(), arm.dof()));
+    }
+    Ok(get_reachable_region(
+        &TorqueCheckedIkSolver {
+            solver: ik_solver,
+            payload_mass,
+            torque_limits,
+        },
+        arm,
+        initial_pose,
+        constraints,
+        max_point,
+        min_point,
+        unit_check_length,
+    ))
+}
+
+/// Order to check the points in [`get_reachable_region_with_order`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ScanOrder {
+    /// Along x first, then y, then z.
+    #[default]
+    Raster,
+    /// Nearest to the position of the initial pose first.
+    CenterOut,
+}
+
+/// Check the poses which can be reached by the robot arm, and return them in
+/// the specified order
+///
+/// The checked points and the result set are the same as [`get_reachable_region`].
+/// With [`ScanOrder::CenterOut`], the poses near the initial pose come first.
+#[allow(clippy::too_many_arguments)]
+pub fn get_reachable_region_with_order<T, I>(
+    ik_solver: &I,
+    arm: &k::SerialChain<T>,
+    initial_pose: &na::Isometry3<T>,
+    constraints: &k::Constraints,
+    max_point: na::Vector3<T>,
+    min_point: na::Vector3<T>,
+    unit_check_length: T,
+    order: ScanOrder,
+) -> Vec<na::Isometry3<T>>
+where
+    T: RealField + Copy + k::SubsetOf<f64> + Send + Sync,
+    I: InverseKinematicsSolver<T> + Send + Sync,
+{
+    let axis_points = |axis| axis_points_with_step(&min_point, &max_point, unit_check_length, axis);
+    let (x_points, y_points, z_points) = (axis_points(0), axis_points(1), axis_points(2));
+    let mut points = Vec::with_capacity(x_points.len() * y_points.len() * z_points.len());
+    for &z in &z_points {
+        for &y in &y_points {
+            for &x in &x_points {
+                points.push(na::Vector3::new(x, y, z));
+            }
+        }
+    }
+    match order {
+        ScanOrder::Raster => {}
+        ScanOrder::CenterOut => {
+            let center = initial_pose.translation.vector;
+            points.sort_by(|a, b| {
+                (a - center)
+                    .norm_squared()
+                    .partial_cmp(&(b - center).norm_squared())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+        }
+    }
+
+    let initial_angles = arm.joint_positions();
+    points
+        .par_iter()
+        .map_init(
+            || arm.clone(),
+            |arm, &point| {
+                let mut target_pose = *initial_pose;
+                target_pose.translation.vector = point;
+                arm.set_joint_positions_unchecked(&initial_angles);
+                ik_solver
+                    .solve_with_constraints(arm, &target_pose, constraints)
+                    .ok()
+                    .map(|()| target_pose)
+            },
+        )
+        .flatten()
+        .collect()
+}
+
+/// Points from `min_point` to `max_point` (exclusive) on the axis at every
+/// `unit_check_length`.
+fn axis_points_with_step<T>(
+    min_point: &na::Vector3<T>,
+    max_point: &na::Vector3<T>,
+    unit_check_length: T,
+    axis: usize,
+) -> Vec<T>
+where
+    T: RealField + Copy,
+{
+    let mut points = vec![];
+    let mut v = min_point[axis];
+    while v < max_point[axis] {
+        points.push(v);
+        v += unit_check_length;
+    }
+    points
+}
+
+/// Points from `min_point` to `max_point` (inclusive) on the axis at every
+/// `unit_check_length`.
+fn axis_points_inclusive<T>(
+    min_point: &na::Vector3<T>,
+    max_point: &na::Vector3<T>,
+    unit_check_length: T,
+    axis: usize,
+) -> Vec<T>
+where
+    T: RealField + Copy + k::SubsetOf<f64>,
+{
+    let range: f64 = na::convert((max_point[axis] - min_point[axis]) / unit_check_length);
+    if range < 0.0 {
+        return vec![];
+    }
+    // Tolerate the rounding errors of the range
+    let num_steps = (range + 1e-6).floor() as usize;
+    (0..=num_steps)
+        .map(|i| min_point[axis] + unit_check_length * na::convert(i as f64))
+        .collect()
+}
+
+/// Check the poses which can be reached by the robot arm, saving the progress
+/// to `state_path` so that an interrupted scan can be resumed.
+///
+/// This scans the same points as [`get_reachable_region`]. If `state_path`
+/// exists, the points already tested in it are skipped (and the points found
+/// reachable in it are included in the result). The state is saved after
+/// each layer along the z axis is scanned. An error is returned if the saved
+/// state was created with different `max_point`, `min_point` or
+/// `unit_check_length`.
+#[allow(clippy::too_many_arguments)]
+pub fn get_reachable_region_resume<T, I>(
+    state_path: impl AsRef<Path>,
+    ik_solver: &I,
+    arm: &k::SerialChain<T>,
+    initial_pose: &na::Isometry3<T>,
+    constraints: &k::Constraints,
+    max_point: na::Vector3<T>,
+    min_point: na::Vector3<T>,
+    unit_check_length: T,
+) -> Result<Vec<na::Isometry3<T>>>
+where
+    T: RealField + Copy + k::SubsetOf<f64> + Send + Sync,
+    I: InverseKinematicsSolver<T> + Send + Sync,
+{
+    let state_path = state_path.as_ref();
+    let to_array = |v: &na::Vector3<T>| -> [f64; 3] {
+        [na::convert(v[0]), na::convert(v[1]), na::convert(v[2])]
+    };
+    let (min_array, max_array) = (to_array(&min_point), to_array(&max_point));
+    let unit_f64: f64 = na::convert(unit_check_length);
+    let state = if state_path.exists() {
+        let state = ReachabilityScanState::load(state_path)?;
+        if !state.is_compatible(min_array, max_array, unit_f64) {
+            return Err(Error::Other {
+                error: format!(
+                    "{} was saved for a scan with different parameters",
+                    state_path.display()
+                ),
+            });
+        }
+        state
+    } else {
+        ReachabilityScanState::new(min_array, max_array, unit_f64)
+    };
+    let state = Mutex::new(state);
+
+    let axis_points = |axis| axis_points_with_step(&min_point, &max_point, unit_check_length, axis);
+    let (x_points, y_points, z_points) = (axis_points(0), axis_points(1), axis_points(2));
+    let initial_angles = arm.joint_positions();
+    let target_pose_at = |[ix, iy, iz]: SampleIndex| {
+        let mut target_pose = *initial_pose;
+        target_pose.translation.vector = na::Vector3::new(x_points[ix], y_points[iy], z_points[iz]);
+        target_pose
+    };
+
+    (0..z_points.len())
+        .collect::<Vec<_>>()
+        .par_iter()
+        .try_for_each(|&iz| {
+            let arm = arm.clone();
+            let mut results = vec![];
+            for iy in 0..y_points.len() {
+                for ix in 0..x_points.len() {
+                    let index = [ix, iy, iz];
+                    if state.lock().unwrap().is_tested(&index) {
+                        continue;
+                    }
+                    arm.set_joint_positions_unchecked(&initial_angles);
+                    let reachable = ik_solver
+                        .solve_with_constraints(&arm, &target_pose_at(index), constraints)
+                        .is_ok();
+                    results.push((index, reachable));
+                }
+            }
+            if results.is_empty() {
+                return Ok(());
+            }
+            let mut state = state.lock().unwrap();
+            for (index, reachable) in results {
+                state.insert(index, reachable);
+            }
+            state.save(state_path)
+        })?;
+
+    Ok(state
+        .into_inner()
+        .unwrap()
+        .reachable()
+        .map(|&index| target_pose_at(index))
+        .collect())
+}
+
+/// Check the poses which can be reached by the robot arm, sampling each axis
+/// separately.
+///
+/// For each axis, `Some(n)` samples `n` evenly spaced points from `min_point`
+/// to `max_point` (both inclusive; only `min_point` if `n == 1`), and `None`
+/// fixes the axis at the position of `initial_pose`. For example,
+/// `[Some(10), Some(10), None]` scans a horizontal slice at the height of
+/// `initial_pose`.
+pub fn get_reachable_region_with_samples<T, I>(
+    ik_solver: &I,
+    arm: &k::SerialChain<T>,
+    initial_pose: &na::Isometry3<T>,
+    constraints: &k::Constraints,
+    max_point: na::Vector3<T>,
+    min_point: na::Vector3<T>,
+    num_samples: [Option<usize>; 3],
+) -> Vec<na::Isometry3<T>>
+where
+    T: RealField + Copy + k::SubsetOf<f64> + Send + Sync,
+    I: InverseKinematicsSolver<T> + Send + Sync,
+{
+    let axis_points = |axis: usize| match num_samples[axis] {
+        Some(n) if n > 1 => {
+            let step = (max_point[axis] - min_point[axis]) / na::convert((n - 1) as f64);
+            (0..n)
+                .map(|i| min_point[axis] + step * na::convert(i as f64))
+                .collect()
+        }
+        Some(n) => vec![min_point[axis]; n],
+        None => vec![initial_pose.translation.vector[axis]],
+    };
+    solve_reachable_poses(
+        ik_solver,
+        arm,
+        initial_pose,
+        constraints,
+        [axis_points(0), axis_points(1), axis_points(2)],
+    )
+}
+
+/// Solve IK for the grid of positions given by `axis_points` (x, y and z) with
+/// the rotation of `initial_pose`, and return the solved poses.
+fn solve_reachable_poses<T, I>(
+    ik_solver: &I,
+    arm: &k::SerialChain<T>,
+    initial_pose: &na::Isometry3<T>,
+    constraints: &k::Constraints,
+    axis_points: [Vec<T>; 3],
+) -> Vec<na::Isometry3<T>>
+where
+    T: RealField + Copy + k::SubsetOf<f64> + Send + Sync,
+    I: InverseKinematicsSolver<T> + Send + Sync,
+{
+    let solved_poses = Mutex::new(Vec::new());
+    solve_reachable_poses_with_callback(
+        ik_solver,
+        arm,
+        initial_pose,
+        constraints,
+        axis_points,
+        |pose| solved_poses.lock().unwrap().push(pose),
+    );
+    solved_poses.into_inner().unwrap()
+}
+
+/// Solve IK for the grid of positions given by `axis_points` (x, y and z) with
+/// the rotation of `initial_pose`, and call `callback` with each solved pose.
+fn solve_reachable_poses_with_callback<T, I, F>(
+    ik_solver: &I,
+    arm: &k::SerialChain<T>,
+    initial_pose: &na::Isometry3<T>,
+    constraints: &k::Constraints,
+    axis_points: [Vec<T>; 3],
+    callback: F,
+) where
+    T: RealField + Copy + k::SubsetOf<f64> + Send + Sync,
+    I: InverseKinematicsSolver<T> + Send + Sync,
+    F: Fn(na::Isometry3<T>) + Send + Sync,
+{
+    let initial_angles = arm.joint_positions();
+    let target_pose = *initial_pose;
+    let [x_points, y_points, z_points] = axis_points;
+
+    z_points.par_iter().for_each(|&z| {
+        let arm = arm.clone();
+        let mut target_pose = target_pose;
+        target_pose.translation.vector[2] = z;
+        for &y in &y_points {
+            target_pose.translation.vector[1] = y;
+            for &x in &x_points {
+                target_pose.translation.vector[0] = x;
+                arm.set_joint_positions_unchecked(&initial_angles);
+                if ik_solver
+                    .solve_with_constraints(&arm, &target_pose, constraints)
+                    .is_ok()
+                {
+                    callback(target_pose);
+                }
+            }
+        }
+    });
+}
+
+/// Compute the fraction of `orientations` reachable at each of `positions`
+///
+/// For each position, IK is solved for the pose with each orientation from
+/// the current joint positions of `arm`, in parallel over the positions.
+/// Returns the pose at each position (with the identity rotation) and the
+/// fraction of the reachable orientations, in the order of `positions`. This
+/// is known as the reachability index, e.g., with `orientations` approaching
+/// from the directions of a sphere. The orientations are counted in the same
+/// way as [`get_reachable_region_with_orientations`].
+pub fn get_orientation_coverage<T, I>(
+    ik_solver: &I,
+    arm: &k::SerialChain<T>,
+    positions: &[na::Vector3<T>],
+    orientations: &[na::UnitQuaternion<T>],
+    constraints: &k::Constraints,
+) -> Vec<(na::Isometry3<T>, f32)>
+where
+    T: RealField + Copy + k::SubsetOf<f64> + Send + Sync,
+    I: InverseKinematicsSolver<T> + Send + Sync,
+{
+    let initial_angles = arm.joint_positions();
+    positions
+        .par_iter()
+        .map(|position| {
+            let num_reachable = count_reachable_orientations(
+                ik_solver,
+                &arm.clone(),
+                &initial_angles,
+                &na::Translation3::from(*position),
+                orientations,
+                constraints,
+            );
+            let coverage = if orientations.is_empty() {
+                0.0
+            } else {
+                num_reachable as f32 / orientations.len() as f32
+            };
+            (
+                na::Isometry3::translation(position.x, position.y, position.z),
+                coverage,
+            )
+        })
+        .collect()
+}
+
+/// Find the base placement from which the robot arm can reach the most targets
+///
+/// Each of `candidate_offsets` is the pose of the robot relative to its
+/// current placement, and `targets` are in the world frame of the current
+/// placement. For each candidate, IK is solved for every target from the
+/// current joint positions of `arm`, in parallel over the candidates. Returns
+/// the best candidate and the number of targets reachable from it (the
+/// earliest one if some candidates reach the same number of targets), or
+/// `None` if `candidate_offsets` is empty. The joint positions of `arm` are
+/// not changed.
+pub fn optimize_base_placement<T, I>(
+    ik_solver: &I,
+    arm: &k::SerialChain<T>,
+    targets: &[na::Isometry3<T>],
+    constraints: &k::Constraints,
+    candidate_offsets: &[na::Isometry3<T>],
+) -> Option<(na::Isometry3<T>, usize)>
+where
+    T: RealField + Copy + k::SubsetOf<f64> + Send + Sync,
+    I: InverseKinematicsSolver<T> + Send + Sync,
+{
+    let initial_angles = arm.joint_positions();
+    let counts: Vec<usize> = candidate_offsets
+        .par_iter()
+        .map_init(
+            || arm.clone(),
+            |arm, offset| {
+                let inverse_offset = offset.inverse();
+                targets
+                    .iter()
+                    .filter(|&target| {
+                        arm.set_joint_positions_unchecked(&initial_angles);
+                        ik_solver
+                            .solve_with_constraints(arm, &(inverse_offset * target), constraints)
+                            .is_ok()
+                    })
+                    .count()
+            },
+        )
+        .collect();
+    counts
+        .into_iter()
+        .zip(candidate_offsets)
+        .fold(None, |best, (count, offset)| match best {
+            Some((_, max)) if count <= max => best,
+            _ => Some((*offset, count)),
+        })
+}
+
+/// Interpolate poses from `start` to `end`
+///
+/// Translations are interpolated linearly and rotations by SLERP. The result
+/// has `n` poses including `start` and `end` (only `end` if `n == 1`).
+pub fn interpolate_cartesian<T>(
+    start: &na::Isometry3<T>,
+    end: &na::Isometry3<T>,
+    n: usize,
+) -> Vec<na::Isometry3<T>>
+where
+    T: RealField + Copy,
+{
+    if n == 1 {
+        return vec![*end];
+    }
+    (0..n)
+        .map(|i| {
+            let t = na::convert::<f64, T>(i as f64) / na::convert((n - 1) as f64);
+            na::Isometry3::from_parts(
+                start
+                    .translation
+                    .vector
+                    .lerp(&end.translation.vector, t)
+                    .into(),
+                start.rotation.slerp(&end.rotation, t),
+            )
+        })
+        .collect()
+}
+
+/// Solve IK for each pose of the path in order
+///
+/// Each pose is solved from the solution of the previous one, so that the
+/// joint positions change continuously along the path. Returns the joint
+/// positions for each pose. If any pose can't be solved, the joint positions
+/// of `arm` are restored and [`Error::UnreachableWaypoint`] with the index of
+/// the pose is returned.
+pub fn solve_path<T, I>(
+    ik_solver: &I,
+    arm: &k::SerialChain<T>,
+    poses: &[na::Isometry3<T>],
+    constraints: &k::Constraints,
+) -> Result<Vec<Vec<T>>>
+where
+    T: RealField + Copy + k::SubsetOf<f64>,
+    I: InverseKinematicsSolver<T>,
+{
+    let initial_angles = arm.joint_positions();
+    let mut path = Vec::with_capacity(poses.len());
+    for (index, pose) in poses.iter().enumerate() {
+        if let Err(source) = ik_solver.solve_with_constraints(arm, pose, constraints) {
+            arm.set_joint_positions_unchecked(&initial_angles);
+            return Err(Error::UnreachableWaypoint { index, source });
+        }
+        path.push(arm.joint_positions());
+    }
+    Ok(path)
+}
+
+/// Plan a joint path moving the end of `arm` along the straight line from
+/// `start` to `end`
+///
+/// The poses are interpolated by [`interpolate_cartesian`] so that the
+/// translation between consecutive poses is at most `max_cartesian_step` (in
+/// meters) and the rotation is at most `max_angular_step` (in radians), and
+/// solved in order from the current joint positions of `arm` by
+/// [`solve_path`]. The first pose is `start` itself, so `arm` should be near
+/// it. Returns the joint positions for each pose. If any pose can't be
+/// solved, the joint positions of `arm` are restored and
+/// [`Error::UnreachableWaypoint`] with the index of the pose is returned. If
+/// either step is not positive and finite, an error is returned.
+pub fn plan_cartesian_path<T, I>(
+    ik_solver: &I,
+    arm: &k::SerialChain<T>,
+    start: &na::Isometry3<T>,
+    end: &na::Isometry3<T>,
+    max_cartesian_step: T,
+    max_angular_step: T,
+    constraints: &k::Constraints,
+) -> Result<Vec<Vec<T>>>
+where
+    T: RealField + Copy + k::SubsetOf<f64>,
+    I: InverseKinematicsSolver<T>,
+{
+    let max_cartesian_step: f64 = na::convert(max_cartesian_step);
+    let max_angular_step: f64 = na::convert(max_angular_step);
+    if !(max_cartesian_step.is_finite() && max_cartesian_step > 0.0)
+        || !(max_angular_step.is_finite() && max_angular_step > 0.0)
+    {
+        return Err(Error::Other {
+            error: format!(
+                "Steps must be positive and finite: max_cartesian_step={max_cartesian_step}, max_angular_step={max_angular_step}"
+            ),
+        });
+    }
+    let translation: f64 = na::convert((end.translation.vector - start.translation.vector).norm());
+    let angle: f64 = na::convert(start.rotation.angle_to(&end.rotation));
+    let num_steps = (translation / max_cartesian_step)
+        .max(angle / max_angular_step)
+        .ceil();
+    let poses = interpolate_cartesian(start, end, num_steps as usize + 1);
+    solve_path(ik_solver, arm, &poses, constraints)
+}
+
+/// Solve IK for each pose of the path in order, keeping the solved prefix
+///
+/// This is the same as [`solve_path`], except that it stops at the first pose
+/// which can't be solved instead of returning an error. Returns the joint
+/// positions for the solved poses and the index of the first failed pose
+/// (`None` if all poses are solved). The joint positions of `arm` are set to
+/// the last solved ones, or restored if no pose is solved.
+pub fn solve_path_partial<T, I>(
+    ik_solver: &I,
+    arm: &k::SerialChain<T>,
+    poses: &[na::Isometry3<T>],
+    constraints: &k::Constraints,
+) -> (Vec<Vec<T>>, Option<usize>)
+where
+    T: RealField + Copy + k::SubsetOf<f64>,
+    I: InverseKinematicsSolver<T>,
+{
+    let mut last_angles = arm.joint_positions();
+    let mut path = Vec::with_capacity(poses.len());
+    for (i, pose) in poses.iter().enumerate() {
+        if ik_solver
+            .solve_with_constraints(arm, pose, constraints)
+            .is_err()
+        {
+            arm.set_joint_positions_unchecked(&last_angles);
+            return (path, Some(i));
+        }
+        last_angles = arm.joint_positions();
+        path.push(last_angles.clone());
+    }
+    (path, None)
+}
+
+/// Ratio of the range of a joint regarded as being at its limit by
+/// [`is_seed_feasible`]
+pub const SEED_LIMIT_MARGIN_RATIO: f64 = 0.01;
+
+/// Returns `true` if the current joint positions of `arm` are a good seed
+/// for solving IK with `constraints`.
+///
+/// A seed is not good if any joint which is not ignored by `constraints` is
+/// out of its limits, or within [`SEED_LIMIT_MARGIN_RATIO`] of its range
+/// from a limit, because the solvers can hardly leave such a position.
+pub fn is_seed_feasible<T>(arm: &k::SerialChain<T>, constraints: &k::Constraints) -> bool
+where
+    T: RealField + Copy + SubsetOf<f64>,
+{
+    joints_near_limits(arm, constraints).is_empty()
+}
+
+/// Returns the names of the joints not ignored by `constraints` which are out
+/// of their limits or within [`SEED_LIMIT_MARGIN_RATIO`] of their range from
+/// a limit.
+fn joints_near_limits<T>(arm: &k::SerialChain<T>, constraints: &k::Constraints) -> Vec<String>
+where
+    T: RealField + Copy + SubsetOf<f64>,
+{
+    let margin_ratio: T = na::convert(SEED_LIMIT_MARGIN_RATIO);
+    arm.iter_joints()
+        .filter(|joint| !constraints.ignored_joint_names.contains(&joint.name))
+        .filter(|joint| match (joint.limits, joint.joint_position()) {
+            (Some(range), Some(position)) => {
+                let margin = (range.max - range.min) * margin_ratio;
+                position <= range.min + margin || position >= range.max - margin
+            }
+            _ => false,
+        })
+        .map(|joint| joint.name.clone())
+        .collect()
+}
+
+/// Returns the indices of the joints of `arm` within `tolerance` of their
+/// limits at the current joint positions.
+///
+/// IK solutions with joints pinned at a limit are fragile for the subsequent
+/// motion. Joints without limits are never reported. See also
+/// [`RandomInitializeIkSolver::reject_joints_at_limits`].
+pub fn joints_at_limits<T>(arm: &k::SerialChain<T>, tolerance: T) -> Vec<usize>
+where
+    T: RealField + Copy,
+{
+    arm.iter_joints()
+        .enumerate()
+        .filter(|(_, joint)| match (joint.limits, joint.joint_position()) {
+            (Some(range), Some(position)) => {
+                position <= range.min + tolerance || position >= range.max - tolerance
+            }
+            _ => false,
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Returns `constraints` without the constraints on the rotation.
+pub fn position_only_constraints(constraints: &k::Constraints) -> k::Constraints {
+    k::Constraints {
+        rotation_x: false,
+        rotation_y: false,
+        rotation_z: false,
+        ..constraints.clone()
+    }
+}
+
+/// Returns `constraints` without the constraints on the position.
+pub fn orientation_only_constraints(constraints: &k::Constraints) -> k::Constraints {
+    k::Constraints {
+        position_x: false,
+        position_y: false,
+        position_z: false,
+        ..constraints.clone()
+    }
+}
+
+/// Reason why a pose can't be reached, returned by [`diagnose_unreachable`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UnreachableReason {
+    /// The position can't be reached even if the orientation is ignored.
+    OutOfReach,
+    /// The orientation can't be reached even if the position is ignored.
+    OrientationInfeasible,
+    /// The position and the orientation can be reached separately, but the
+    /// joints are at their limits when the position is reached.
+    JointLimited {
+        /// The joints at their limits
+        joint_names: Vec<String>,
+    },
+    /// The position and the orientation can be reached separately, but not
+    /// at the same time.
+    Conflicting,
+}
+
+/// Diagnose why `target_pose` can't be reached
+///
+/// Returns `None` if `target_pose` is reachable with `constraints`. Otherwise,
+/// the position-only and orientation-only sub-problems are solved to classify
+/// the failure. The joint positions of `arm` are not changed.
+pub fn diagnose_unreachable<T, I>(
+    ik_solver: &I,
+    arm: &k::SerialChain<T>,
+    target_pose: &na::Isometry3<T>,
+    constraints: &k::Constraints,
+) -> Option<UnreachableReason>
+where
+    T: RealField + Copy + SubsetOf<f64>,
+    I: InverseKinematicsSolver<T>,
+{
+    let initial_angles = arm.joint_positions();
+    let solve = |constraints: &k::Constraints| {
+        arm.set_joint_positions_unchecked(&initial_angles);
+        ik_solver
+            .solve_with_constraints(arm, target_pose, constraints)
+            .is_ok()
+    };
+    let has_position = constraints.position_x || constraints.position_y || constraints.position_z;
+    let has_rotation = constraints.rotation_x || constraints.rotation_y || constraints.rotation_z;
+
+    let reason = if solve(constraints) {
+        None
+    } else if has_position && !solve(&position_only_constraints(constraints)) {
+        Some(UnreachableReason::OutOfReach)
+    } else {
+        let position_solution = arm.joint_positions();
+        if has_rotation && !solve(&orientation_only_constraints(constraints)) {
+            Some(UnreachableReason::OrientationInfeasible)
+        } else {
+            let joint_names = if has_position {
+                arm.set_joint_positions_unchecked(&position_solution);
+                joints_near_limits(arm, constraints)
+            } else {
+                vec![]
+            };
+            if joint_names.is_empty() {
+                Some(UnreachableReason::Conflicting)
+            } else {
+                Some(UnreachableReason::JointLimited { joint_names })
+            }
+        }
+    };
+    arm.set_joint_positions_unchecked(&initial_angles);
+    reason
+}
+
+/// Number of bisections in [`min_orientation_tolerance_for_reach`]
+const ORIENTATION_TOLERANCE_BISECTIONS: usize = 16;
+
+/// Find the orientation tolerance needed to reach `target_pose`
+///
+/// First, the position of `target_pose` is solved ignoring the orientation,
+/// and `None` is returned if it can't be reached within `position_tolerance`.
+/// Then, the target orientation is rotated toward the orientation of that
+/// solution, and the smallest rotation (in radians) with which IK succeeds is
+/// searched by bisection. Returns `Some(0)` if `target_pose` is reachable as
+/// is. The result is a tolerance which suffices along this direction, not
+/// necessarily the smallest in all directions. The joint positions of `arm`
+/// are not changed.
+pub fn min_orientation_tolerance_for_reach<T, I>(
+    ik_solver: &I,
+    arm: &k::SerialChain<T>,
+    target_pose: &na::Isometry3<T>,
+    constraints: &k::Constraints,
+    position_tolerance: T,
+) -> Option<T>
+where
+    T: RealField + Copy + SubsetOf<f64>,
+    I: InverseKinematicsSolver<T>,
+{
+    let initial_angles = arm.joint_positions();
+    let solve = |pose: &na::Isometry3<T>, constraints: &k::Constraints| {
+        arm.set_joint_positions_unchecked(&initial_angles);
+        if ik_solver
+            .solve_with_constraints(arm, pose, constraints)
+            .is_err()
+        {
+            return false;
+        }
+        arm.update_transforms();
+        let (position_diff, _) = masked_pose_diff(&arm.end_transform(), target_pose, constraints);
+        position_diff.norm() <= position_tolerance
+    };
+
+    let result = if solve(target_pose, constraints) {
+        Some(T::zero())
+    } else if !solve(target_pose, &position_only_constraints(constraints)) {
+        None
+    } else {
+        let free_rotation = arm.end_transform().rotation;
+        let max_angle = target_pose.rotation.angle_to(&free_rotation);
+        let relaxed = |angle: T| {
+            let mut pose = *target_pose;
+            if max_angle > T::zero() {
+                pose.rotation = target_pose
+                    .rotation
+                    .slerp(&free_rotation, (angle / max_angle).min(T::one()));
+            }
+            pose
+        };
+        // The orientation of the position-only solution is reachable.
+        let (mut lower, mut upper) = (T::zero(), max_angle);
+        for _ in 0..ORIENTATION_TOLERANCE_BISECTIONS {
+            let middle = (lower + upper) / na::convert(2.0);
+            if solve(&relaxed(middle), constraints) {
+                upper = middle;
+            } else {
+                lower = middle;
+            }
+        }
+        Some(upper)
+    };
+    arm.set_joint_positions_unchecked(&initial_angles);
+    result
+}
+
+/// Suggest a seed to solve IK for `target_pose`
+///
+/// The seed starts from the center of the limits of each joint (the current
+/// position for a joint without limits). Then, each joint from the root is
+/// moved by a quarter of its range (`pi / 2` without limits) to either side
+/// if it brings the end of `arm` closer to the position of `target_pose`.
+/// The returned seed is within the joint limits. The joint positions of
+/// `arm` are not changed; set the seed before solving if it is preferred.
+pub fn suggest_seed<T>(arm: &k::SerialChain<T>, target_pose: &na::Isometry3<T>) -> Vec<T>
+where
+    T: RealField + Copy + SubsetOf<f64>,
+{
+    let initial_angles = arm.joint_positions();
+    let limits = arm.iter_joints().map(|j| j.limits).collect::<Vec<_>>();
+    let mut seed = limits
+        .iter()
+        .zip(&initial_angles)
+        .map(|(limit, &current)| match limit {
+            Some(range) => (range.min + range.max) * na::convert(0.5),
+            None => current,
+        })
+        .collect::<Vec<_>>();
+    let distance = |positions: &[T]| {
+        arm.set_joint_positions_unchecked(positions);
+        (arm.end_transform().translation.vector - target_pose.translation.vector).norm()
+    };
+    let mut best = distance(&seed);
+    for (i, limit) in limits.iter().enumerate() {
+        let step = match limit {
+            Some(range) => (range.max - range.min) * na::convert(0.25),
+            None => T::frac_pi_2(),
+        };
+        let center = seed[i];
+        for candidate in [center - step, center + step] {
+            let previous = seed[i];
+            seed[i] = candidate;
+            let d = distance(&seed);
+            if d < best {
+                best = d;
+            } else {
+                seed[i] = previous;
+            }
+        }
+    }
+    arm.set_joint_positions_unchecked(&initial_angles);
+    seed
+}
+
+/// Compute the joint velocities which realize the end-effector velocity `twist`
+///
+/// `twist` is `[vx, vy, vz, wx, wy, wz]` in the world frame. The damped least-squares
+/// pseudo-inverse of the Jacobian is used; larger `damping` is more robust near
+/// singularities but less accurate. The velocity of a joint at its limit is set to
+/// zero if it would move the joint out of the limit.
+pub fn cartesian_velocity_to_joint_velocity<T>(
+    arm: &k::SerialChain<T>,
+    twist: na::Vector6<T>,
+    damping: T,
+) -> Result<Vec<T>>
+where
+    T: RealField + Copy + k::SubsetOf<f64>,
+{
+    arm.update_transforms();
+    let jacobian = k::jacobian(arm);
+    let jjt = &jacobian * jacobian.transpose() + na::Matrix6::identity() * (damping * damping);
+    let inverse = jjt.try_inverse().ok_or_else(|| Error::Other {
+        error: "Jacobian is singular, use positive damping".to_owned(),
+    })?;
+    let velocities = jacobian.transpose() * (inverse * twist);
+    Ok(arm
+        .iter_joints()
+        .zip(arm.joint_positions())
+        .zip(velocities.iter())
+        .map(|((joint, position), &velocity)| match joint.limits {
+            Some(range)
+                if (position >= range.max && velocity > T::zero())
+                    || (position <= range.min && velocity < T::zero()) =>
+            {
+                T::zero()
+            }
+            _ => velocity,
+        })
+        .collect())
+}
+
+/// Secondary objective of [`PrioritizedIkSolver`] to keep the orientation of a
+/// link (e.g., keep a camera level)
+#[derive(Debug, Clone)]
+pub struct OrientationObjective<T>
+where
+    T: RealField,
+{
+    /// Name of the joint whose child link is kept
+    pub joint_name: String,
+    /// Target orientation of the link in the world frame
+    pub rotation: na::UnitQuaternion<T>,
+    /// Keep the rotation around the x axis of the world frame
+    pub rotation_x: bool,
+    /// Keep the rotation around the y axis of the world frame
+    pub rotation_y: bool,
+    /// Keep the rotation around the z axis of the world frame
+    pub rotation_z: bool,
+}
+
+/// Task-priority IK solver
+///
+/// The target pose (with the constraints) is the primary task, and the
+/// optional secondary objective is solved in the nullspace of the primary
+/// task, so it never disturbs the primary task. This is useful for
+/// redundant arms, or when some axes of the target are not constrained.
+/// Solving succeeds if the primary task is achieved; the secondary objective
+/// is achieved as much as possible.
+#[derive(Debug, Clone)]
+pub struct PrioritizedIkSolver<T>
+where
+    T: RealField,
+{
+    /// The distance (in meters) of the primary task regarded as reached
+    pub allowable_target_distance: T,
+    /// The angle (in radians) of the primary task regarded as reached
+    pub allowable_target_angle: T,
+    /// The max number of iterations
+    pub num_max_try: usize,
+    /// The damping factor of the pseudo-inverses
+    pub damping: T,
+    /// The secondary objective
+    pub secondary: Option<OrientationObjective<T>>,
+}
+
+impl<T> PrioritizedIkSolver<T>
+where
+    T: RealField + Copy + k::SubsetOf<f64>,
+{
+    pub fn new(
+        allowable_target_distance: T,
+        num_max_try: usize,
+        secondary: Option<OrientationObjective<T>>,
+    ) -> Self {
+        Self {
+            allowable_target_distance,
+            allowable_target_angle: na::convert(0.005),
+            num_max_try,
+            damping: na::convert(0.01),
+            secondary,
+        }
+    }
+
+    /// Set the angle (in radians) of the primary task regarded as reached
+    pub fn allowable_target_angle(mut self, allowable_target_angle: T) -> Self {
+        self.allowable_target_angle = allowable_target_angle;
+        self
+    }
+
+    /// Set the damping factor of the pseudo-inverses
+    pub fn damping(mut self, damping: T) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    fn pseudo_inverse(matrix: &na::DMatrix<T>, damping: T) -> na::DMatrix<T> {
+        let rows = matrix.nrows();
+        let damped =
+            matrix * matrix.transpose() + na::DMatrix::identity(rows, rows) * (damping * damping);
+        match damped.try_inverse() {
+            Some(inverse) => matrix.transpose() * inverse,
+            None => na::DMatrix::zeros(matrix.ncols(), rows),
+        }
+    }
+
+    /// Returns the Jacobian of the orientation of the secondary link w.r.t.
+    /// the joints of `arm`, and the orientation error.
+    fn secondary_task(
+        &self,
+        arm: &k::SerialChain<T>,
+        secondary: &OrientationObjective<T>,
+    ) -> std::result::Result<(na::DMatrix<T>, na::DVector<T>), k::Error> {
+        let node = arm
+            .iter()
+            .find(|node| node.joint().name == secondary.joint_name)
+            .ok_or_else(|| k::Error::InvalidJointNameError {
+                joint_name: secondary.joint_name.clone(),
+            })?;
+        let sub_chain = k::SerialChain::from_end(node);
+        let sub_jacobian = k::jacobian(&sub_chain);
+        let joint_names = arm
+            .iter_joints()
+            .map(|joint| joint.name.clone())
+            .collect::<Vec<_>>();
+        let mut jacobian = na::DMatrix::zeros(3, joint_names.len());
+        for (sub_index, joint) in sub_chain.iter_joints().enumerate() {
+            if let Some(index) = joint_names.iter().position(|name| *name == joint.name) {
+                for row in 0..3 {
+                    jacobian[(row, index)] = sub_jacobian[(row + 3, sub_index)];
+                }
+            }
+        }
+        let current = node.world_transform().unwrap();
+        let error = (secondary.rotation * current.rotation.inverse()).scaled_axis();
+        let mask = [
+            secondary.rotation_x,
+            secondary.rotation_y,
+            secondary.rotation_z,
+        ];
+        let rows = (0..3).filter(|&i| mask[i]).collect::<Vec<_>>();
+        Ok((
+            na::DMatrix::from_fn(rows.len(), jacobian.ncols(), |r, c| jacobian[(rows[r], c)]),
+            na::DVector::from_fn(rows.len(), |r, _| error[rows[r]]),
+        ))
+    }
+}
+
+impl<T> InverseKinematicsSolver<T> for PrioritizedIkSolver<T>
+where
+    T: RealField + Copy + k::SubsetOf<f64>,
+{
+    fn solve_with_constraints(
+        &self,
+        arm: &k::SerialChain<T>,
+        target_pose: &na::Isometry3<T>,
+        constraints: &k::Constraints,
+    ) -> ::std::result::Result<(), k::Error> {
+        self.solve_with_damping(arm, target_pose, constraints, self.damping)
+    }
+}
+
+impl<T> PrioritizedIkSolver<T>
+where
+    T: RealField + Copy + k::SubsetOf<f64>,
+{
+    /// Solve with `damping` instead of the damping factor of this solver
+    fn solve_with_damping(
+        &self,
+        arm: &k::SerialChain<T>,
+        target_pose: &na::Isometry3<T>,
+        constraints: &k::Constraints,
+        damping: T,
+    ) -> ::std::result::Result<(), k::Error> {
+        let _span = ik_solve_span("PrioritizedIkSolver", target_pose).entered();
+        let initial_angles = arm.joint_positions();
+        let limits = arm.iter_joints().map(|j| j.limits).collect::<Vec<_>>();
+        let ignored = arm
+            .iter_joints()
+            .map(|joint| constraints.ignored_joint_names.contains(&joint.name))
+            .collect::<Vec<_>>();
+        let mask = [
+            constraints.position_x,
+            constraints.position_y,
+            constraints.position_z,
+            constraints.rotation_x,
+            constraints.rotation_y,
+            constraints.rotation_z,
+        ];
+        let rows = (0..6).filter(|&i| mask[i]).collect::<Vec<_>>();
+        let dof = initial_angles.len();
+        let reached = |position_diff: &na::Vector3<T>, rotation_diff: &na::Vector3<T>| {
+            position_diff.norm() < self.allowable_target_distance
+                && rotation_diff.norm() < self.allowable_target_angle
+        };
+
+        for _ in 0..self.num_max_try {
+            arm.update_transforms();
+            let (position_diff, rotation_diff) =
+                masked_pose_diff(&arm.end_transform(), target_pose, constraints);
+            let error = na::DVector::from_fn(rows.len(), |r, _| {
+                let i = rows[r];
+                if i < 3 {
+                    position_diff[i]
+                } else {
+                    rotation_diff[i - 3]
+                }
+            });
+            let jacobian = k::jacobian(arm);
+            let primary_jacobian = na::DMatrix::from_fn(rows.len(), dof, |r, c| {
+                if ignored[c] {
+                    T::zero()
+                } else {
+                    jacobian[(rows[r], c)]
+                }
+            });
+            let primary_inverse = Self::pseudo_inverse(&primary_jacobian, damping);
+            let mut dq = &primary_inverse * &error;
+            if let Some(secondary) = &self.secondary {
+                let (mut secondary_jacobian, secondary_error) =
+                    self.secondary_task(arm, secondary)?;
+                for (c, &ignored) in ignored.iter().enumerate() {
+                    if ignored {
+                        secondary_jacobian.column_mut(c).fill(T::zero());
+                    }
+                }
+                let nullspace =
+                    na::DMatrix::identity(dof, dof) - &primary_inverse * &primary_jacobian;
+                let projected = &secondary_jacobian * &nullspace;
+                dq += &nullspace
+                    * Self::pseudo_inverse(&projected, damping)
+                    * (secondary_error - &secondary_jacobian * &dq);
+            }
+            if reached(&position_diff, &rotation_diff) && dq.norm() < self.allowable_target_distance
+            {
+                return Ok(());
+            }
+            let positions = arm
+                .joint_positions()
+                .iter()
+                .zip(dq.iter())
+                .zip(&limits)
+                .map(|((&q, &dq), limit)| match limit {
+                    Some(range) => (q + dq).clamp(range.min, range.max),
+                    None => q + dq,
+                })
+                .collect::<Vec<_>>();
+            arm.set_joint_positions_unchecked(&positions);
+        }
+        arm.update_transforms();
+        let (position_diff, rotation_diff) =
+            masked_pose_diff(&arm.end_transform(), target_pose, constraints);
+        if reached(&position_diff, &rotation_diff) {
+            return Ok(());
+        }
+        arm.set_joint_positions_unchecked(&initial_angles);
+        Err(k::Error::NotConvergedError {
+            num_tried: self.num_max_try,
+            position_diff: position_diff.map(na::convert),
+            rotation_diff: rotation_diff.map(na::convert),
+        })
+    }
+}
+
+/// Optimize a secondary objective in the null space of the pose after solving
+/// it by the inner solver
+///
+/// After `solver` reaches the target pose, the joints are moved along the
+/// negative gradient of `objective` (scaled by `gain`) projected onto the null
+/// space of the Jacobian of the constrained axes, so that redundant arms
+/// (e.g., 7-DOF arms, or 6-DOF arms with position-only constraints) choose a
+/// solution minimizing `objective`, e.g., [`joint_center_objective`]. The
+/// primary task takes priority: a step which moves the end more than
+/// `allowable_target_distance` from the target is discarded, and the
+/// optimization stops there.
+pub struct NullspaceIkSolver<T, I, F>
+where
+    T: RealField,
+    I: InverseKinematicsSolver<T>,
+    F: Fn(&[T]) -> T,
+{
+    /// The IK solver for the primary task
+    pub solver: I,
+    /// The gain of the gradient of the secondary objective
+    pub gain: T,
+    /// The max number of iterations of the optimization
+    pub num_max_try: usize,
+    /// The distance of the primary task regarded as kept
+    pub allowable_target_distance: T,
+    /// The damping factor of the pseudo-inverse
+    pub damping: T,
+    objective: F,
+}
+
+impl<T, I, F> std::fmt::Debug for NullspaceIkSolver<T, I, F>
+where
+    T: RealField,
+    I: InverseKinematicsSolver<T> + std::fmt::Debug,
+    F: Fn(&[T]) -> T,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NullspaceIkSolver")
+            .field("solver", &self.solver)
+            .field("gain", &self.gain)
+            .field("num_max_try", &self.num_max_try)
+            .field("allowable_target_distance", &self.allowable_target_distance)
+            .field("damping", &self.damping)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, I, F> NullspaceIkSolver<T, I, F>
+where
+    T: RealField + Copy + k::SubsetOf<f64>,
+    I: InverseKinematicsSolver<T>,
+    F: Fn(&[T]) -> T,
+{
+    /// Create a solver minimizing `objective` of the joint positions
+    pub fn new(solver: I, gain: T, objective: F) -> Self {
+        Self {
+            solver,
+            gain,
+            num_max_try: 100,
+            allowable_target_distance: na::convert(0.001),
+            damping: na::convert(0.01),
+            objective,
+        }
+    }
+
+    /// Numerical gradient of the objective at `positions`
+    fn objective_gradient(&self, positions: &[T]) -> na::DVector<T> {
+        let h: T = na::convert(1e-6);
+        let mut shifted = positions.to_vec();
+        na::DVector::from_fn(positions.len(), |i, _| {
+            shifted[i] = positions[i] + h;
+            let upper = (self.objective)(&shifted);
+            shifted[i] = positions[i] - h;
+            let lower = (self.objective)(&shifted);
+            shifted[i] = positions[i];
+            (upper - lower) / (h + h)
+        })
+    }
+}
+
+impl<T, I, F> InverseKinematicsSolver<T> for NullspaceIkSolver<T, I, F>
+where
+    T: RealField + Copy + k::SubsetOf<f64>,
+    I: InverseKinematicsSolver<T>,
+    F: Fn(&[T]) -> T,
+{
+    fn solve_with_constraints(
+        &self,
+        arm: &k::SerialChain<T>,
+        target_pose: &na::Isometry3<T>,
+        constraints: &k::Constraints,
+    ) -> ::std::result::Result<(), k::Error> {
+        let _span = ik_solve_span("NullspaceIkSolver", target_pose).entered();
+        self.solver
+            .solve_with_constraints(arm, target_pose, constraints)?;
+        let limits = arm.iter_joints().map(|j| j.limits).collect::<Vec<_>>();
+        let ignored = arm
+            .iter_joints()
+            .map(|joint| constraints.ignored_joint_names.contains(&joint.name))
+            .collect::<Vec<_>>();
+        let mask = [
+            constraints.position_x,
+            constraints.position_y,
+            constraints.position_z,
+            constraints.rotation_x,
+            constraints.rotation_y,
+            constraints.rotation_z,
+        ];
+        let rows = (0..6).filter(|&i| mask[i]).collect::<Vec<_>>();
+        let dof = limits.len();
+        let pose_error = |arm: &k::SerialChain<T>| {
+            arm.update_transforms();
+            let (position_diff, rotation_diff) =
+                masked_pose_diff(&arm.end_transform(), target_pose, constraints);
+            na::DVector::from_fn(rows.len(), |r, _| {
+                let i = rows[r];
+                if i < 3 {
+                    position_diff[i]
+                } else {
+                    rotation_diff[i - 3]
+                }
+            })
+        };
+
+        for _ in 0..self.num_max_try {
+            let positions = arm.joint_positions();
+            let error = pose_error(arm);
+            let jacobian = k::jacobian(arm);
+            let primary_jacobian = na::DMatrix::from_fn(rows.len(), dof, |r, c| {
+                if ignored[c] {
+                    T::zero()
+                } else {
+                    jacobian[(rows[r], c)]
+                }
+            });
+            let primary_inverse =
+                PrioritizedIkSolver::<T>::pseudo_inverse(&primary_jacobian, self.damping);
+            let mut nullspace =
+                na::DMatrix::identity(dof, dof) - &primary_inverse * &primary_jacobian;
+            for (c, &ignored) in ignored.iter().enumerate() {
+                if ignored {
+                    nullspace.row_mut(c).fill(T::zero());
+                }
+            }
+            let secondary = &nullspace * self.objective_gradient(&positions) * -self.gain;
+            if secondary.norm() < na::convert(1e-9) {
+                break;
+            }
+            // correct the drift of the primary task caused by the linearization
+            let dq = secondary + &primary_inverse * error;
+            let new_positions = positions
+                .iter()
+                .zip(dq.iter())
+                .zip(&limits)
+                .map(|((&q, &dq), limit)| match limit {
+                    Some(range) => (q + dq).clamp(range.min, range.max),
+                    None => q + dq,
+                })
+                .collect::<Vec<_>>();
+            arm.set_joint_positions_unchecked(&new_positions);
+            if pose_error(arm).norm() > self.allowable_target_distance
+                || (self.objective)(&new_positions) >= (self.objective)(&positions)
+            {
+                arm.set_joint_positions_unchecked(&positions);
+                break;
+            }
+        }
+        arm.update_transforms();
+        Ok(())
+    }
+}
+
+/// Returns the objective of [`NullspaceIkSolver`] to keep the joints of `arm`
+/// near the centers of their limits
+///
+/// The objective is the sum of the squared distances of the joint positions
+/// from the centers, normalized by the half ranges. Joints without limits
+/// don't contribute to it.
+pub fn joint_center_objective<T>(arm: &k::SerialChain<T>) -> impl Fn(&[T]) -> T + Send + Sync
+where
+    T: RealField + Copy,
+{
+    let limits = arm.iter_joints().map(|j| j.limits).collect::<Vec<_>>();
+    move |positions: &[T]| {
+        positions
+            .iter()
+            .zip(&limits)
+            .fold(T::zero(), |sum, (&q, limit)| match limit {
+                Some(range) => {
+                    let half_range = (range.max - range.min) * na::convert(0.5);
+                    let d = (q - (range.min + range.max) * na::convert(0.5)) / half_range;
+                    sum + d * d
+                }
+                None => sum,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The left arm of `sample.urdf` at the initial joint positions of the tests
+    fn arm<T>() -> k::SerialChain<T>
+    where
+        T: RealField + Copy + SubsetOf<f64>,
+    {
+        let robot = k::Chain::<T>::from_urdf_file("sample.urdf").unwrap();
+        let arm = k::SerialChain::from_end(robot.find("l_tool_fixed").unwrap());
+        let initial = [0.2, 0.2, 0.0, -1.0, 0.0, 0.0].map(na::convert);
+        arm.set_joint_positions(&initial).unwrap();
+        arm
+    }
+
+    /// The pose of the end of `arm` at the current joint positions
+    fn target<T>(arm: &k::SerialChain<T>) -> na::Isometry3<T>
+    where
+        T: RealField + Copy + SubsetOf<f64>,
+    {
+        arm.update_transforms();
+        arm.end_transform()
+    }
+
+    /// The constraints on the position only
+    fn position_constraints() -> k::Constraints {
+        position_only_constraints(&k::Constraints::default())
+    }
+
+    #[test]
+    fn get_region() {
+        let robot = k::Chain::<f32>::from_urdf_file("sample.urdf").unwrap();
+        let target_link = robot.find("l_tool_fixed").unwrap();
+        let chain = k::SerialChain::from_end(target_link);
+
+        // Set initial joint angles
+        let angles = vec![0.2, 0.2, 0.0, -1.0, 0.0, 0.0];
+
+        chain.set_joint_positions(&angles).unwrap();
+        println!("initial angles={:?}", chain.joint_positions());
+
+        // Get the transform of the end of the manipulator (forward kinematics)
+        chain.update_transforms();
+        let target = target_link.world_transform().unwrap();
+        println!("{:?}", target.translation);
+        // Create IK solver with default settings
+        let solver = k::JacobianIkSolver::default();
+
+        // Create a set of joints from end joint
+        let arm = k::SerialChain::from_end(target_link);
+        let regions = get_reachable_region(
+            &solver,
+            &arm,
+            &target,
+            &k::Constraints::default(),
+            na::Vector3::new(0.8, 0.9, 0.9),
+            na::Vector3::new(0.0, -0.9, 0.0),
+            0.1,
+        );
+        assert_eq!(regions.len(), 114);
+
+        // including the boundary of max_point
+        let inclusive = get_reachable_region_inclusive(
+            &solver,
+            &arm,
+            &target,
+            &k::Constraints::default(),
+            na::Vector3::new(0.8, 0.9, 0.9),
+            na::Vector3::new(0.0, -0.9, 0.0),
+            0.1,
+        );
+        assert!(inclusive.len() > regions.len());
+        assert!(inclusive
+            .iter()
+            .any(|pose| (pose.translation.vector.x - 0.8).abs() < 1e-4));
+
+        // streaming the same poses
+        let count = std::sync::atomic::AtomicUsize::new(0);
+        get_reachable_region_with_callback(
+            &solver,
+            &arm,
+            &target,
+            &k::Constraints::default(),
+            na::Vector3::new(0.8, 0.9, 0.9),
+            na::Vector3::new(0.0, -0.9, 0.0),
+            0.1,
+            |pose| {
+                assert!(regions.contains(&pose));
+                count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            },
+        );
+        assert_eq!(count.into_inner(), regions.len());
+    }
+
+    #[test]
+    fn get_region_with_orientations() {
+        let arm = arm::<f64>();
+        let target = arm.end_transform();
+        let solver = k::JacobianIkSolver::default();
+        let max_point = target.translation.vector + na::Vector3::new(0.15, 0.15, 0.15);
+        let min_point = target.translation.vector - na::Vector3::new(0.1, 0.1, 0.1);
+
+        // the same as get_reachable_region with the rotation of the target
+        let regions = get_reachable_region(
+            &solver,
+            &arm,
+            &target,
+            &k::Constraints::default(),
+            max_point,
+            min_point,
+            0.1,
+        );
+        let counts = get_reachable_region_with_orientations(
+            &solver,
+            &arm,
+            &target,
+            &k::Constraints::default(),
+            max_point,
+            min_point,
+            0.1,
+            &[target.rotation],
+        );
+        assert!(!counts.is_empty());
+        assert_eq!(counts.len(), regions.len());
+        assert!(counts
+            .iter()
+            .all(|(pose, count)| *count == 1 && regions.contains(pose)));
+
+        let rotated = target.rotation * na::UnitQuaternion::from_euler_angles(0.0, 0.0, 0.01);
+        let counts = get_reachable_region_with_orientations(
+            &solver,
+            &arm,
+            &target,
+            &k::Constraints::default(),
+            max_point,
+            min_point,
+            0.1,
+            &[target.rotation, rotated],
+        );
+        assert!(counts.iter().all(|(_, count)| (1..=2).contains(count)));
+        assert!(counts.iter().any(|(_, count)| *count == 2));
+
+        // consistent with get_orientation_coverage
+        let positions = counts
+            .iter()
+            .map(|(pose, _)| pose.translation.vector)
+            .collect::<Vec<_>>();
+        let coverage = get_orientation_coverage(
+            &solver,
+            &arm,
+            &positions,
+            &[target.rotation, rotated],
+            &k::Constraints::default(),
+        );
+        for ((_, count), (_, fraction)) in counts.iter().zip(&coverage) {
+            assert_eq!(*fraction, *count as f32 / 2.0);
+        }
+    }
+
+    #[test]
+    fn get_region_with_mimics() {
+        let arm = arm::<f64>();
+        // l_wrist_yaw follows l_shoulder_roll.
+        let mimics = vec![JointMimic {
+            joint_name: "l_wrist_yaw".to_owned(),
+            source_joint_name: "l_shoulder_roll".to_owned(),
+            multiplier: 1.0,
+            offset: 0.0,
+        }];
+        apply_mimic_joints(&arm, &mimics);
+        let target = target(&arm);
+        let solver = k::JacobianIkSolver::default();
+        let constraints = position_constraints();
+        let max_point = target.translation.vector + na::Vector3::new(0.15, 0.15, 0.15);
+        let min_point = target.translation.vector - na::Vector3::new(0.1, 0.1, 0.1);
+        let region = |mimics: &[JointMimic]| {
+            get_reachable_region_with_mimics(
+                &solver,
+                &arm,
+                &target,
+                &constraints,
+                max_point,
+                min_point,
+                0.1,
+                mimics,
+            )
+        };
+        // the cells around the target are reachable keeping the mimic joints
+        assert!(!region(&mimics).is_empty());
+        assert_eq!(
+            region(&[]).len(),
+            get_reachable_region(
+                &solver,
+                &arm,
+                &target,
+                &constraints,
+                max_point,
+                min_point,
+                0.1,
+            )
+            .len()
+        );
+    }
+
+    #[test]
+    fn axis_points() {
+        let min_point = na::Vector3::new(0.0, -0.9, 0.0);
+        let max_point = na::Vector3::new(0.8, 0.9, -0.1);
+        let points = axis_points_inclusive(&min_point, &max_point, 0.1, 0);
+        assert_eq!(points.len(), 9);
+        assert!((points[8] - 0.8).abs() < 1e-9);
+        let points = axis_points_inclusive(&min_point, &max_point, 0.1, 1);
+        assert_eq!(points.len(), 19);
+        assert!((points[18] - 0.9).abs() < 1e-9);
+        assert!(axis_points_inclusive(&min_point, &max_point, 0.1, 2).is_empty());
+    }
+
+    #[test]
+    fn base_placement() {
+        let arm = arm::<f64>();
+        let initial = arm.joint_positions();
+        let mut targets = vec![target(&arm)];
+        for angles in [
+            [0.3, 0.1, 0.1, -0.9, 0.1, 0.0],
+            [0.1, 0.3, -0.1, -1.1, 0.0, 0.1],
+        ] {
+            arm.set_joint_positions(&angles).unwrap();
+            targets.push(target(&arm));
+        }
+        arm.set_joint_positions(&initial).unwrap();
+
+        let solver = k::JacobianIkSolver::default();
+        let candidates = [
+            na::Isometry3::translation(10.0, 0.0, 0.0),
+            na::Isometry3::identity(),
+            na::Isometry3::translation(0.0, 10.0, 0.0),
+        ];
+        let (best, count) = optimize_base_placement(
+            &solver,
+            &arm,
+            &targets,
+            &k::Constraints::default(),
+            &candidates,
+        )
+        .unwrap();
+        assert_eq!(best, candidates[1]);
+        assert_eq!(count, targets.len());
+        assert_eq!(arm.joint_positions(), initial);
+
+        let (best, count) = optimize_base_placement(
+            &solver,
+            &arm,
+            &targets,
+            &k::Constraints::default(),
+            &candidates[..1],
+        )
+        .unwrap();
+        assert_eq!(best, candidates[0]);
+        assert_eq!(count, 0);
+
+        assert!(
+            optimize_base_placement(&solver, &arm, &targets, &k::Constraints::default(), &[])
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn get_region_with_tolerance() {
+        let arm = arm::<f32>();
+        let target = target(&arm);
+        let mut solver = k::JacobianIkSolver::default();
+        let original_tolerance = solver.tolerance();
+        let max_point = na::Vector3::new(0.8, 0.9, 0.9);
+        let min_point = na::Vector3::new(0.0, -0.9, 0.0);
+        let mut region = |position_tolerance, angle_tolerance| {
+            get_reachable_region_with_tolerance(
+                &mut solver,
+                &arm,
+                &target,
+                &k::Constraints::default(),
+                max_point,
+                min_point,
+                0.1,
+                position_tolerance,
+                angle_tolerance,
+            )
+            .len()
+        };
+        let loose = region(0.01, 0.05);
+        let strict = region(0.00001, 0.00001);
+        assert_eq!(solver.tolerance(), original_tolerance);
+        let default = get_reachable_region(
+            &solver,
+            &arm,
+            &target,
+            &k::Constraints::default(),
+            max_point,
+            min_point,
+            0.1,
+        )
+        .len();
+        assert_eq!(default, 114);
+        // the inner solver stops at the looser tolerance
+        assert!(loose >= default);
+        assert!(strict < default);
+    }
+
+    #[test]
+    fn get_region_with_payload() {
+        let arm = arm::<f64>();
+        let target = target(&arm);
+
+        let torques = gravity_torques(&arm, 0.0);
+        assert_eq!(torques.len(), arm.dof());
+        let heavy_torques = gravity_torques(&arm, 10.0);
+        assert!(heavy_torques
+            .iter()
+            .zip(&torques)
+            .any(|(heavy, light)| (heavy - light).abs() > 1.0));
+
+        let solver = k::JacobianIkSolver::default();
+        let region = |payload_mass, torque_limit| {
+            get_reachable_region_with_payload(
+                &solver,
+                &arm,
+                &target,
+                &k::Constraints::default(),
+                na::Vector3::new(0.8, 0.9, 0.9),
+                na::Vector3::new(0.0, -0.9, 0.0),
+                0.1,
+                payload_mass,
+                &[torque_limit; 6],
+            )
+            .unwrap()
+            .len()
+        };
+        assert_eq!(region(0.0, f64::MAX), 114);
+        let light = region(1.0, 20.0);
+        let heavy = region(5.0, 20.0);
+        assert!(heavy <= light);
+        assert!(heavy < 114);
+
+        assert!(matches!(
+            get_reachable_region_with_payload(
+                &solver,
+                &arm,
+                &target,
+                &k::Constraints::default(),
+                na::Vector3::new(0.8, 0.9, 0.9),
+                na::Vector3::new(0.0, -0.9, 0.0),
+                0.1,
+                1.0,
+                &[20.0; 5],
+            ),
+            Err(Error::DofMismatch(5, 6))
+        ));
+    }
+
+    #[test]
+    fn diagnose() {
+        let arm = arm::<f64>();
+        let initial = arm.joint_positions();
+        arm.set_joint_positions(&[0.3, 0.1, 0.1, -0.9, 0.1, 0.0])
+            .unwrap();
+        let target = target(&arm);
+        arm.set_joint_positions(&initial).unwrap();
+        let solver = k::JacobianIkSolver::default();
+        let constraints = k::Constraints::default();
+
+        assert_eq!(
+            diagnose_unreachable(&solver, &arm, &target, &constraints),
+            None
+        );
+        let mut far = target;
+        far.translation.vector[0] += 10.0;
+        assert_eq!(
+            diagnose_unreachable(&solver, &arm, &far, &constraints),
+            Some(UnreachableReason::OutOfReach)
+        );
+        assert_eq!(arm.joint_positions(), initial);
+
+        let position_only = position_only_constraints(&constraints);
+        assert!(position_only.position_x && !position_only.rotation_x);
+        let orientation_only = orientation_only_constraints(&constraints);
+        assert!(!orientation_only.position_x && orientation_only.rotation_x);
+    }
+
+    #[test]
+    fn orientation_tolerance() {
+        let arm = arm::<f64>();
+        let initial = arm.joint_positions();
+        arm.set_joint_positions(&[0.3, 0.1, 0.1, -0.9, 0.1, 0.0])
+            .unwrap();
+        let target = target(&arm);
+        arm.set_joint_positions(&initial).unwrap();
+        let solver = k::JacobianIkSolver::default();
+        let constraints = k::Constraints::default();
+
+        assert_eq!(
+            min_orientation_tolerance_for_reach(&solver, &arm, &target, &constraints, 0.001),
+            Some(0.0)
+        );
+        let mut far = target;
+        far.translation.vector[0] += 10.0;
+        assert_eq!(
+            min_orientation_tolerance_for_reach(&solver, &arm, &far, &constraints, 0.001),
+            None
+        );
+
+        let mut rotated = target;
+        rotated.rotation *= na::UnitQuaternion::from_euler_angles(0.0, 1.0, 0.0);
+        let tolerance =
+            min_orientation_tolerance_for_reach(&solver, &arm, &rotated, &constraints, 0.001)
+                .unwrap();
+        assert!((0.0..=std::f64::consts::PI).contains(&tolerance));
+        assert_eq!(arm.joint_positions(), initial);
+    }
+
+    struct FailingIkSolver;
+
+    impl InverseKinematicsSolver<f32> for FailingIkSolver {
+        fn solve_with_constraints(
+            &self,
+            _arm: &k::SerialChain<f32>,
+            _target_pose: &na::Isometry3<f32>,
+            _constraints: &k::Constraints,
+        ) -> ::std::result::Result<(), k::Error> {
+            Err(k::Error::NotConvergedError {
+                num_tried: 0,
+                position_diff: na::Vector3::zeros(),
+                rotation_diff: na::Vector3::zeros(),
+            })
+        }
+    }
+
+    #[test]
+    fn get_region_resume() {
+        let arm = arm::<f32>();
+        let target = target(&arm);
+        let solver = k::JacobianIkSolver::default();
+        let path = std::env::temp_dir().join(format!(
+            "openrr-planner-test-region-resume-{}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let max_point = na::Vector3::new(0.8, 0.9, 0.9);
+        let min_point = na::Vector3::new(0.0, -0.9, 0.0);
+
+        let regions = get_reachable_region_resume(
+            &path,
+            &solver,
+            &arm,
+            &target,
+            &k::Constraints::default(),
+            max_point,
+            min_point,
+            0.1,
+        )
+        .unwrap();
+        assert_eq!(regions.len(), 114);
+
+        // All points have been tested, so the solver is not used anymore.
+        let resumed = get_reachable_region_resume(
+            &path,
+            &FailingIkSolver,
+            &arm,
+            &target,
+            &k::Constraints::default(),
+            max_point,
+            min_point,
+            0.1,
+        )
+        .unwrap();
+        assert_eq!(resumed.len(), 114);
+
+        // The state of another scan can't be resumed.
+        assert!(get_reachable_region_resume(
+            &path,
+            &solver,
+            &arm,
+            &target,
+            &k::Constraints::default(),
+            max_point,
+            min_point,
+            0.2,
+        )
+        .is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn solve_with_mimic_joints() {
+        let arm = arm::<f64>();
+        // l_wrist_yaw follows l_shoulder_roll.
+        let mimics = vec![JointMimic {
+            joint_name: "l_wrist_yaw".to_owned(),
+            source_joint_name: "l_shoulder_roll".to_owned(),
+            multiplier: 1.0,
+            offset: 0.0,
+        }];
+        arm.set_joint_positions(&[0.3, 0.1, 0.1, -0.9, 0.1, 0.0])
+            .unwrap();
+        let target = target(&arm);
+        arm.set_joint_positions(&[0.2, 0.2, 0.0, -1.0, 0.3, 0.0])
+            .unwrap();
+
+        let solver =
+            RandomInitializeIkSolver::new(k::JacobianIkSolver::default(), 100).mimic_joints(mimics);
+        let constraints = position_constraints();
+        solver
+            .solve_with_constraints(&arm, &target, &constraints)
+            .unwrap();
+        let positions = arm.joint_positions();
+        assert!((positions[4] - positions[2]).abs() < 1e-9, "{positions:?}");
+        let reached = arm.end_transform();
+        assert!((reached.translation.vector - target.translation.vector).norm() < 0.01);
+    }
+
+    /// Succeeds only from `goal`, recording the joint positions tried
+    struct SeedRecordingIkSolver {
+        goal: Vec<f64>,
+        tried: std::sync::Mutex<Vec<Vec<f64>>>,
+    }
+
+    impl InverseKinematicsSolver<f64> for SeedRecordingIkSolver {
+        fn solve_with_constraints(
+            &self,
+            arm: &k::SerialChain<f64>,
+            _target_pose: &na::Isometry3<f64>,
+            _constraints: &k::Constraints,
+        ) -> ::std::result::Result<(), k::Error> {
+            let positions = arm.joint_positions();
+            self.tried.lock().unwrap().push(positions.clone());
+            if positions == self.goal {
+                Ok(())
+            } else {
+                Err(k::Error::NotConvergedError {
+                    num_tried: 0,
+                    position_diff: na::Vector3::zeros(),
+                    rotation_diff: na::Vector3::zeros(),
+                })
+            }
+        }
+    }
+
+    #[test]
+    fn solve_with_seeds() {
+        let arm = arm::<f64>();
+        let initial = arm.joint_positions();
+        let goal = vec![0.0, 0.1, 0.0, -0.5, 0.0, 0.0];
+        let seeds = vec![
+            // out of the joint limits
+            vec![100.0; 6],
+            vec![0.1, 0.1, 0.0, -0.5, 0.0, 0.0],
+            goal.clone(),
+        ];
+        let inner = SeedRecordingIkSolver {
+            goal: goal.clone(),
+            tried: Default::default(),
+        };
+        let solver = RandomInitializeIkSolver::with_seeds(inner, 1, seeds);
+        solver.solve(&arm, &na::Isometry3::identity()).unwrap();
+        assert_eq!(arm.joint_positions(), goal);
+        let tried = solver.solver.tried.lock().unwrap();
+        // the initial positions, a random one instead of the invalid seed,
+        // and the valid seeds
+        assert_eq!(tried.len(), 4);
+        assert_eq!(tried[0], initial);
+        assert_eq!(tried[2], vec![0.1, 0.1, 0.0, -0.5, 0.0, 0.0]);
+        assert_eq!(tried[3], goal);
+    }
+
+    #[test]
+    fn orientation_coverage() {
+        let arm = arm::<f64>();
+        let reachable = arm.end_transform();
+        let solver = k::JacobianIkSolver::default();
+        let positions = vec![
+            reachable.translation.vector,
+            na::Vector3::new(10.0, 0.0, 0.0),
+        ];
+        let orientations = vec![
+            reachable.rotation,
+            reachable.rotation * na::UnitQuaternion::from_euler_angles(0.0, 0.0, 0.01),
+        ];
+        let initial = arm.joint_positions();
+        let coverage = get_orientation_coverage(
+            &solver,
+            &arm,
+            &positions,
+            &orientations,
+            &k::Constraints::default(),
+        );
+        assert_eq!(coverage.len(), 2);
+        assert_eq!(coverage[0].0.translation.vector, positions[0]);
+        assert_eq!(coverage[0].1, 1.0);
+        assert_eq!(coverage[1].0.translation.vector, positions[1]);
+        assert_eq!(coverage[1].1, 0.0);
+        assert_eq!(arm.joint_positions(), initial);
+
+        let coverage =
+            get_orientation_coverage(&solver, &arm, &positions, &[], &k::Constraints::default());
+        assert!(coverage.iter().all(|(_, c)| *c == 0.0));
+    }
+
+    /// Moves the arm to the fixed joint positions, or fails if `None`
+    struct FixedIkSolver(Option<Vec<f64>>);
+
+    impl InverseKinematicsSolver<f64> for FixedIkSolver {
+        fn solve_with_constraints(
+            &self,
+            arm: &k::SerialChain<f64>,
+            _target_pose: &na::Isometry3<f64>,
+            _constraints: &k::Constraints,
+        ) -> ::std::result::Result<(), k::Error> {
+            match &self.0 {
+                Some(positions) => arm.set_joint_positions(positions),
+                None => Err(k::Error::NotConvergedError {
+                    num_tried: 0,
+                    position_diff: na::Vector3::zeros(),
+                    rotation_diff: na::Vector3::zeros(),
+                }),
+            }
+        }
+    }
+
+    #[test]
+    fn solve_avoiding_collision() {
+        let urdf_robot = urdf_rs::read_file("sample.urdf").unwrap();
+        let arm = arm::<f64>();
+        let initial = arm.joint_positions();
+        let pairs = vec![("root".to_owned(), "l_shoulder_roll".to_owned())];
+        let solver = |solution| {
+            CollisionAvoidIkSolver::from_urdf_robot(
+                FixedIkSolver(solution),
+                3,
+                &urdf_robot,
+                pairs.clone(),
+                0.001,
+            )
+        };
+        let target = na::Isometry3::identity();
+        let constraints = k::Constraints::default();
+
+        let free = vec![0.0; 6];
+        solver(Some(free.clone()))
+            .solve_avoiding_collision(&arm, &target, &constraints)
+            .unwrap();
+        assert_eq!(arm.joint_positions(), free);
+
+        arm.set_joint_positions(&initial).unwrap();
+        let colliding = solver(Some(vec![1.57, 0.0, 0.0, 0.0, 0.0, 0.0]));
+        assert!(matches!(
+            colliding.solve_avoiding_collision(&arm, &target, &constraints),
+            Err(Error::SelfCollision { .. })
+        ));
+        assert_eq!(arm.joint_positions(), initial);
+        let error = colliding
+            .solve_with_constraints(&arm, &target, &constraints)
+            .unwrap_err();
+        assert!(is_self_collision_ik_error(&error), "{error}");
+
+        let not_converged = solver(None);
+        assert!(matches!(
+            not_converged.solve_avoiding_collision(&arm, &target, &constraints),
+            Err(Error::KinematicsError { .. })
+        ));
+        let error = not_converged
+            .solve_with_constraints(&arm, &target, &constraints)
+            .unwrap_err();
+        assert!(matches!(error, k::Error::NotConvergedError { .. }));
+        assert!(!is_self_collision_ik_error(&error));
+        assert_eq!(arm.joint_positions(), initial);
+    }
+
+    #[test]
+    fn solve_around_nominal() {
+        let arm = arm::<f64>();
+        let initial = arm.joint_positions();
+        let nominal = vec![0.0, 0.1, 0.0, -0.5, 0.0, 0.0];
+        let inner = SeedRecordingIkSolver {
+            goal: nominal.clone(),
+            tried: Default::default(),
+        };
+        // without spread, the random joint angles are the nominal ones
+        let solver = RandomInitializeIkSolver::with_nominal(inner, 3, nominal.clone(), 0.0);
+        solver.solve(&arm, &na::Isometry3::identity()).unwrap();
+        assert_eq!(arm.joint_positions(), nominal);
+        let tried = solver.solver.tried.lock().unwrap();
+        assert_eq!(*tried, vec![initial.clone(), nominal.clone()]);
+        drop(tried);
+
+        arm.set_joint_positions(&initial).unwrap();
+        let solver = solver.nominal(nominal[..5].to_vec(), 0.0);
+        assert!(matches!(
+            solver.solve(&arm, &na::Isometry3::identity()),
+            Err(k::Error::PreconditionError { .. })
+        ));
+        assert_eq!(arm.joint_positions(), initial);
+    }
+
+    #[test]
+    fn detect_joints_at_limits() {
+        let arm = arm::<f64>();
+        assert!(joints_at_limits(&arm, 0.01).is_empty());
+        // l_shoulder_pitch: [-2.0, 1.5], l_elbow_pitch: [-2.0, 1.5]
+        arm.set_joint_positions(&[0.2, -2.0, 0.0, 1.495, 0.0, 0.0])
+            .unwrap();
+        assert_eq!(joints_at_limits(&arm, 0.01), vec![1, 3]);
+        assert_eq!(joints_at_limits(&arm, 0.001), vec![1]);
+
+        // the solution at the limits is accepted by default
+        let target = arm.end_transform();
+        arm.set_joint_positions(&[0.0, 0.0, 0.0, -1.0, 0.0, 0.0])
+            .unwrap();
+        let locked = vec![0.2, -2.0, 0.0, 1.495, 0.0, 0.0];
+        let inner = SeedRecordingIkSolver {
+            goal: locked.clone(),
+            tried: Default::default(),
+        };
+        let solver = RandomInitializeIkSolver::with_seeds(inner, 1, vec![locked.clone()]);
+        solver.solve(&arm, &target).unwrap();
+        assert_eq!(arm.joint_positions(), locked);
+
+        arm.set_joint_positions(&[0.0, 0.0, 0.0, -1.0, 0.0, 0.0])
+            .unwrap();
+        // and rejected if requested
+        let solver = solver.reject_joints_at_limits(0.01);
+        assert!(solver.solve(&arm, &target).is_err());
+        assert_eq!(arm.joint_positions(), vec![0.0, 0.0, 0.0, -1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn interpolate_poses() {
+        let start = na::Isometry3::new(na::Vector3::new(0.0, 0.0, 0.0), na::Vector3::zeros());
+        let end = na::Isometry3::new(
+            na::Vector3::new(1.0, 2.0, 0.0),
+            na::Vector3::new(0.0, 0.0, 1.0),
+        );
+        let poses = interpolate_cartesian(&start, &end, 3);
+        assert_eq!(poses.len(), 3);
+        assert_eq!(poses[0], start);
+        assert!((poses[2].translation.vector - end.translation.vector).norm() < 1e-9);
+        assert!(poses[2].rotation.angle_to(&end.rotation) < 1e-9);
+        assert!((poses[1].translation.vector - na::Vector3::new(0.5, 1.0, 0.0)).norm() < 1e-9);
+        assert!((poses[1].rotation.angle() - 0.5).abs() < 1e-9);
+
+        assert_eq!(interpolate_cartesian(&start, &end, 1), vec![end]);
+        assert!(interpolate_cartesian(&start, &end, 0).is_empty());
+    }
+
+    #[test]
+    fn solve_interpolated_path() {
+        let arm = arm::<f64>();
+        let start = arm.end_transform();
+        let mut end = start;
+        end.translation.vector[2] += 0.05;
+        let poses = interpolate_cartesian(&start, &end, 5);
+
+        let solver = k::JacobianIkSolver::default();
+        let path = solve_path(&solver, &arm, &poses, &k::Constraints::default()).unwrap();
+        assert_eq!(path.len(), 5);
+        assert_eq!(arm.joint_positions(), path[4]);
+        let reached = arm.end_transform();
+        assert!((reached.translation.vector - end.translation.vector).norm() < 0.01);
+
+        // unreachable
+        let initial = arm.joint_positions();
+        end.translation.vector[0] += 10.0;
+        assert!(solve_path(&solver, &arm, &[end], &k::Constraints::default()).is_err());
+        assert_eq!(arm.joint_positions(), initial);
+    }
+
+    #[test]
+    fn cartesian_path() {
+        let arm = arm::<f64>();
+        let start = arm.end_transform();
+        let mut end = start;
+        end.translation.vector[2] += 0.05;
+        let solver = k::JacobianIkSolver::default();
+
+        let path = plan_cartesian_path(
+            &solver,
+            &arm,
+            &start,
+            &end,
+            0.02,
+            0.1,
+            &k::Constraints::default(),
+        )
+        .unwrap();
+        // 0.05 / 0.02 is rounded up to 3 steps
+        assert_eq!(path.len(), 4);
+        for positions in &path {
+            arm.set_joint_positions(positions).unwrap();
+            let reached = arm.end_transform().translation.vector;
+            // on the line from start to end
+            let offset = reached - start.translation.vector;
+            assert!(offset.x.abs() < 0.01 && offset.y.abs() < 0.01, "{offset:?}");
+        }
+        assert!((arm.end_transform().translation.vector - end.translation.vector).norm() < 0.01);
+
+        // unreachable
+        let initial = arm.joint_positions();
+        let start = arm.end_transform();
+        let mut far = start;
+        far.translation.vector[0] += 10.0;
+        match plan_cartesian_path(
+            &solver,
+            &arm,
+            &start,
+            &far,
+            0.1,
+            0.1,
+            &k::Constraints::default(),
+        ) {
+            Err(Error::UnreachableWaypoint { index, .. }) => assert!(index > 0),
+            r => panic!("unexpected result {r:?}"),
+        }
+        assert_eq!(arm.joint_positions(), initial);
+
+        // rotation only
+        let mut rotated = start;
+        rotated.rotation *= na::UnitQuaternion::from_euler_angles(0.0, 0.0, 0.05);
+        let path = plan_cartesian_path(
+            &solver,
+            &arm,
+            &start,
+            &rotated,
+            0.02,
+            0.02,
+            &k::Constraints::default(),
+        )
+        .unwrap();
+        // 0.05 / 0.02 is rounded up to 3 steps
+        assert_eq!(path.len(), 4);
+
+        for step in [0.0, -0.1, f64::NAN, f64::INFINITY] {
+            assert!(plan_cartesian_path(
+                &solver,
+                &arm,
+                &start,
+                &end,
+                step,
+                0.1,
+                &k::Constraints::default()
+            )
+            .is_err());
+            assert!(plan_cartesian_path(
+                &solver,
+                &arm,
+                &start,
+                &end,
+                0.02,
+                step,
+                &k::Constraints::default()
+            )
+            .is_err());
+        }
+        assert_eq!(arm.joint_positions(), initial);
+    }
+
+    #[test]
+    fn solve_partial_path() {
+        let arm = arm::<f64>();
+        let start = arm.end_transform();
+        let mut end = start;
+        end.translation.vector[2] += 0.05;
+        let mut poses = interpolate_cartesian(&start, &end, 3);
+        let solver = k::JacobianIkSolver::default();
+
+        let (path, failed) = solve_path_partial(&solver, &arm, &poses, &k::Constraints::default());
+        assert_eq!(path.len(), 3);
+        assert_eq!(failed, None);
+
+        // the third pose is unreachable
+        arm.set_joint_positions(&path[0]).unwrap();
+        poses[2].translation.vector[0] += 10.0;
+        let (partial, failed) =
+            solve_path_partial(&solver, &arm, &poses, &k::Constraints::default());
+        assert_eq!(partial.len(), 2);
+        assert_eq!(failed, Some(2));
+        assert_eq!(arm.joint_positions(), partial[1]);
+
+        // the first pose is unreachable
+        let initial = arm.joint_positions();
+        let (partial, failed) =
+            solve_path_partial(&solver, &arm, &poses[2..], &k::Constraints::default());
+        assert!(partial.is_empty());
+        assert_eq!(failed, Some(0));
+        assert_eq!(arm.joint_positions(), initial);
+    }
+
+    #[test]
+    fn get_region_with_order() {
+        let arm = arm::<f32>();
+        let target = target(&arm);
+        let solver = k::JacobianIkSolver::default();
+        let scan = |order| {
+            get_reachable_region_with_order(
+                &solver,
+                &arm,
+                &target,
+                &k::Constraints::default(),
+                na::Vector3::new(0.8, 0.9, 0.9),
+                na::Vector3::new(0.0, -0.9, 0.0),
+                0.1,
+                order,
+            )
+        };
+        let raster = scan(ScanOrder::Raster);
+        let center_out = scan(ScanOrder::CenterOut);
+        assert_eq!(raster.len(), 114);
+        assert_eq!(center_out.len(), 114);
+        let distance = |pose: &na::Isometry3<f32>| {
+            (pose.translation.vector - target.translation.vector).norm()
+        };
+        for pair in center_out.windows(2) {
+            assert!(distance(&pair[0]) <= distance(&pair[1]));
+        }
+        for pair in raster.windows(2) {
+            let (a, b) = (pair[0].translation.vector, pair[1].translation.vector);
+            assert!((a[2], a[1], a[0]) < (b[2], b[1], b[0]));
+        }
+    }
+
+    #[test]
+    fn seed() {
+        let arm = arm::<f64>();
+        let limits = arm.iter_joints().map(|j| j.limits).collect::<Vec<_>>();
+
+        assert!(is_seed_feasible(&arm, &k::Constraints::default()));
+        arm.set_joint_positions(&[0.2, 1.5, 0.0, -1.0, 0.0, 0.0])
+            .unwrap();
+        assert!(!is_seed_feasible(&arm, &k::Constraints::default()));
+        let constraints = k::Constraints {
+            ignored_joint_names: vec!["l_shoulder_pitch".to_owned()],
+            ..Default::default()
+        };
+        assert!(is_seed_feasible(&arm, &constraints));
+
+        arm.set_joint_positions(&[0.3, 0.1, 0.1, -0.9, 0.1, 0.0])
+            .unwrap();
+        arm.update_transforms();
+        let target = arm.end_transform();
+        let initial = arm.joint_positions();
+        let seed = suggest_seed(&arm, &target);
+        assert_eq!(arm.joint_positions(), initial);
+        assert_eq!(seed.len(), limits.len());
+        for (position, limit) in seed.iter().zip(&limits) {
+            let range = limit.unwrap();
+            assert!(*position >= range.min && *position <= range.max);
+        }
+        let center = limits
+            .iter()
+            .map(|limit| {
+                let range = limit.unwrap();
+                (range.min + range.max) / 2.0
+            })
+            .collect::<Vec<_>>();
+        let distance = |positions: &[f64]| {
+            arm.set_joint_positions(positions).unwrap();
+            (arm.end_transform().translation.vector - target.translation.vector).norm()
+        };
+        assert!(distance(&seed) <= distance(&center));
+        arm.set_joint_positions(&seed).unwrap();
+        assert!(is_seed_feasible(&arm, &k::Constraints::default()));
+    }
+
+    #[test]
+    fn prioritized_ik() {
+        let arm = arm::<f64>();
+        let wrist = arm.find("l_wrist_pitch").unwrap();
+        let initial = arm.joint_positions();
+        arm.set_joint_positions(&[0.3, 0.1, 0.1, -0.9, 0.1, 0.0])
+            .unwrap();
+        let target = target(&arm);
+        let wrist_rotation = wrist.world_transform().unwrap().rotation;
+        arm.set_joint_positions(&initial).unwrap();
+
+        // Only the position is the primary task, and the orientation of the
+        // wrist is kept as much as possible.
+        let constraints = position_constraints();
+        let solver = PrioritizedIkSolver::new(
+            0.001,
+            1000,
+            Some(OrientationObjective {
+                joint_name: "l_wrist_pitch".to_owned(),
+                rotation: wrist_rotation,
+                rotation_x: true,
+                rotation_y: true,
+                rotation_z: true,
+            }),
+        );
+        solver
+            .solve_with_constraints(&arm, &target, &constraints)
+            .unwrap();
+        arm.update_transforms();
+        let reached = arm.end_transform();
+        assert!((reached.translation.vector - target.translation.vector).norm() < 0.001);
+        let wrist_error = wrist
+            .world_transform()
+            .unwrap()
+            .rotation
+            .angle_to(&wrist_rotation);
+        assert!(wrist_error < 0.01, "{wrist_error}");
+
+        // unreachable
+        let initial = arm.joint_positions();
+        let mut far = target;
+        far.translation.vector[0] += 10.0;
+        assert!(solver
+            .solve_with_constraints(&arm, &far, &constraints)
+            .is_err());
+        assert_eq!(arm.joint_positions(), initial);
+    }
+
+    #[test]
+    fn prioritized_ik_tolerances() {
+        let arm = arm::<f64>();
+        let mut target = target(&arm);
+        target.rotation *= na::UnitQuaternion::from_euler_angles(0.0, 0.0, 0.1);
+        // the rotation error is not hidden by the large distance tolerance
+        let solver = PrioritizedIkSolver::new(1.0, 1000, None).allowable_target_angle(0.001);
+        assert_eq!(solver.tolerance(), (1.0, 0.001));
+        solver
+            .solve_with_constraints(&arm, &target, &k::Constraints::default())
+            .unwrap();
+        let reached = arm.end_transform();
+        assert!(reached.rotation.angle_to(&target.rotation) < 0.001);
+    }
+
+    #[test]
+    fn record_best_attempt() {
+        let arm = arm::<f64>();
+        let initial = arm.joint_positions();
+        let mut far = arm.end_transform();
+        far.translation.vector[0] += 10.0;
+        let constraints = position_constraints();
+
+        let solver = RandomInitializeIkSolver::new(k::JacobianIkSolver::default(), 5);
+        assert!(solver
+            .solve_with_constraints(&arm, &far, &constraints)
+            .is_err());
+        assert!(solver.best_attempt().is_none());
+
+        let solver = solver.record_best_attempt();
+        let err = solver
+            .solve_with_constraints(&arm, &far, &constraints)
+            .unwrap_err();
+        assert_eq!(arm.joint_positions(), initial);
+        let best = solver.best_attempt().unwrap();
+        // the target is about 10m away from the reachable region
+        let residual = solver.best_residual().unwrap();
+        assert!(residual > 8.0 && residual <= 10.0 + 1e-9, "{residual}");
+        assert_eq!(best.rotation_diff, na::Vector3::zeros());
+        match err {
+            k::Error::NotConvergedError {
+                num_tried,
+                position_diff,
+                ..
+            } => {
+                assert_eq!(num_tried, 5);
+                assert!((position_diff - best.position_diff).norm() < 1e-9);
+            }
+            e => panic!("unexpected error {e:?}"),
+        }
+        // the best attempt is reproducible from its joint positions
+        arm.set_joint_positions(&best.joint_positions).unwrap();
+        let (position_diff, _) = masked_pose_diff(&arm.end_transform(), &far, &constraints);
+        assert!((position_diff - best.position_diff).norm() < 1e-9);
+    }
+
+    #[test]
+    fn nullspace_ik() {
+        let arm = arm::<f64>();
+        let initial = arm.joint_positions();
+        arm.set_joint_positions(&[0.3, 0.1, 0.1, -0.9, 0.1, 0.0])
+            .unwrap();
+        let target = target(&arm);
+        // the arm is redundant for the position-only constraints
+        let constraints = position_constraints();
+        let objective = joint_center_objective(&arm);
+
+        arm.set_joint_positions(&initial).unwrap();
+        k::JacobianIkSolver::default()
+            .solve_with_constraints(&arm, &target, &constraints)
+            .unwrap();
+        let primary_only = objective(&arm.joint_positions());
+
+        arm.set_joint_positions(&initial).unwrap();
+        let solver = NullspaceIkSolver::new(
+            k::JacobianIkSolver::default(),
+            0.1,
+            joint_center_objective(&arm),
+        );
+        solver
+            .solve_with_constraints(&arm, &target, &constraints)
+            .unwrap();
+        let reached = arm.end_transform();
+        assert!((reached.translation.vector - target.translation.vector).norm() < 0.002);
+        let optimized = objective(&arm.joint_positions());
+        assert!(optimized < primary_only, "{optimized} >= {primary_only}");
+
+        // the primary task fails
+        arm.set_joint_positions(&initial).unwrap();
+        let mut far = target;
+        far.translation.vector[0] += 10.0;
+        assert!(solver
+            .solve_with_constraints(&arm, &far, &constraints)
+            .is_err());
+    }
+
+    #[test]
+    fn random_initialize_with_damping_schedule() {
+        let arm = arm::<f64>();
+        let initial = arm.joint_positions();
+        arm.set_joint_positions(&[0.3, 0.1, 0.1, -0.9, 0.1, 0.0])
+            .unwrap();
+        let target = target(&arm);
+        arm.set_joint_positions(&initial).unwrap();
+        let constraints = position_constraints();
+
+        // too large damping to move
+        let inner = PrioritizedIkSolver::new(0.001, 1000, None).damping(1e6);
+        let solver = RandomInitializeIkSolver::new(inner, 1);
+        assert!(solver
+            .solve_with_constraints(&arm, &target, &constraints)
+            .is_err());
+        assert_eq!(arm.joint_positions(), initial);
+
+        let solver = solver.damping_schedule(vec![0.01]);
+        solver
+            .solve_with_constraints(&arm, &target, &constraints)
+            .unwrap();
+        let reached = arm.end_transform();
+        assert!((reached.translation.vector - target.translation.vector).norm() < 0.001);
+        // the inner solver is not modified
+        assert_eq!(solver.solver.damping, 1e6);
+
+        arm.set_joint_positions(&initial).unwrap();
+        let solver = solver.damping_schedule(vec![1e6]);
+        assert!(solver
+            .solve_with_constraints(&arm, &target, &constraints)
+            .is_err());
+    }
+
+    #[test]
+    fn random_initialize_jacobian_with_damping_schedule() {
+        let arm = arm::<f64>();
+        let initial = arm.joint_positions();
+        arm.set_joint_positions(&[0.3, 0.1, 0.1, -0.9, 0.1, 0.0])
+            .unwrap();
+        let target = target(&arm);
+        arm.set_joint_positions(&initial).unwrap();
+
+        let solver =
+            RandomInitializeIkSolver::new(k::JacobianIkSolver::default(), 1).inner_num_max_try(100);
+        let jacobian_multiplier = solver.solver.jacobian_multiplier;
+        solver
+            .solve_with_constraints(&arm, &target, &k::Constraints::default())
+            .unwrap();
+
+        // too large damping to move
+        arm.set_joint_positions(&initial).unwrap();
+        let solver = solver.damping_schedule(vec![1e6]);
+        assert!(solver
+            .solve_with_constraints(&arm, &target, &k::Constraints::default())
+            .is_err());
+        assert_eq!(arm.joint_positions(), initial);
+        // the inner solver is not modified
+        assert_eq!(solver.solver.jacobian_multiplier, jacobian_multiplier);
+
+        let solver = solver.damping_schedule(vec![0.0]);
+        solver
+            .solve_with_constraints(&arm, &target, &k::Constraints::default())
+            .unwrap();
+
+        let constraints = position_constraints();
+        arm.set_joint_positions(&initial).unwrap();
+        let solver = solver
+            .damping_schedule_with_nullspace(vec![0.01], |q: &[f64]| {
+                q.iter().map(|q| -0.01 * q).collect()
+            })
+            .inner_num_max_try(1000);
+        solver
+            .solve_with_constraints(&arm, &target, &constraints)
+            .unwrap();
+        let reached = arm.end_transform();
+        assert!((reached.translation.vector - target.translation.vector).norm() < 0.001);
+
+        arm.set_joint_positions(&initial).unwrap();
+        let solver =
+            solver.damping_schedule_with_nullspace(vec![0.01], |q: &[f64]| vec![0.0; q.len() + 1]);
+        assert!(solver
+            .solve_with_constraints(&arm, &target, &constraints)
+            .is_err());
+        assert_eq!(arm.joint_positions(), initial);
+    }
+
+    #[test]
+    fn inner_solver_parameters() {
+        let solver = RandomInitializeIkSolver::new(k::JacobianIkSolver::<f64>::default(), 10)
+            .inner_num_max_try(5)
+            .inner_allowable_target_distance(0.01);
+        assert_eq!(solver.num_max_try, 10);
+        assert_eq!(solver.solver.num_max_try, 5);
+        assert_eq!(solver.solver.allowable_target_distance, 0.01);
+    }
+
+    #[test]
+    fn joint_velocity_from_cartesian_velocity() {
+        let arm = arm::<f64>();
+        let twist = na::Vector6::new(0.1, -0.05, 0.02, 0.0, 0.1, 0.0);
+
+        let velocities = cartesian_velocity_to_joint_velocity(&arm, twist, 0.0).unwrap();
+        assert_eq!(velocities.len(), 6);
+        let realized = k::jacobian(&arm) * na::DVector::from_vec(velocities);
+        for i in 0..6 {
+            assert!((realized[i] - twist[i]).abs() < 1e-6, "{realized:?}");
+        }
+
+        // The first joint is at the upper limit and must not move further.
+        arm.set_joint_positions(&[3.0, 0.2, 0.0, -1.0, 0.0, 0.0])
+            .unwrap();
+        let velocities = cartesian_velocity_to_joint_velocity(&arm, twist, 0.01).unwrap();
+        assert!(velocities[0] <= 0.0);
+    }
+
+    #[test]
+    fn get_region_with_samples() {
+        let arm = arm::<f32>();
+        let target = target(&arm);
+        let solver = k::JacobianIkSolver::default();
+
+        let regions = get_reachable_region_with_samples(
+            &solver,
+            &arm,
+            &target,
+            &k::Constraints::default(),
+            na::Vector3::new(0.8, 0.9, 0.9),
+            na::Vector3::new(0.0, -0.9, 0.0),
+            [Some(9), Some(19), None],
+        );
+        assert!(regions.len() <= 9 * 19);
+        for pose in &regions {
+            assert_eq!(pose.translation.vector[2], target.translation.vector[2]);
+        }
+        // The initial pose itself is always reachable.
+        let regions = get_reachable_region_with_samples(
+            &solver,
+            &arm,
+            &target,
+            &k::Constraints::default(),
+            na::Vector3::new(0.8, 0.9, 0.9),
+            na::Vector3::new(0.0, -0.9, 0.0),
+            [None, None, None],
+        );
+        assert_eq!(regions.len(), 1);
     }
 }
@@ -21,19 +21,25 @@ mod errors;
 
 pub mod collision;
 
+mod fk_cache;
+
 mod funcs;
 
 mod ik;
 
 mod planner;
 
+mod reachability;
+
 // re-export k::IK modules
 pub use k::{InverseKinematicsSolver, JacobianIkSolver};
 
 pub use crate::{
     collision::{CollisionDetector, FromUrdf, SelfCollisionChecker, SelfCollisionCheckerConfig},
     errors::{Error, Result},
+    fk_cache::*,
     funcs::*,
     ik::*,
     planner::*,
+    reachability::*,
 };
@@ -0,0 +1,727 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use k::nalgebra as na;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::*;
+
+/// Index of a cell in a [`ReachabilityGrid`].
+pub type CellIndex = [i64; 3];
+
+/// Set of reachable positions snapped to a regular grid.
+///
+/// The grid is aligned to `min_point` (the one used for the scan), and each
+/// position is snapped to the nearest cell center (`min_point` plus
+/// multiples of `unit_check_length`). This makes it possible to compare and
+/// combine the results of [`get_reachable_region`] computed for different
+/// base placements.
+///
+/// [`get_reachable_region`]: crate::get_reachable_region
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReachabilityGrid {
+    min_point: na::Vector3<f64>,
+    unit_check_length: f64,
+    cells: HashSet<CellIndex>,
+}
+
+impl ReachabilityGrid {
+    /// Creates an empty grid aligned to `min_point`.
+    #[track_caller]
+    pub fn new(min_point: na::Vector3<f64>, unit_check_length: f64) -> Self {
+        assert!(
+            unit_check_length > 0.0,
+            "unit_check_length must be positive, but got {unit_check_length}"
+        );
+        Self {
+            min_point,
+            unit_check_length,
+            cells: HashSet::new(),
+        }
+    }
+
+    /// Creates a grid from the poses returned by [`get_reachable_region`](crate::get_reachable_region).
+    ///
+    /// `min_point` and `unit_check_length` should be the ones used for the scan.
+    #[track_caller]
+    pub fn from_poses(
+        poses: &[na::Isometry3<f64>],
+        min_point: na::Vector3<f64>,
+        unit_check_length: f64,
+    ) -> Self {
+        let mut grid = Self::new(min_point, unit_check_length);
+        for pose in poses {
+            grid.insert(&pose.translation.vector);
+        }
+        grid
+    }
+
+    /// Returns the point the grid is aligned to.
+    pub fn min_point(&self) -> na::Vector3<f64> {
+        self.min_point
+    }
+
+    /// Returns the length of the edge of a cell.
+    pub fn unit_check_length(&self) -> f64 {
+        self.unit_check_length
+    }
+
+    /// Returns the index of the cell containing `position`.
+    pub fn cell_index(&self, position: &na::Vector3<f64>) -> CellIndex {
+        let relative = (position - self.min_point) / self.unit_check_length;
+        [
+            relative[0].round() as i64,
+            relative[1].round() as i64,
+            relative[2].round() as i64,
+        ]
+    }
+
+    /// Returns the center of the cell.
+    pub fn cell_center(&self, index: &CellIndex) -> na::Vector3<f64> {
+        self.min_point
+            + na::Vector3::new(index[0] as f64, index[1] as f64, index[2] as f64)
+                * self.unit_check_length
+    }
+
+    /// Marks the cell containing `position` as reachable.
+    ///
+    /// Returns `false` if the cell was already reachable.
+    pub fn insert(&mut self, position: &na::Vector3<f64>) -> bool {
+        let index = self.cell_index(position);
+        self.cells.insert(index)
+    }
+
+    /// Returns `true` if the cell containing `position` is reachable.
+    pub fn contains(&self, position: &na::Vector3<f64>) -> bool {
+        self.cells.contains(&self.cell_index(position))
+    }
+
+    /// Returns the number of reachable cells.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Returns `true` if no cell is reachable.
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Returns an iterator over the indices of the reachable cells.
+    pub fn cells(&self) -> impl Iterator<Item = &CellIndex> + '_ {
+        self.cells.iter()
+    }
+
+    /// Returns an iterator over the centers of the reachable cells.
+    pub fn positions(&self) -> impl Iterator<Item = na::Vector3<f64>> + '_ {
+        self.cells.iter().map(|index| self.cell_center(index))
+    }
+
+    /// Returns the cells which are reachable in `self` or `other`.
+    ///
+    /// Returns an error if the grids are not aligned to the same cells.
+    pub fn union(&self, other: &Self) -> Result<Self> {
+        self.check_same_cells(other)?;
+        Ok(self.with_cells(self.cells.union(&other.cells).copied().collect()))
+    }
+
+    /// Returns the cells which are reachable in both `self` and `other`.
+    ///
+    /// Returns an error if the grids are not aligned to the same cells.
+    pub fn intersection(&self, other: &Self) -> Result<Self> {
+        self.check_same_cells(other)?;
+        Ok(self.with_cells(self.cells.intersection(&other.cells).copied().collect()))
+    }
+
+    /// Returns the cells which are reachable in `self` but not in `other`.
+    ///
+    /// Returns an error if the grids are not aligned to the same cells.
+    pub fn difference(&self, other: &Self) -> Result<Self> {
+        self.check_same_cells(other)?;
+        Ok(self.with_cells(self.cells.difference(&other.cells).copied().collect()))
+    }
+
+    fn with_cells(&self, cells: HashSet<CellIndex>) -> Self {
+        Self {
+            min_point: self.min_point,
+            unit_check_length: self.unit_check_length,
+            cells,
+        }
+    }
+
+    fn check_same_cells(&self, other: &Self) -> Result<()> {
+        if (self.unit_check_length - other.unit_check_length).abs() >= f64::EPSILON {
+            return Err(Error::Other {
+                error: format!(
+                    "unit_check_length mismatch: {} != {}",
+                    self.unit_check_length, other.unit_check_length
+                ),
+            });
+        }
+        if (self.min_point - other.min_point).amax() >= f64::EPSILON {
+            return Err(Error::Other {
+                error: format!(
+                    "min_point mismatch: {:?} != {:?}",
+                    self.min_point, other.min_point
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Index of an orientation bin in a [`ReachabilityMap`], which is the
+/// quantized unit quaternion `[w, i, j, k]`.
+pub type OrientationBin = [i64; 4];
+
+/// Lookup table of reachable poses built from the result of a reachability scan.
+///
+/// Positions are quantized in the same way as [`ReachabilityGrid`]. If an
+/// angle resolution is set, orientations are also quantized (as unit
+/// quaternions, identifying `q` and `-q`) and a pose is reachable only if
+/// both its cell and its orientation bin were reached. Unlike euler angles,
+/// the bins have no discontinuity at the wrap of the angles or near the
+/// gimbal lock. Queries are O(1).
+#[derive(Debug, Clone)]
+pub struct ReachabilityMap {
+    grid: ReachabilityGrid,
+    angle_resolution: Option<f64>,
+    orientations: HashMap<CellIndex, HashSet<OrientationBin>>,
+}
+
+impl ReachabilityMap {
+    /// Creates an empty map aligned to `min_point` which ignores orientations.
+    #[track_caller]
+    pub fn new(min_point: na::Vector3<f64>, unit_check_length: f64) -> Self {
+        Self {
+            grid: ReachabilityGrid::new(min_point, unit_check_length),
+            angle_resolution: None,
+            orientations: HashMap::new(),
+        }
+    }
+
+    /// Creates an empty map aligned to `min_point` which also distinguishes
+    /// orientations by `angle_resolution` (in radians).
+    #[track_caller]
+    pub fn with_angle_resolution(
+        min_point: na::Vector3<f64>,
+        unit_check_length: f64,
+        angle_resolution: f64,
+    ) -> Self {
+        assert!(
+            angle_resolution > 0.0,
+            "angle_resolution must be positive, but got {angle_resolution}"
+        );
+        Self {
+            angle_resolution: Some(angle_resolution),
+            ..Self::new(min_point, unit_check_length)
+        }
+    }
+
+    /// Creates a map ignoring orientations from the poses returned by
+    /// [`get_reachable_region`](crate::get_reachable_region).
+    ///
+    /// `min_point` and `unit_check_length` should be the ones used for the scan.
+    #[track_caller]
+    pub fn from_poses(
+        poses: &[na::Isometry3<f64>],
+        min_point: na::Vector3<f64>,
+        unit_check_length: f64,
+    ) -> Self {
+        let mut map = Self::new(min_point, unit_check_length);
+        for pose in poses {
+            map.insert(pose);
+        }
+        map
+    }
+
+    /// Marks `pose` as reachable.
+    pub fn insert(&mut self, pose: &na::Isometry3<f64>) {
+        self.grid.insert(&pose.translation.vector);
+        if let Some(bin) = self.orientation_bin(&pose.rotation) {
+            self.orientations
+                .entry(self.grid.cell_index(&pose.translation.vector))
+                .or_default()
+                .insert(bin);
+        }
+    }
+
+    /// Returns `true` if `pose` is in a reachable cell (and orientation bin).
+    pub fn is_reachable(&self, pose: &na::Isometry3<f64>) -> bool {
+        match self.orientation_bin(&pose.rotation) {
+            Some(bin) => self
+                .orientations
+                .get(&self.grid.cell_index(&pose.translation.vector))
+                .is_some_and(|bins| bins.contains(&bin)),
+            None => self.grid.contains(&pose.translation.vector),
+        }
+    }
+
+    /// Returns the reachable pose nearest to `pose`, or `None` if the map is empty.
+    ///
+    /// The candidates are the centers of the reachable cells, and if the map
+    /// distinguishes orientations, the centers of their reachable orientation
+    /// bins. The distance is the translation distance plus the rotation angle
+    /// (in radians) multiplied by `orientation_weight`. If orientations are
+    /// ignored, the rotation of `pose` is kept. This searches all cells, so
+    /// it is O(n) unlike [`is_reachable`](Self::is_reachable).
+    pub fn nearest_reachable(
+        &self,
+        pose: &na::Isometry3<f64>,
+        orientation_weight: f64,
+    ) -> Option<na::Isometry3<f64>> {
+        let candidates: Box<dyn Iterator<Item = na::Isometry3<f64>>> = match self.angle_resolution {
+            Some(angle_resolution) => {
+                Box::new(self.orientations.iter().flat_map(move |(cell, bins)| {
+                    let translation = self.grid.cell_center(cell);
+                    bins.iter().filter_map(move |bin| {
+                        let step = quaternion_step(angle_resolution);
+                        let rotation = na::UnitQuaternion::try_new(
+                            na::Quaternion::new(
+                                bin[0] as f64 * step,
+                                bin[1] as f64 * step,
+                                bin[2] as f64 * step,
+                                bin[3] as f64 * step,
+                            ),
+                            f64::EPSILON,
+                        )?;
+                        Some(na::Isometry3::from_parts(translation.into(), rotation))
+                    })
+                }))
+            }
+            None => Box::new(
+                self.grid
+                    .positions()
+                    .map(|position| na::Isometry3::from_parts(position.into(), pose.rotation)),
+            ),
+        };
+        let distance = |candidate: &na::Isometry3<f64>| {
+            (candidate.translation.vector - pose.translation.vector).norm()
+                + orientation_weight * candidate.rotation.angle_to(&pose.rotation)
+        };
+        candidates.min_by(|a, b| distance(a).total_cmp(&distance(b)))
+    }
+
+    /// Returns the reachable positions regardless of orientations.
+    pub fn grid(&self) -> &ReachabilityGrid {
+        &self.grid
+    }
+
+    /// Returns the angle resolution, or `None` if orientations are ignored.
+    pub fn angle_resolution(&self) -> Option<f64> {
+        self.angle_resolution
+    }
+
+    fn orientation_bin(&self, rotation: &na::UnitQuaternion<f64>) -> Option<OrientationBin> {
+        let step = quaternion_step(self.angle_resolution?);
+        let q = rotation.quaternion();
+        let bin = [q.w, q.i, q.j, q.k].map(|v| (v / step).round() as i64);
+        // `q` and `-q` are the same rotation, and rounding is symmetric, so
+        // their bins are negations of each other.
+        Some(bin.max(bin.map(|v| -v)))
+    }
+}
+
+/// Returns the quantization step of the quaternion components for
+/// `angle_resolution`, which is about half of the rotation angle.
+fn quaternion_step(angle_resolution: f64) -> f64 {
+    angle_resolution / 2.0
+}
+
+/// Index of a sample point in a reachability scan.
+pub type SampleIndex = [usize; 3];
+
+/// Progress of a reachability scan, which can be saved to a file to resume
+/// the scan later.
+///
+/// See [`get_reachable_region_resume`](crate::get_reachable_region_resume).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReachabilityScanState {
+    min_point: [f64; 3],
+    max_point: [f64; 3],
+    unit_check_length: f64,
+    tested: HashSet<SampleIndex>,
+    reachable: HashSet<SampleIndex>,
+}
+
+impl ReachabilityScanState {
+    /// Creates the state of a scan in which no point has been tested.
+    pub fn new(min_point: [f64; 3], max_point: [f64; 3], unit_check_length: f64) -> Self {
+        Self {
+            min_point,
+            max_point,
+            unit_check_length,
+            tested: HashSet::new(),
+            reachable: HashSet::new(),
+        }
+    }
+
+    /// Loads the state from a JSON file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let s = std::fs::read_to_string(path.as_ref())?;
+        serde_json::from_str(&s)
+            .map_err(|e| Error::ParseError(format!("{}: {e}", path.as_ref().display())))
+    }
+
+    /// Saves the state to a JSON file.
+    ///
+    /// The file is replaced atomically so that an interrupted save does not
+    /// corrupt the previous state.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let s = serde_json::to_string(self).map_err(|e| Error::Other {
+            error: e.to_string(),
+        })?;
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, s)?;
+        std::fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Returns `true` if the state was created for the same scan parameters.
+    pub fn is_compatible(
+        &self,
+        min_point: [f64; 3],
+        max_point: [f64; 3],
+        unit_check_length: f64,
+    ) -> bool {
+        self.min_point == min_point
+            && self.max_point == max_point
+            && self.unit_check_length == unit_check_length
+    }
+
+    /// Records the result of the test of the sample point.
+    pub fn insert(&mut self, index: SampleIndex, reachable: bool) {
+        self.tested.insert(index);
+        if reachable {
+            self.reachable.insert(index);
+        } else {
+            self.reachable.remove(&index);
+        }
+    }
+
+    /// Returns `true` if the sample point has been tested.
+    pub fn is_tested(&self, index: &SampleIndex) -> bool {
+        self.tested.contains(index)
+    }
+
+    /// Returns the number of tested sample points.
+    pub fn num_tested(&self) -> usize {
+        self.tested.len()
+    }
+
+    /// Returns an iterator over the sample points found reachable.
+    pub fn reachable(&self) -> impl Iterator<Item = &SampleIndex> + '_ {
+        self.reachable.iter()
+    }
+}
+
+/// Merge the reachable regions (e.g. computed for several base placements)
+/// into a grid containing the cells reachable in any of them.
+///
+/// Use [`ReachabilityGrid::from_poses`] and [`ReachabilityGrid::intersection`]
+/// to get the cells reachable in all of them instead.
+///
+/// `min_point` and `unit_check_length` should be the ones used for the scans.
+#[track_caller]
+pub fn merge_reachable_regions(
+    regions: &[Vec<na::Isometry3<f64>>],
+    min_point: na::Vector3<f64>,
+    unit_check_length: f64,
+) -> ReachabilityGrid {
+    let mut grid = ReachabilityGrid::new(min_point, unit_check_length);
+    for pose in regions.iter().flatten() {
+        grid.insert(&pose.translation.vector);
+    }
+    grid
+}
+
+/// Cells which gained or lost reachability, returned by [`diff_reachable_regions`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReachabilityDiff {
+    /// Cells reachable only after the change
+    pub gained: ReachabilityGrid,
+    /// Cells reachable only before the change
+    pub lost: ReachabilityGrid,
+    /// Cells reachable both before and after the change
+    pub unchanged: ReachabilityGrid,
+}
+
+/// Compare the reachable regions before and after a change (e.g. of the tool
+/// or the mounting of the robot).
+///
+/// The poses are snapped to a common grid as in [`ReachabilityGrid`], so
+/// `min_point` and `unit_check_length` should be the ones used for the scans.
+#[track_caller]
+pub fn diff_reachable_regions(
+    before: &[na::Isometry3<f64>],
+    after: &[na::Isometry3<f64>],
+    min_point: na::Vector3<f64>,
+    unit_check_length: f64,
+) -> ReachabilityDiff {
+    let before = ReachabilityGrid::from_poses(before, min_point, unit_check_length);
+    let after = ReachabilityGrid::from_poses(after, min_point, unit_check_length);
+    ReachabilityDiff {
+        gained: after.with_cells(after.cells.difference(&before.cells).copied().collect()),
+        lost: before.with_cells(before.cells.difference(&after.cells).copied().collect()),
+        unchanged: before.with_cells(before.cells.intersection(&after.cells).copied().collect()),
+    }
+}
+
+/// Returns the vertices of the convex hull of the reachable translations.
+///
+/// This is a compact approximation of the boundary of the region returned by
+/// [`get_reachable_region`](crate::get_reachable_region). Note that concave
+/// parts of the region are filled.
+pub fn reachable_region_hull(poses: &[na::Isometry3<f64>]) -> Vec<na::Point3<f64>> {
+    if poses.is_empty() {
+        return vec![];
+    }
+    let points = poses
+        .iter()
+        .map(|pose| na::Point3::from(pose.translation.vector))
+        .collect::<Vec<_>>();
+    ncollide3d::transformation::convex_hull(&points).coords
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pose(x: f64, y: f64, z: f64) -> na::Isometry3<f64> {
+        na::Isometry3::translation(x, y, z)
+    }
+
+    #[test]
+    fn test_merge_reachable_regions() {
+        let a = vec![pose(0.0, 0.0, 0.0), pose(0.1, 0.0, 0.0)];
+        let b = vec![pose(0.1, 0.0, 0.0), pose(0.2, 0.0, 0.0)];
+        let merged = merge_reachable_regions(&[a.clone(), b.clone()], na::Vector3::zeros(), 0.1);
+        assert_eq!(merged.len(), 3);
+
+        let a = ReachabilityGrid::from_poses(&a, na::Vector3::zeros(), 0.1);
+        let b = ReachabilityGrid::from_poses(&b, na::Vector3::zeros(), 0.1);
+        assert_eq!(a.union(&b).unwrap(), merged);
+        let both = a.intersection(&b).unwrap();
+        assert_eq!(both.len(), 1);
+        assert!(both.contains(&na::Vector3::new(0.1, 0.0, 0.0)));
+        // snapped to the nearest cell
+        assert!(both.contains(&na::Vector3::new(0.11, -0.02, 0.04)));
+        assert!(!both.contains(&na::Vector3::new(0.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_diff_reachable_regions() {
+        let before = vec![pose(0.0, 0.0, 0.0), pose(0.1, 0.0, 0.0)];
+        let after = vec![
+            pose(0.11, 0.0, 0.0),
+            pose(0.2, 0.0, 0.0),
+            pose(0.3, 0.0, 0.0),
+        ];
+        let diff = diff_reachable_regions(&before, &after, na::Vector3::zeros(), 0.1);
+        assert_eq!(diff.gained.len(), 2);
+        assert!(diff.gained.contains(&na::Vector3::new(0.2, 0.0, 0.0)));
+        assert!(diff.gained.contains(&na::Vector3::new(0.3, 0.0, 0.0)));
+        assert_eq!(diff.lost.len(), 1);
+        assert!(diff.lost.contains(&na::Vector3::new(0.0, 0.0, 0.0)));
+        assert_eq!(diff.unchanged.len(), 1);
+        assert!(diff.unchanged.contains(&na::Vector3::new(0.1, 0.0, 0.0)));
+
+        let diff = diff_reachable_regions(&before, &before, na::Vector3::zeros(), 0.1);
+        assert!(diff.gained.is_empty() && diff.lost.is_empty());
+        assert_eq!(diff.unchanged.len(), 2);
+    }
+
+    #[test]
+    fn test_grid_positions() {
+        let mut grid = ReachabilityGrid::new(na::Vector3::zeros(), 0.5);
+        assert!(grid.is_empty());
+        assert!(grid.insert(&na::Vector3::new(0.9, -0.1, 1.2)));
+        assert!(!grid.insert(&na::Vector3::new(1.1, 0.1, 0.8)));
+        assert_eq!(grid.cells().collect::<Vec<_>>(), vec![&[2, 0, 2]]);
+        assert_eq!(
+            grid.positions().collect::<Vec<_>>(),
+            vec![na::Vector3::new(1.0, 0.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn test_grid_not_aligned_to_origin() {
+        // scan points are min_point + n * unit_check_length, which lie on
+        // the boundaries of the cells of a grid aligned to the origin
+        let min_point = na::Vector3::new(0.05, 0.05, 0.05);
+        let poses = (0..4)
+            .map(|i| pose(0.05 + 0.1 * i as f64, 0.05, 0.05))
+            .collect::<Vec<_>>();
+        let grid = ReachabilityGrid::from_poses(&poses, min_point, 0.1);
+        assert_eq!(grid.len(), 4);
+        for p in &poses {
+            assert!(grid.contains(&p.translation.vector));
+        }
+        assert!(grid.contains(&na::Vector3::new(0.26, 0.04, 0.06)));
+        assert!(!grid.contains(&na::Vector3::new(0.45, 0.05, 0.05)));
+        let mut positions = grid.positions().map(|p| p.x).collect::<Vec<_>>();
+        positions.sort_by(f64::total_cmp);
+        for (x, p) in positions.iter().zip(&poses) {
+            assert!((x - p.translation.x).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_reachability_map() {
+        let poses = vec![pose(0.0, 0.0, 0.0), pose(0.1, 0.2, 0.3)];
+        let map = ReachabilityMap::from_poses(&poses, na::Vector3::zeros(), 0.1);
+        assert_eq!(map.grid().len(), 2);
+        assert!(map.is_reachable(&pose(0.1, 0.2, 0.3)));
+        assert!(map.is_reachable(&pose(0.12, 0.18, 0.3)));
+        assert!(!map.is_reachable(&pose(0.1, 0.1, 0.3)));
+        // orientations are ignored
+        let rotated = na::Isometry3::new(na::Vector3::zeros(), na::Vector3::new(0.0, 0.0, 1.0));
+        assert!(map.is_reachable(&rotated));
+    }
+
+    #[test]
+    fn test_reachability_map_not_aligned_to_origin() {
+        let min_point = na::Vector3::new(0.05, 0.05, 0.05);
+        let poses = vec![pose(0.05, 0.05, 0.05), pose(0.15, 0.05, 0.05)];
+        let map = ReachabilityMap::from_poses(&poses, min_point, 0.1);
+        assert_eq!(map.grid().len(), 2);
+        for p in &poses {
+            assert!(map.is_reachable(p));
+        }
+        // just below the scan point, which would be in another cell of a
+        // grid aligned to the origin
+        assert!(map.is_reachable(&pose(0.149, 0.049, 0.049)));
+        assert!(!map.is_reachable(&pose(0.25, 0.05, 0.05)));
+        let nearest = map.nearest_reachable(&pose(0.3, 0.05, 0.05), 1.0).unwrap();
+        assert!((nearest.translation.vector - poses[1].translation.vector).norm() < 1e-9);
+    }
+
+    #[test]
+    fn test_reachability_map_with_orientation() {
+        let mut map = ReachabilityMap::with_angle_resolution(na::Vector3::zeros(), 0.1, 0.2);
+        let rotated =
+            |yaw| na::Isometry3::new(na::Vector3::zeros(), na::Vector3::new(0.0, 0.0, yaw));
+        map.insert(&rotated(1.0));
+        assert!(map.is_reachable(&rotated(1.0)));
+        assert!(map.is_reachable(&rotated(1.05)));
+        assert!(!map.is_reachable(&rotated(0.0)));
+        assert!(!map.is_reachable(&pose(0.0, 0.0, 0.0)));
+        assert!(map.grid().contains(&na::Vector3::zeros()));
+
+        // the same rotations across the wrap of yaw
+        map.insert(&rotated(std::f64::consts::PI - 0.01));
+        assert!(map.is_reachable(&rotated(-std::f64::consts::PI + 0.01)));
+
+        // nearly the same rotations with different roll and yaw near the gimbal lock
+        let pitched = |roll, yaw| {
+            na::Isometry3::from_parts(
+                na::Translation3::identity(),
+                na::UnitQuaternion::from_euler_angles(
+                    roll,
+                    std::f64::consts::FRAC_PI_2 - 1e-6,
+                    yaw,
+                ),
+            )
+        };
+        map.insert(&pitched(0.5, 0.5));
+        assert!(map.is_reachable(&pitched(0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_nearest_reachable() {
+        let map = ReachabilityMap::new(na::Vector3::zeros(), 0.1);
+        assert!(map.nearest_reachable(&pose(0.0, 0.0, 0.0), 1.0).is_none());
+
+        let map = ReachabilityMap::from_poses(
+            &[pose(0.0, 0.0, 0.0), pose(0.5, 0.0, 0.0)],
+            na::Vector3::zeros(),
+            0.1,
+        );
+        let target = na::Isometry3::new(
+            na::Vector3::new(0.8, 0.1, 0.0),
+            na::Vector3::new(0.0, 0.0, 1.0),
+        );
+        let nearest = map.nearest_reachable(&target, 1.0).unwrap();
+        assert!((nearest.translation.vector - na::Vector3::new(0.5, 0.0, 0.0)).norm() < 1e-9);
+        // orientations are ignored
+        assert!(nearest.rotation.angle_to(&target.rotation) < 1e-9);
+
+        let mut map = ReachabilityMap::with_angle_resolution(na::Vector3::zeros(), 0.1, 0.2);
+        let rotated = |x, yaw| {
+            na::Isometry3::new(
+                na::Vector3::new(x, 0.0, 0.0),
+                na::Vector3::new(0.0, 0.0, yaw),
+            )
+        };
+        map.insert(&rotated(0.0, 0.0));
+        map.insert(&rotated(0.3, 1.0));
+        let target = rotated(0.1, 1.0);
+        // the nearest by translation
+        let nearest = map.nearest_reachable(&target, 0.0).unwrap();
+        assert!(nearest.translation.vector.norm() < 1e-9);
+        // the nearest by orientation
+        let nearest = map.nearest_reachable(&target, 1.0).unwrap();
+        assert!((nearest.translation.vector.x - 0.3).abs() < 1e-9);
+        assert!(nearest.rotation.angle_to(&target.rotation) < 0.2);
+        assert!(map.is_reachable(&nearest));
+    }
+
+    #[test]
+    fn test_reachable_region_hull() {
+        assert!(reachable_region_hull(&[]).is_empty());
+
+        let mut poses = vec![];
+        for x in [0.0, 1.0] {
+            for y in [0.0, 1.0] {
+                for z in [0.0, 1.0] {
+                    poses.push(pose(x, y, z));
+                }
+            }
+        }
+        // inner points are not on the hull
+        poses.push(pose(0.5, 0.5, 0.5));
+        poses.push(pose(0.2, 0.7, 0.4));
+        let hull = reachable_region_hull(&poses);
+        assert_eq!(hull.len(), 8);
+        for p in &poses[..8] {
+            assert!(hull
+                .iter()
+                .any(|v| (v.coords - p.translation.vector).norm() < 1e-6));
+        }
+    }
+
+    #[test]
+    fn test_scan_state_save_load() {
+        let path = std::env::temp_dir().join(format!(
+            "openrr-planner-test-scan-state-{}.json",
+            std::process::id()
+        ));
+        let mut state = ReachabilityScanState::new([0.0; 3], [1.0; 3], 0.1);
+        state.insert([0, 1, 2], true);
+        state.insert([1, 1, 2], false);
+        state.save(&path).unwrap();
+        let loaded = ReachabilityScanState::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, state);
+        assert_eq!(loaded.num_tested(), 2);
+        assert!(loaded.is_tested(&[1, 1, 2]));
+        assert!(!loaded.is_tested(&[2, 1, 2]));
+        assert_eq!(loaded.reachable().collect::<Vec<_>>(), vec![&[0, 1, 2]]);
+        assert!(loaded.is_compatible([0.0; 3], [1.0; 3], 0.1));
+        assert!(!loaded.is_compatible([0.0; 3], [1.0; 3], 0.2));
+    }
+
+    #[test]
+    fn test_grid_mismatch() {
+        let grid = ReachabilityGrid::new(na::Vector3::zeros(), 0.1);
+        assert!(grid
+            .union(&ReachabilityGrid::new(na::Vector3::zeros(), 0.2))
+            .is_err());
+        let shifted = ReachabilityGrid::new(na::Vector3::new(0.05, 0.0, 0.0), 0.1);
+        assert!(grid.intersection(&shifted).is_err());
+        assert!(grid.difference(&shifted).is_err());
+        assert!(grid.union(&grid).is_ok());
+    }
+}
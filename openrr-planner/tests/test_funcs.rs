@@ -117,3 +117,163 @@ fn test_interpolation_fail() {
     // do not interpolate minus duration (no extrapolate)
     assert!(interpolate(&[vec![0.0], vec![2.0]], 1.0, -0.1).is_none());
 }
+
+#[test]
+fn test_limits_from_urdf() {
+    let urdf_robot = urdf_rs::read_file("sample.urdf").unwrap();
+    let robot = k::Chain::<f64>::from(&urdf_robot);
+    let arm = k::SerialChain::from_end(robot.find("l_tool_fixed").unwrap());
+
+    let velocity_limits = velocity_limits_from_urdf(&urdf_robot, &arm);
+    assert_eq!(velocity_limits.len(), arm.dof());
+    assert!(velocity_limits.iter().all(|limit| *limit == Some(1.0)));
+    // effort="0" is regarded as unspecified
+    let effort_limits = effort_limits_from_urdf(&urdf_robot, &arm);
+    assert_eq!(effort_limits, vec![None; arm.dof()]);
+}
+
+#[test]
+fn test_joint_distance() {
+    use std::f64::consts::PI;
+
+    let limits = vec![None, Some(k::joint::Range::new(-PI, PI))];
+    assert_approx_eq!(joint_distance(&[0.0, 0.0], &[0.0, 0.0], &limits), 0.0);
+    assert_approx_eq!(joint_distance(&[0.0, 0.3], &[0.0, -0.1], &limits), 0.4);
+    // continuous joint wraps around
+    assert_approx_eq!(
+        joint_distance(&[PI - 0.1, 0.0], &[-PI + 0.1, 0.0], &limits),
+        0.2
+    );
+    assert_approx_eq!(
+        joint_distance(&[4.0 * PI + 0.1, 0.0], &[0.0, 0.0], &limits),
+        0.1
+    );
+    // limited joint doesn't wrap around
+    assert_approx_eq!(
+        joint_distance(&[0.0, PI - 0.1], &[0.0, -PI + 0.1], &limits),
+        2.0 * PI - 0.2
+    );
+
+    let candidates = vec![vec![0.0, 1.0], vec![PI - 0.1, 0.5], vec![0.0, -1.0]];
+    assert_eq!(
+        nearest_configuration(&candidates, &[-PI + 0.1, 0.5], &limits),
+        Some(1)
+    );
+    assert_eq!(
+        nearest_configuration(&candidates, &[0.0, -0.8], &limits),
+        Some(2)
+    );
+    assert_eq!(nearest_configuration(&[], &[0.0, 0.0], &limits), None);
+}
+
+#[test]
+fn test_preview_trajectory() {
+    let robot = k::Chain::<f64>::from_urdf_file("sample.urdf").unwrap();
+    let arm = k::SerialChain::from_end(robot.find("l_tool_fixed").unwrap());
+    let initial_positions = vec![0.1, 0.2, 0.0, -0.5, 0.0, -0.3];
+    arm.set_joint_positions(&initial_positions).unwrap();
+
+    let positions = [vec![0.0; arm.dof()], vec![0.2, 0.2, 0.0, -1.0, 0.0, 0.0]];
+    let trajectory = positions
+        .iter()
+        .map(|p| TrajectoryPoint::new(p.clone(), vec![0.0; arm.dof()], vec![0.0; arm.dof()]))
+        .collect::<Vec<_>>();
+    let transforms = preview_trajectory(&arm, &trajectory).unwrap();
+    assert_eq!(transforms.len(), 2);
+    assert_eq!(arm.joint_positions(), initial_positions);
+    for (position, transform) in positions.iter().zip(&transforms) {
+        arm.set_joint_positions(position).unwrap();
+        assert!(
+            (arm.end_transform().translation.vector - transform.translation.vector).norm() < 1e-9
+        );
+    }
+
+    // out of the joint limits
+    arm.set_joint_positions(&initial_positions).unwrap();
+    let invalid = vec![TrajectoryPoint::new(vec![100.0; arm.dof()], vec![], vec![])];
+    assert!(preview_trajectory(&arm, &invalid).is_err());
+    assert_eq!(arm.joint_positions(), initial_positions);
+}
+
+#[test]
+fn test_random_joint_positions_biased() {
+    use std::f64::consts::PI;
+
+    let limits = vec![
+        None,
+        Some(k::joint::Range::new(-1.0, 1.0)),
+        Some(k::joint::Range::new(0.0, 0.1)),
+    ];
+    let nominal = [2.0 * PI, 0.5, 0.1];
+    let num_samples = 1000;
+    let mut sum = [0.0; 3];
+    for _ in 0..num_samples {
+        let angles =
+            generate_random_joint_positions_from_limits_biased(&limits, &nominal, 0.1).unwrap();
+        assert_eq!(angles.len(), limits.len());
+        assert!(angles[1] >= -1.0 && angles[1] <= 1.0);
+        assert!(angles[2] >= 0.0 && angles[2] <= 0.1);
+        for (sum, angle) in sum.iter_mut().zip(&angles) {
+            *sum += angle;
+        }
+    }
+    assert!((sum[0] / num_samples as f64 - 2.0 * PI).abs() < 0.05);
+    assert!((sum[1] / num_samples as f64 - 0.5).abs() < 0.05);
+
+    // without spread
+    let angles =
+        generate_random_joint_positions_from_limits_biased(&limits, &[1.0, 2.0, 0.05], 0.0)
+            .unwrap();
+    assert_eq!(angles, vec![1.0, 1.0, 0.05]);
+
+    assert!(matches!(
+        generate_random_joint_positions_from_limits_biased(&limits, &[1.0, 2.0], 0.0),
+        Err(Error::DofMismatch(2, 3))
+    ));
+}
+
+#[test]
+fn test_min_jerk_trajectory() {
+    let start = [0.0, 1.0, -0.5];
+    let end = [2.0, 0.0, -0.5];
+    let points = min_jerk_trajectory(&start, &end, 2.0, 0.1).unwrap();
+    assert_eq!(points.len(), 21);
+
+    // exact start and goal, at rest
+    let (first, last) = (&points[0], points.last().unwrap());
+    assert_eq!(first.position, start.to_vec());
+    assert_eq!(last.position, end.to_vec());
+    for point in [first, last] {
+        for i in 0..start.len() {
+            assert_approx_eq!(point.velocity[i], 0.0);
+            assert_approx_eq!(point.acceleration[i], 0.0);
+        }
+    }
+
+    // symmetric around the middle, with the peak velocity of 1.875 * distance / duration
+    let middle = &points[10];
+    assert_approx_eq!(middle.position[0], 1.0);
+    assert_approx_eq!(middle.velocity[0], 1.875);
+    assert_approx_eq!(middle.velocity[1], -0.9375);
+    assert_approx_eq!(middle.acceleration[0], 0.0);
+    // a joint which doesn't move stays still
+    for point in &points {
+        assert_approx_eq!(point.position[2], -0.5);
+        assert_approx_eq!(point.velocity[2], 0.0);
+    }
+    // monotonic motion
+    for pair in points.windows(2) {
+        assert!(pair[0].position[0] <= pair[1].position[0]);
+        assert!(pair[0].position[1] >= pair[1].position[1]);
+    }
+
+    // zero or non-finite durations, too many points and mismatched lengths are rejected
+    assert!(min_jerk_trajectory(&start, &end, 0.0, 0.1).is_err());
+    assert!(min_jerk_trajectory(&start, &end, 1.0, 0.0).is_err());
+    assert!(min_jerk_trajectory(&start, &end, f64::NAN, 0.1).is_err());
+    assert!(min_jerk_trajectory(&start, &end, f64::INFINITY, 0.1).is_err());
+    assert!(min_jerk_trajectory(&start, &end, 1.0, f64::NAN).is_err());
+    assert!(min_jerk_trajectory(&start, &end, 1.0, f64::INFINITY).is_err());
+    assert!(min_jerk_trajectory(&start, &end, 1.0, 1e-9).is_err());
+    assert!(min_jerk_trajectory(&start, &end[..2], 1.0, 0.1).is_err());
+}
@@ -282,6 +282,12 @@ impl arci::JointTrajectoryClient for JointTrajectoryClientProxy {
                 .into(),
         )
     }
+    fn hold_current_position(
+        &self,
+        duration: std::time::Duration,
+    ) -> Result<WaitFuture, Error> {
+        Ok(self.0.hold_current_position(duration.into()).into_result()?.into())
+    }
 }
 impl std::fmt::Debug for JointTrajectoryClientProxy {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
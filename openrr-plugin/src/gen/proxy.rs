@@ -205,6 +205,7 @@ pub(crate) trait RJointTrajectoryClientTrait: Send + Sync + 'static {
         &self,
         trajectory: RVec<RTrajectoryPoint>,
     ) -> RResult<RWaitFuture, RError>;
+    fn hold_current_position(&self, duration: RDuration) -> RResult<RWaitFuture, RError>;
 }
 impl<T> RJointTrajectoryClientTrait for T
 where
@@ -248,6 +249,18 @@ where
                 .into(),
         )
     }
+    fn hold_current_position(
+        &self,
+        duration: RDuration,
+    ) -> RResult<RWaitFuture, RError> {
+        let _guard = crate::TOKIO.enter();
+        ROk(
+            rtry!(
+                arci::JointTrajectoryClient::hold_current_position(self, duration.into())
+            )
+                .into(),
+        )
+    }
 }
 pub(crate) type LaserScan2DTraitObject = RLaserScan2DTrait_TO<RBox<()>>;
 #[abi_stable::sabi_trait]
@@ -479,6 +492,8 @@ pub(crate) struct RScan2D {
     range_max: f64,
     ranges: RVec<f64>,
     intensities: RVec<f64>,
+    stamp: ROption<RSystemTime>,
+    frame_id: ROption<RString>,
 }
 impl From<arci::Scan2D> for RScan2D {
     fn from(v: arci::Scan2D) -> Self {
@@ -492,6 +507,9 @@ impl From<arci::Scan2D> for RScan2D {
             range_max,
             ranges,
             intensities,
+            stamp,
+            frame_id,
+            ..
         } = v;
         Self {
             angle_min,
@@ -503,6 +521,8 @@ impl From<arci::Scan2D> for RScan2D {
             range_max,
             ranges: ranges.into_iter().collect(),
             intensities: intensities.into_iter().collect(),
+            stamp: stamp.and_then(|v| v.try_into().ok()).into(),
+            frame_id: frame_id.map(Into::into).into(),
         }
     }
 }
@@ -518,18 +538,22 @@ impl From<RScan2D> for arci::Scan2D {
             range_max,
             ranges,
             intensities,
+            stamp,
+            frame_id,
         } = v;
-        Self {
-            angle_min,
-            angle_max,
-            angle_increment,
-            time_increment,
-            scan_time,
-            range_min,
-            range_max,
-            ranges: ranges.into_iter().collect(),
-            intensities: intensities.into_iter().collect(),
-        }
+        let mut r = Self::default();
+        r.angle_min = angle_min;
+        r.angle_max = angle_max;
+        r.angle_increment = angle_increment;
+        r.time_increment = time_increment;
+        r.scan_time = scan_time;
+        r.range_min = range_min;
+        r.range_max = range_max;
+        r.ranges = ranges.into_iter().collect();
+        r.intensities = intensities.into_iter().collect();
+        r.stamp = stamp.into_option().and_then(|v| v.try_into().ok());
+        r.frame_id = frame_id.into_option().map(Into::into);
+        r
     }
 }
 /// FFI-safe equivalent of [`arci::BaseVelocity`].
@@ -454,22 +454,26 @@ impl From<arci::Scan2D> for pb::Scan2D {
             range_max: val.range_max,
             ranges: val.ranges,
             intensities: val.intensities,
+            stamp: val.stamp.map(Into::into),
+            frame_id: val.frame_id,
         }
     }
 }
 
 impl From<pb::Scan2D> for arci::Scan2D {
     fn from(val: pb::Scan2D) -> Self {
-        Self {
-            angle_min: val.angle_min,
-            angle_max: val.angle_max,
-            angle_increment: val.angle_increment,
-            time_increment: val.time_increment,
-            scan_time: val.scan_time,
-            range_min: val.range_min,
-            range_max: val.range_max,
-            ranges: val.ranges,
-            intensities: val.intensities,
-        }
+        let mut scan = Self::default();
+        scan.angle_min = val.angle_min;
+        scan.angle_max = val.angle_max;
+        scan.angle_increment = val.angle_increment;
+        scan.time_increment = val.time_increment;
+        scan.scan_time = val.scan_time;
+        scan.range_min = val.range_min;
+        scan.range_max = val.range_max;
+        scan.ranges = val.ranges;
+        scan.intensities = val.intensities;
+        scan.stamp = val.stamp.and_then(|stamp| stamp.try_into().ok());
+        scan.frame_id = val.frame_id;
+        scan
     }
 }
@@ -375,19 +375,13 @@ async fn multiple() -> Result<()> {
 
     let scan = laser_scan.current_scan()?;
     assert_eq!(scan, Scan2D::default());
-    recv_laser_scan.set_scan(Scan2D {
-        angle_min: 1.0,
-        angle_max: 2.0,
-        ..Default::default()
-    });
-    assert_eq!(
-        laser_scan.current_scan()?,
-        Scan2D {
-            angle_min: 1.0,
-            angle_max: 2.0,
-            ..Default::default()
-        }
-    );
+    let mut scan = Scan2D::default();
+    scan.angle_min = 1.0;
+    scan.angle_max = 2.0;
+    scan.stamp = Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1));
+    scan.frame_id = Some("laser".to_owned());
+    recv_laser_scan.set_scan(scan.clone());
+    assert_eq!(laser_scan.current_scan()?, scan);
 
     Ok(())
 }
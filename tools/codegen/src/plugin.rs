@@ -16,8 +16,8 @@ use syn::{
 use super::*;
 
 pub(crate) fn gen(workspace_root: &Path) -> Result<()> {
-    const FULLY_IGNORE: &[&str] = &["SetCompleteCondition"];
-    const IGNORE: &[&str] = &["SetCompleteCondition"];
+    const FULLY_IGNORE: &[&str] = &["SetCompleteCondition", "Sensor"];
+    const IGNORE: &[&str] = &["SetCompleteCondition", "Sensor"];
     const USE_TRY_INTO: &[&str] = &["SystemTime"];
 
     let out_dir = &workspace_root.join("openrr-plugin/src/gen");
@@ -214,22 +214,18 @@ pub(crate) fn gen(workspace_root: &Path) -> Result<()> {
             }
         });
     }
+    /// Returns the expressions converting the field from and to arci.
     fn map_field(
         pats: &mut Vec<TokenStream>,
-        from_arci: &mut Vec<TokenStream>,
-        to_arci: &mut Vec<TokenStream>,
         syn::Field { ident, ty, .. }: &syn::Field,
         index: usize,
-    ) {
+    ) -> (TokenStream, TokenStream) {
         let index_or_ident = ident
             .clone()
             .unwrap_or_else(|| format_ident!("field{}", index));
-        let pat = quote! { #index_or_ident, };
-        pats.push(pat.clone());
+        pats.push(quote! { #index_or_ident, });
         if is_primitive(ty) {
-            from_arci.push(pat.clone());
-            to_arci.push(pat);
-            return;
+            return (quote! { #index_or_ident }, quote! { #index_or_ident });
         }
         if let Some(ty) = is_option(ty) {
             if let Some(ty) = is_vec(ty) {
@@ -238,36 +234,58 @@ pub(crate) fn gen(workspace_root: &Path) -> Result<()> {
                 } else {
                     quote! { map(|v| v.into_iter().map(Into::into).collect()) }
                 };
-                let mut to = quote! { #index_or_ident.into_option().#t, };
-                let mut from = quote! { #index_or_ident.#t.into(), };
-                if ident.is_some() {
-                    from = quote! { #ident: #from };
-                    to = quote! { #ident: #to };
-                }
-                from_arci.push(from);
-                to_arci.push(to);
-                return;
+                return (
+                    quote! { #index_or_ident.#t.into() },
+                    quote! { #index_or_ident.into_option().#t },
+                );
             }
+            let use_try_into = get_ty_path(ty).map_or(false, |path| {
+                USE_TRY_INTO.contains(&&*path.segments.last().unwrap().ident.to_string())
+            });
+            let t = if use_try_into {
+                // Values which can't be converted are dropped.
+                quote! { and_then(|v| v.try_into().ok()) }
+            } else {
+                quote! { map(Into::into) }
+            };
+            return (
+                quote! { #index_or_ident.#t.into() },
+                quote! { #index_or_ident.into_option().#t },
+            );
         }
         if let Some(ty) = is_vec(ty) {
-            let mut t = if is_primitive(ty) {
-                quote! { #index_or_ident.into_iter().collect(), }
+            let t = if is_primitive(ty) {
+                quote! { #index_or_ident.into_iter().collect() }
             } else {
-                quote! { #index_or_ident.into_iter().map(Into::into).collect(), }
+                quote! { #index_or_ident.into_iter().map(Into::into).collect() }
             };
-            if ident.is_some() {
-                t = quote! { #ident: #t };
-            }
-            from_arci.push(t.clone());
-            to_arci.push(t);
-            return;
+            return (t.clone(), t);
         }
-        let mut t = quote! { #index_or_ident.into(), };
-        if ident.is_some() {
-            t = quote! { #ident: #t };
+        let t = quote! { #index_or_ident.into() };
+        (t.clone(), t)
+    }
+    /// Pushes the field initializers of the converted fields.
+    fn push_fields(
+        from_arci: &mut Vec<TokenStream>,
+        to_arci: &mut Vec<TokenStream>,
+        field: &syn::Field,
+        (from, to): (TokenStream, TokenStream),
+    ) {
+        match &field.ident {
+            // Use the shorthand for the fields which are not converted.
+            Some(ident) if from.to_string() == ident.to_string() => {
+                from_arci.push(quote! { #ident, });
+                to_arci.push(quote! { #ident, });
+            }
+            Some(ident) => {
+                from_arci.push(quote! { #ident: #from, });
+                to_arci.push(quote! { #ident: #to, });
+            }
+            None => {
+                from_arci.push(quote! { #from, });
+                to_arci.push(quote! { #to, });
+            }
         }
-        from_arci.push(t.clone());
-        to_arci.push(t);
     }
     // Generate R* structs.
     for item in arci_structs {
@@ -286,9 +304,31 @@ pub(crate) fn gen(workspace_root: &Path) -> Result<()> {
         let mut pats = vec![];
         let mut from_arci = vec![];
         let mut to_arci = vec![];
+        let mut assign_to_arci = vec![];
         for (i, field) in item.fields.iter().enumerate() {
-            map_field(&mut pats, &mut from_arci, &mut to_arci, field, i);
+            let (from, to) = map_field(&mut pats, field, i);
+            let ident = &field.ident;
+            assign_to_arci.push(quote! { r.#ident = #to; });
+            push_fields(&mut from_arci, &mut to_arci, field, (from, to));
         }
+        // Structs marked `#[non_exhaustive]` can't be constructed or fully
+        // destructured outside arci, so they are converted through `Default`.
+        let non_exhaustive = item
+            .attrs
+            .iter()
+            .any(|attr| attr.path().is_ident("non_exhaustive"));
+        let (rest, construct_arci) = if non_exhaustive {
+            (
+                quote! { .. },
+                quote! {
+                    let mut r = Self::default();
+                    #(#assign_to_arci)*
+                    r
+                },
+            )
+        } else {
+            (quote! {}, quote! { Self { #(#to_arci)* } })
+        };
         proxy_impls.extend(quote! {
             #[doc = #struct_doc]
             #[derive(StableAbi)]
@@ -298,14 +338,14 @@ pub(crate) fn gen(workspace_root: &Path) -> Result<()> {
             }
             impl From<#arci_path> for #r_name {
                 fn from(v: #arci_path) -> Self {
-                    let #arci_path { #(#pats)* } = v;
+                    let #arci_path { #(#pats)* #rest } = v;
                     Self { #(#from_arci)* }
                 }
             }
             impl From<#r_name> for #arci_path {
                 fn from(v: #r_name) -> Self {
                     let #r_name { #(#pats)* } = v;
-                    Self { #(#to_arci)* }
+                    #construct_arci
                 }
             }
         });
@@ -328,13 +368,8 @@ pub(crate) fn gen(workspace_root: &Path) -> Result<()> {
             let mut from_arci_fields = vec![];
             let mut to_arci_fields = vec![];
             for (i, field) in v.fields.iter().enumerate() {
-                map_field(
-                    &mut pats_fields,
-                    &mut from_arci_fields,
-                    &mut to_arci_fields,
-                    field,
-                    i,
-                );
+                let converted = map_field(&mut pats_fields, field, i);
+                push_fields(&mut from_arci_fields, &mut to_arci_fields, field, converted);
             }
             match &v.fields {
                 syn::Fields::Named(..) => {
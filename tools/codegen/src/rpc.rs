@@ -15,8 +15,13 @@ use syn::{
 use super::*;
 
 pub(crate) fn gen(workspace_root: &Path) -> Result<()> {
-    const FULLY_IGNORE: &[&str] = &["SetCompleteCondition"];
-    const IGNORE: &[&str] = &["JointTrajectoryClient", "SetCompleteCondition", "Gamepad"];
+    const FULLY_IGNORE: &[&str] = &["SetCompleteCondition", "Sensor"];
+    const IGNORE: &[&str] = &[
+        "JointTrajectoryClient",
+        "SetCompleteCondition",
+        "Gamepad",
+        "Sensor",
+    ];
 
     let out_dir = &workspace_root.join("openrr-remote/src/gen");
     fs::create_dir_all(out_dir)?;